@@ -6,112 +6,405 @@
 use anyhow::{anyhow, Result};
 use cpal::{
     traits::{DeviceTrait, HostTrait, StreamTrait},
-    Device, Stream, StreamConfig,
+    Device, FromSample, Sample, SampleFormat, SizedSample, Stream, StreamConfig,
+    SupportedStreamConfig,
 };
+use std::ops::Range;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-const SAMPLE_RATE: u32 = 16000;
-const CHANNELS: u16 = 1;
+use crate::resample::{convert_channels, Resampler};
+use crate::ring_buffer::AudioRingBuffer;
+
+const DEFAULT_SAMPLE_RATE: u32 = 16000;
+const DEFAULT_CHANNELS: u16 = 1;
+
+/// Input sample formats the recorder can convert, in order of preference
+const SUPPORTED_INPUT_FORMATS: [SampleFormat; 4] = [
+    SampleFormat::F32,
+    SampleFormat::I16,
+    SampleFormat::I32,
+    SampleFormat::U16,
+];
 
 // Memory management constants
-const MAX_RECORDING_DURATION_SECONDS: usize = 300; // 5 minutes max
-const MAX_BUFFER_SIZE: usize = SAMPLE_RATE as usize * MAX_RECORDING_DURATION_SECONDS;
+const DEFAULT_BUFFER_DURATION_SECONDS: usize = 300; // 5 minutes max
+const DEFAULT_PRE_ROLL_MS: u64 = 500;
+
+// Scratch space reserved for conversion so typical callbacks never reallocate
+const SCRATCH_CAPACITY: usize = 48000 * 2 / 10; // 100ms of 48kHz stereo
+
+/// Target format for captured audio
+/// The device is opened at its native format and converted to this one
+#[derive(Debug, Clone)]
+pub struct RecorderConfig {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Input device name (exact or substring) or index; `None` uses the system default
+    pub device: Option<String>,
+    /// Length of captured audio retained before the oldest samples are overwritten
+    pub buffer_duration_seconds: usize,
+    /// Audio kept from before the recording was triggered, see `AudioRecorder::trim_pre_roll`
+    pub pre_roll: Duration,
+}
+
+impl Default for RecorderConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            channels: DEFAULT_CHANNELS,
+            device: None,
+            buffer_duration_seconds: DEFAULT_BUFFER_DURATION_SECONDS,
+            pre_roll: Duration::from_millis(DEFAULT_PRE_ROLL_MS),
+        }
+    }
+}
+
+/// Description of an available input device, as printed by `--list-devices`
+#[derive(Debug, Clone)]
+pub struct InputDeviceInfo {
+    pub index: usize,
+    pub name: String,
+    pub is_default: bool,
+    pub configs: Vec<String>,
+}
+
+/// Enumerate input devices of the default host with their supported configs
+pub fn list_input_devices() -> Result<Vec<InputDeviceInfo>> {
+    let host = cpal::default_host();
+    let default_name = host
+        .default_input_device()
+        .and_then(|device| device.name().ok());
+
+    let mut devices = Vec::new();
+    for (index, device) in host.input_devices()?.enumerate() {
+        let name = device.name().unwrap_or("Unknown".to_string());
+        let configs = match device.supported_input_configs() {
+            Ok(configs) => configs
+                .map(|config| {
+                    format!(
+                        "{}, {} channels, {}-{} Hz",
+                        config.sample_format(),
+                        config.channels(),
+                        config.min_sample_rate().0,
+                        config.max_sample_rate().0
+                    )
+                })
+                .collect(),
+            Err(e) => vec![format!("unavailable: {}", e)],
+        };
+
+        devices.push(InputDeviceInfo {
+            index,
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            name,
+            configs,
+        });
+    }
+
+    Ok(devices)
+}
+
+/// Resolve a device selector against a list of device names
+/// Accepts an index, an exact name, or a case-insensitive substring of a name.
+fn match_device(names: &[String], selector: &str) -> Option<usize> {
+    let selector = selector.trim();
+
+    if let Ok(index) = selector.parse::<usize>() {
+        if index < names.len() {
+            return Some(index);
+        }
+    }
+
+    if let Some(index) = names.iter().position(|name| name == selector) {
+        return Some(index);
+    }
+
+    let needle = selector.to_lowercase();
+    names
+        .iter()
+        .position(|name| name.to_lowercase().contains(&needle))
+}
+
+/// Open the input device matching `selector`, or the default input device
+fn find_input_device(host: &cpal::Host, selector: Option<&str>) -> Result<Device> {
+    let Some(selector) = selector else {
+        return host
+            .default_input_device()
+            .ok_or_else(|| anyhow!("No default input device available"));
+    };
+
+    let devices: Vec<Device> = host.input_devices()?.collect();
+    let names: Vec<String> = devices
+        .iter()
+        .map(|device| device.name().unwrap_or_default())
+        .collect();
+
+    match_device(&names, selector)
+        .and_then(|index| devices.into_iter().nth(index))
+        .ok_or_else(|| {
+            anyhow!(
+                "No input device matches '{}'. Run with --list-devices to see available devices",
+                selector
+            )
+        })
+}
+
+/// Converts native device frames into the target format and appends them to the shared buffer
+struct CaptureProcessor {
+    device_channels: u16,
+    target_channels: u16,
+    resampler: Resampler,
+    buffer: Arc<AudioRingBuffer>,
+    converted: Vec<f32>,
+    mixed: Vec<f32>,
+    resampled: Vec<f32>,
+}
+
+impl CaptureProcessor {
+    fn new(
+        device_rate: u32,
+        device_channels: u16,
+        target: &RecorderConfig,
+        buffer: Arc<AudioRingBuffer>,
+    ) -> Self {
+        Self {
+            device_channels,
+            target_channels: target.channels,
+            resampler: Resampler::new(device_rate, target.sample_rate, target.channels),
+            buffer,
+            converted: Vec::with_capacity(SCRATCH_CAPACITY),
+            mixed: Vec::with_capacity(SCRATCH_CAPACITY),
+            resampled: Vec::with_capacity(SCRATCH_CAPACITY),
+        }
+    }
+
+    /// Normalize native samples of any supported format to f32 in [-1.0, 1.0]
+    fn push<T>(&mut self, data: &[T])
+    where
+        T: Sample,
+        f32: FromSample<T>,
+    {
+        self.converted.clear();
+        self.converted
+            .extend(data.iter().map(|&sample| f32::from_sample(sample)));
+
+        self.mixed.clear();
+        convert_channels(
+            &self.converted,
+            self.device_channels,
+            self.target_channels,
+            &mut self.mixed,
+        );
+
+        self.resampled.clear();
+        self.resampler.process(&self.mixed, &mut self.resampled);
+
+        let first_samples = self.buffer.written() == 0 && !self.resampled.is_empty();
+        self.buffer.push(&self.resampled);
+
+        if first_samples {
+            eprintln!(
+                "🎤 First audio samples captured! Got {} samples",
+                self.resampled.len()
+            );
+        }
+    }
+}
+
+/// Ring buffer capacity in samples for the configured format and duration
+fn max_buffer_size(config: &RecorderConfig) -> usize {
+    config.sample_rate as usize * config.channels as usize * config.buffer_duration_seconds
+}
+
+/// Pick the device input config closest to the target format
+/// Prefers a range that contains the target rate so no resampling is needed,
+/// otherwise falls back to the device default rate.
+fn select_input_config(device: &Device, target: &RecorderConfig) -> Result<SupportedStreamConfig> {
+    let mut ranges: Vec<_> = device
+        .supported_input_configs()?
+        .filter(|config| SUPPORTED_INPUT_FORMATS.contains(&config.sample_format()))
+        .collect();
+    ranges.sort_by_key(|config| format_preference(config.sample_format()));
+
+    let target_rate = cpal::SampleRate(target.sample_rate);
+    if let Some(range) = ranges.iter().find(|config| {
+        config.channels() == target.channels
+            && config.min_sample_rate() <= target_rate
+            && config.max_sample_rate() >= target_rate
+    }) {
+        return Ok((*range).with_sample_rate(target_rate));
+    }
+
+    if let Some(range) = ranges.iter().find(|config| {
+        config.min_sample_rate() <= target_rate && config.max_sample_rate() >= target_rate
+    }) {
+        return Ok((*range).with_sample_rate(target_rate));
+    }
+
+    if let Ok(default) = device.default_input_config() {
+        if SUPPORTED_INPUT_FORMATS.contains(&default.sample_format()) {
+            return Ok(default);
+        }
+    }
+
+    ranges
+        .into_iter()
+        .next()
+        .map(cpal::SupportedStreamConfigRange::with_max_sample_rate)
+        .ok_or_else(|| anyhow!("No suitable audio format found"))
+}
+
+fn format_preference(format: SampleFormat) -> usize {
+    SUPPORTED_INPUT_FORMATS
+        .iter()
+        .position(|supported| *supported == format)
+        .unwrap_or(SUPPORTED_INPUT_FORMATS.len())
+}
+
+/// Build an input stream delivering native `T` samples to the capture processor
+fn build_input_stream<T>(
+    device: &Device,
+    config: &StreamConfig,
+    mut processor: CaptureProcessor,
+) -> Result<Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            processor.push(data);
+        },
+        |err| {
+            eprintln!("❌ Audio stream error: {}", err);
+        },
+        None,
+    )?;
+    Ok(stream)
+}
 
 pub struct AudioRecorder {
-    buffer: Arc<Mutex<Vec<f32>>>,
+    config: RecorderConfig,
+    buffer: Arc<AudioRingBuffer>,
     is_recording: Arc<AtomicBool>,
     stream: Option<Stream>,
     device: Option<Device>,
+    /// Keep the stream open between recordings, see `start_standby`
+    standby: bool,
+    /// Position where the last recording stopped while the stream kept capturing
+    recording_end: Option<u64>,
+    /// Samples read back as silence, such as the start beep
+    muted: Option<Range<u64>>,
 }
 
 impl AudioRecorder {
-    pub fn new() -> Result<Self> {
+    pub fn new(config: RecorderConfig) -> Result<Self> {
+        if config.sample_rate == 0 || config.channels == 0 || config.buffer_duration_seconds == 0 {
+            return Err(anyhow!(
+                "Invalid recorder format: {}Hz, {} channels, {}s buffer",
+                config.sample_rate,
+                config.channels,
+                config.buffer_duration_seconds
+            ));
+        }
+
         Ok(Self {
-            buffer: Arc::new(Mutex::new(Vec::new())),
+            buffer: Arc::new(AudioRingBuffer::new(max_buffer_size(&config))),
+            config,
             is_recording: Arc::new(AtomicBool::new(false)),
             stream: None,
             device: None,
+            standby: false,
+            recording_end: None,
+            muted: None,
         })
     }
 
+    /// Sample rate of the captured audio returned by `get_audio_data`
+    pub fn sample_rate(&self) -> u32 {
+        self.config.sample_rate
+    }
+
+    /// Whether audio is currently being captured
+    pub fn is_recording(&self) -> bool {
+        self.is_recording.load(Ordering::Relaxed)
+    }
+
+    /// Open the microphone without recording and keep it open until dropped
+    /// Audio captured while idle stays in the ring buffer, so a recording
+    /// started later can keep the pre-roll from before its trigger, and
+    /// `stop_recording` ends a recording without closing the device.
+    pub fn start_standby(&mut self) -> Result<()> {
+        if self.stream.is_none() {
+            self.open_stream()?;
+        }
+        self.standby = true;
+        // Nothing captured before the first recording counts as recorded
+        self.recording_end = Some(self.buffer.written());
+        Ok(())
+    }
+
     pub fn start_recording(&mut self) -> Result<()> {
         if self.is_recording.load(Ordering::Relaxed) {
             return Ok(());
         }
 
-        // Get default host and input device
+        if self.stream.is_none() {
+            self.open_stream()?;
+        }
+        self.recording_end = None;
+        self.is_recording.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Open the input device and start capturing into the ring buffer
+    fn open_stream(&mut self) -> Result<()> {
+        // Get default host and the configured (or default) input device
         let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .ok_or_else(|| anyhow!("No default input device available"))?;
+        let device = find_input_device(&host, self.config.device.as_deref())?;
 
         eprintln!(
             "🎤 Using audio device: {}",
             device.name().unwrap_or("Unknown".to_string())
         );
 
-        // Get supported input config close to our target format
-        let mut supported_configs = device.supported_input_configs()?;
-        let _supported_config = supported_configs
-            .find(|config| {
-                config.channels() <= CHANNELS
-                    && config.min_sample_rate().0 <= SAMPLE_RATE
-                    && config.max_sample_rate().0 >= SAMPLE_RATE
-            })
-            .ok_or_else(|| anyhow!("No suitable audio format found"))?;
-
-        let config = StreamConfig {
-            channels: CHANNELS,
-            sample_rate: cpal::SampleRate(SAMPLE_RATE),
-            buffer_size: cpal::BufferSize::Default,
-        };
+        // Open the device at a format it supports natively
+        let supported_config = select_input_config(&device, &self.config)?;
+        let config: StreamConfig = supported_config.config();
+
+        let sample_format = supported_config.sample_format();
 
         eprintln!(
-            "📊 Audio config: {}Hz, {} channels",
-            config.sample_rate.0, config.channels
+            "📊 Audio config: {}Hz, {} channels, {} (target {}Hz, {} channels)",
+            config.sample_rate.0,
+            config.channels,
+            sample_format,
+            self.config.sample_rate,
+            self.config.channels
         );
 
-        // Clone buffer for the stream callback
-        let buffer_clone = Arc::clone(&self.buffer);
-
-        // Create audio input stream
-        let stream = device.build_input_stream(
-            &config,
-            move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                // Process audio data in the callback
-                if let Ok(mut audio_buffer) = buffer_clone.lock() {
-                    // Manage buffer size
-                    if audio_buffer.len() + data.len() > MAX_BUFFER_SIZE {
-                        let samples_to_remove = (audio_buffer.len() + data.len()) - MAX_BUFFER_SIZE;
-                        if samples_to_remove < audio_buffer.len() {
-                            audio_buffer.drain(0..samples_to_remove);
-                        } else {
-                            audio_buffer.clear();
-                        }
-                    }
-
-                    let old_len = audio_buffer.len();
-                    audio_buffer.extend_from_slice(data);
-
-                    if old_len == 0 && !audio_buffer.is_empty() {
-                        eprintln!(
-                            "🎤 First audio samples captured! Got {} samples",
-                            data.len()
-                        );
-                    }
-                }
-            },
-            |err| {
-                eprintln!("❌ Audio stream error: {}", err);
-            },
-            None,
-        )?;
+        let processor = CaptureProcessor::new(
+            config.sample_rate.0,
+            config.channels,
+            &self.config,
+            Arc::clone(&self.buffer),
+        );
+
+        // Create audio input stream for the device's native sample format
+        let stream = match sample_format {
+            SampleFormat::F32 => build_input_stream::<f32>(&device, &config, processor)?,
+            SampleFormat::I16 => build_input_stream::<i16>(&device, &config, processor)?,
+            SampleFormat::I32 => build_input_stream::<i32>(&device, &config, processor)?,
+            SampleFormat::U16 => build_input_stream::<u16>(&device, &config, processor)?,
+            other => return Err(anyhow!("Unsupported input sample format: {}", other)),
+        };
 
         // Start the stream
         stream.play()?;
 
-        self.is_recording.store(true, Ordering::Relaxed);
         self.stream = Some(stream);
         self.device = Some(device);
 
@@ -125,7 +418,15 @@ impl AudioRecorder {
         }
 
         self.is_recording.store(false, Ordering::Relaxed);
+        self.recording_end = Some(self.buffer.written());
+
+        if self.standby {
+            return Ok(());
+        }
+        self.close_stream()
+    }
 
+    fn close_stream(&mut self) -> Result<()> {
         // Stop and drop the stream
         if let Some(stream) = self.stream.take() {
             stream.pause()?;
@@ -137,29 +438,91 @@ impl AudioRecorder {
         Ok(())
     }
 
+    /// Copy the recorded audio, up to where the recording stopped
     pub fn get_audio_data(&self) -> Result<Vec<f32>> {
-        let buffer = self
-            .buffer
-            .lock()
-            .map_err(|_| anyhow!("Failed to lock buffer"))?;
-        Ok(buffer.clone())
+        Ok(self.read_range(0, self.recorded_until()))
+    }
+
+    /// End of the recorded audio: where it stopped, or the capture position
+    fn recorded_until(&self) -> u64 {
+        self.recording_end.unwrap_or_else(|| self.buffer.written())
     }
 
     pub fn clear_buffer(&self) -> Result<()> {
-        let mut buffer = self
-            .buffer
-            .lock()
-            .map_err(|_| anyhow!("Failed to lock buffer"))?;
-        buffer.clear();
+        self.buffer.clear();
         Ok(())
     }
 
-    pub fn get_recording_duration_seconds(&self) -> Result<f32> {
-        let buffer = self
+    /// Absolute position of the next captured sample, for use with `read_since`
+    pub fn position(&self) -> u64 {
+        self.buffer.written()
+    }
+
+    /// Copy the samples captured since absolute position `start`
+    /// Returns the samples and the position to continue reading from; samples
+    /// that were already overwritten are skipped.
+    pub fn read_since(&self, start: u64) -> (Vec<f32>, u64) {
+        let end = self.buffer.written().max(start);
+        (self.read_range(start, end), end)
+    }
+
+    /// Copy the samples captured in the absolute range `[start, end)`
+    /// The range is clipped to the samples that are still retained, and muted
+    /// samples read as silence.
+    pub fn read_range(&self, start: u64, end: u64) -> Vec<f32> {
+        let end = end.min(self.buffer.written());
+        let mut samples = self
             .buffer
-            .lock()
-            .map_err(|_| anyhow!("Failed to lock buffer"))?;
-        Ok(buffer.len() as f32 / SAMPLE_RATE as f32)
+            .read_range(start.max(self.buffer.consumed()), end);
+
+        if let Some(muted) = &self.muted {
+            let first = end - samples.len() as u64;
+            let from = muted.start.clamp(first, end) - first;
+            let to = muted.end.clamp(first, end) - first;
+            samples[from as usize..to as usize].fill(0.0);
+        }
+        samples
+    }
+
+    /// Number of samples captured in `duration`, counting every channel
+    pub fn samples_for(&self, duration: Duration) -> u64 {
+        let per_second = u64::from(self.config.sample_rate) * u64::from(self.config.channels);
+        (per_second * duration.as_millis() as u64 / 1000)
+            .next_multiple_of(u64::from(self.config.channels))
+    }
+
+    /// Discard audio captured more than the configured pre-roll before `position`
+    pub fn trim_pre_roll(&self, position: u64) {
+        self.buffer
+            .consume(position.saturating_sub(self.samples_for(self.config.pre_roll)));
+    }
+
+    /// Read the samples in `range` as silence, even those not captured yet
+    /// Replaces any previously muted range.
+    pub fn mute(&mut self, range: Range<u64>) {
+        self.muted = Some(range);
+    }
+
+    /// Mute the audio captured while a sound was playing, such as the start beep
+    /// `position` is the capture position at instant `at`; `tail` extends the
+    /// muted range past the end of `played` to cover output and input latency.
+    pub fn mute_played(
+        &mut self,
+        position: u64,
+        at: Instant,
+        played: Range<Instant>,
+        tail: Duration,
+    ) {
+        let start = position + self.samples_for(played.start.saturating_duration_since(at));
+        let end = position + self.samples_for(played.end.saturating_duration_since(at) + tail);
+        self.mute(start..end);
+    }
+
+    pub fn get_recording_duration_seconds(&self) -> Result<f32> {
+        let samples_per_second = self.config.sample_rate as f32 * f32::from(self.config.channels);
+        let after_end = self.buffer.written() - self.recorded_until().min(self.buffer.written());
+        let recorded = self.buffer.available().saturating_sub(after_end as usize);
+        Ok(recorded as f32 / samples_per_second)
     }
 
     // Method to process audio events (for compatibility with main loop)
@@ -173,6 +536,7 @@ impl AudioRecorder {
 impl Drop for AudioRecorder {
     fn drop(&mut self) {
         let _ = self.stop_recording();
+        let _ = self.close_stream();
     }
 }
 
@@ -183,20 +547,21 @@ mod tests {
 
     #[test]
     fn test_audio_recorder_creation() {
-        let recorder = AudioRecorder::new();
+        let recorder = AudioRecorder::new(RecorderConfig::default());
         assert!(recorder.is_ok());
     }
 
     #[test]
     fn test_initial_state() {
-        let recorder = AudioRecorder::new().unwrap();
+        let recorder = AudioRecorder::new(RecorderConfig::default()).unwrap();
         let buffer_data = recorder.get_audio_data().unwrap();
         assert_eq!(buffer_data.len(), 0);
+        assert!(!recorder.is_recording());
     }
 
     #[test]
     fn test_buffer_operations() {
-        let recorder = AudioRecorder::new().unwrap();
+        let recorder = AudioRecorder::new(RecorderConfig::default()).unwrap();
 
         // Initially empty
         let data = recorder.get_audio_data().unwrap();
@@ -214,7 +579,7 @@ mod tests {
 
     #[test]
     fn test_recording_lifecycle() {
-        let mut recorder = AudioRecorder::new().unwrap();
+        let mut recorder = AudioRecorder::new(RecorderConfig::default()).unwrap();
 
         // Multiple stop calls should not fail
         assert!(recorder.stop_recording().is_ok());
@@ -223,7 +588,7 @@ mod tests {
 
     #[test]
     fn test_cpal_recording_initialization() {
-        let mut recorder = AudioRecorder::new().unwrap();
+        let mut recorder = AudioRecorder::new(RecorderConfig::default()).unwrap();
 
         // This test attempts to start CPAL recording
         // It may fail if no audio device is available
@@ -252,15 +617,267 @@ mod tests {
 
     #[test]
     fn test_audio_format_constants() {
-        assert_eq!(SAMPLE_RATE, 16000);
-        assert_eq!(CHANNELS, 1);
-        assert_eq!(MAX_RECORDING_DURATION_SECONDS, 300);
-        assert_eq!(MAX_BUFFER_SIZE, 16000 * 300);
+        assert_eq!(DEFAULT_SAMPLE_RATE, 16000);
+        assert_eq!(DEFAULT_CHANNELS, 1);
+        assert_eq!(DEFAULT_BUFFER_DURATION_SECONDS, 300);
+        assert_eq!(max_buffer_size(&RecorderConfig::default()), 16000 * 300);
+    }
+
+    #[test]
+    fn test_recorder_config_format() {
+        let recorder = AudioRecorder::new(RecorderConfig {
+            sample_rate: 44100,
+            channels: 2,
+            device: None,
+            buffer_duration_seconds: 60,
+            ..RecorderConfig::default()
+        })
+        .unwrap();
+        assert_eq!(recorder.sample_rate(), 44100);
+
+        let invalid = AudioRecorder::new(RecorderConfig {
+            sample_rate: 0,
+            channels: 1,
+            ..RecorderConfig::default()
+        });
+        assert!(invalid.is_err());
+
+        let no_buffer = AudioRecorder::new(RecorderConfig {
+            buffer_duration_seconds: 0,
+            ..RecorderConfig::default()
+        });
+        assert!(no_buffer.is_err());
+    }
+
+    #[test]
+    fn test_capture_processor_downmixes_and_resamples() {
+        let buffer = Arc::new(AudioRingBuffer::new(16000));
+        let mut processor =
+            CaptureProcessor::new(48000, 2, &RecorderConfig::default(), Arc::clone(&buffer));
+
+        // 100ms of 48kHz stereo in 10ms callbacks, left and right averaging to 0.25
+        for _ in 0..10 {
+            let frames: Vec<f32> = (0..480).flat_map(|_| [0.5f32, 0.0]).collect();
+            processor.push(&frames);
+        }
+
+        let captured = buffer.snapshot();
+        assert!((captured.len() as i64 - 1600).abs() <= 1);
+        assert!(captured.iter().all(|&s| (s - 0.25).abs() < 1e-6));
+    }
+
+    fn capture_mono(feed: impl FnOnce(&mut CaptureProcessor)) -> Vec<f32> {
+        let buffer = Arc::new(AudioRingBuffer::new(16000));
+        let mut processor =
+            CaptureProcessor::new(16000, 1, &RecorderConfig::default(), Arc::clone(&buffer));
+        feed(&mut processor);
+        buffer.snapshot()
+    }
+
+    #[test]
+    fn test_capture_processor_i16_frames() {
+        let captured = capture_mono(|p| p.push(&[0i16, i16::MAX, i16::MIN, 16384]));
+        assert_eq!(captured.len(), 4);
+        assert!(captured[0].abs() < 1e-6);
+        assert!((captured[1] - 1.0).abs() < 1e-3);
+        assert!((captured[2] + 1.0).abs() < 1e-6);
+        assert!((captured[3] - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_capture_processor_u16_frames() {
+        let captured = capture_mono(|p| p.push(&[32768u16, 0, u16::MAX, 49152]));
+        assert_eq!(captured.len(), 4);
+        assert!(captured[0].abs() < 1e-6);
+        assert!((captured[1] + 1.0).abs() < 1e-6);
+        assert!((captured[2] - 1.0).abs() < 1e-3);
+        assert!((captured[3] - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_capture_processor_i32_frames() {
+        let captured = capture_mono(|p| p.push(&[0i32, i32::MAX, i32::MIN, 1 << 30]));
+        assert_eq!(captured.len(), 4);
+        assert!(captured[0].abs() < 1e-6);
+        assert!((captured[1] - 1.0).abs() < 1e-6);
+        assert!((captured[2] + 1.0).abs() < 1e-6);
+        assert!((captured[3] - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_capture_processor_f32_frames() {
+        let captured = capture_mono(|p| p.push(&[0.0f32, 0.25, -0.75]));
+        assert_eq!(captured, vec![0.0, 0.25, -0.75]);
+    }
+
+    #[test]
+    fn test_capture_processor_i16_stereo_to_mono() {
+        let buffer = Arc::new(AudioRingBuffer::new(16000));
+        let mut processor =
+            CaptureProcessor::new(16000, 2, &RecorderConfig::default(), Arc::clone(&buffer));
+        processor.push(&[16384i16, -16384, i16::MAX, i16::MAX]);
+
+        let captured = buffer.snapshot();
+        assert_eq!(captured.len(), 2);
+        assert!(captured[0].abs() < 1e-3);
+        assert!((captured[1] - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_format_preference() {
+        assert!(format_preference(SampleFormat::F32) < format_preference(SampleFormat::I16));
+        assert!(format_preference(SampleFormat::I16) < format_preference(SampleFormat::U16));
+        assert_eq!(
+            format_preference(SampleFormat::U8),
+            SUPPORTED_INPUT_FORMATS.len()
+        );
+    }
+
+    #[test]
+    fn test_capture_processor_respects_buffer_limit() {
+        let config = RecorderConfig {
+            sample_rate: 100,
+            channels: 1,
+            device: None,
+            buffer_duration_seconds: 2,
+            ..RecorderConfig::default()
+        };
+        assert_eq!(max_buffer_size(&config), 200);
+        let buffer = Arc::new(AudioRingBuffer::new(max_buffer_size(&config)));
+        let mut processor = CaptureProcessor::new(100, 1, &config, Arc::clone(&buffer));
+
+        processor.push(&vec![0.1f32; max_buffer_size(&config)]);
+        processor.push(&[0.9f32; 10]);
+
+        let captured = buffer.snapshot();
+        assert_eq!(captured.len(), max_buffer_size(&config));
+        assert_eq!(captured[captured.len() - 1], 0.9);
+    }
+
+    #[test]
+    fn test_read_since_is_incremental() {
+        let recorder = AudioRecorder::new(RecorderConfig::default()).unwrap();
+        let (samples, position) = recorder.read_since(0);
+        assert!(samples.is_empty());
+        assert_eq!(position, 0);
+
+        recorder.buffer.push(&[0.1, 0.2, 0.3]);
+        let (samples, position) = recorder.read_since(position);
+        assert_eq!(samples, vec![0.1, 0.2, 0.3]);
+
+        recorder.buffer.push(&[0.4]);
+        let (samples, position) = recorder.read_since(position);
+        assert_eq!(samples, vec![0.4]);
+        assert_eq!(position, 4);
+
+        assert_eq!(recorder.read_range(1, 3), vec![0.2, 0.3]);
+        assert_eq!(recorder.read_range(2, 100), vec![0.3, 0.4]);
+    }
+
+    #[test]
+    fn test_position_skips_cleared_samples() {
+        let recorder = AudioRecorder::new(RecorderConfig::default()).unwrap();
+        recorder.buffer.push(&[0.1, 0.2]);
+        recorder.clear_buffer().unwrap();
+        assert_eq!(recorder.position(), 2);
+
+        // Reading from the position after a clear only sees new audio
+        recorder.buffer.push(&[0.3]);
+        let (samples, _) = recorder.read_since(recorder.position() - 1);
+        assert_eq!(samples, vec![0.3]);
+        assert_eq!(recorder.get_audio_data().unwrap(), vec![0.3]);
+    }
+
+    #[test]
+    fn test_pre_roll_and_muted_beep() {
+        let mut recorder = AudioRecorder::new(RecorderConfig {
+            sample_rate: 100,
+            channels: 1,
+            pre_roll: Duration::from_millis(20),
+            ..RecorderConfig::default()
+        })
+        .unwrap();
+        assert_eq!(recorder.samples_for(Duration::from_millis(20)), 2);
+
+        // Only the last 20ms before the beep survive
+        recorder.buffer.push(&[0.1, 0.2, 0.3, 0.4]);
+        recorder.trim_pre_roll(4);
+
+        // The beep and its tail are muted, including samples not yet captured
+        recorder.mute(4..7);
+        recorder.buffer.push(&[0.9, 0.9]);
+        assert_eq!(recorder.get_audio_data().unwrap(), vec![0.3, 0.4, 0.0, 0.0]);
+        recorder.buffer.push(&[0.9, 0.5]);
+        assert_eq!(
+            recorder.get_audio_data().unwrap(),
+            vec![0.3, 0.4, 0.0, 0.0, 0.0, 0.5]
+        );
+        assert_eq!(recorder.read_range(5, 8), vec![0.0, 0.0, 0.5]);
+        assert_eq!(recorder.read_since(0).0, vec![0.3, 0.4, 0.0, 0.0, 0.0, 0.5]);
+
+        // Reading ahead of the capture returns nothing and keeps the position
+        assert_eq!(recorder.read_since(10), (Vec::new(), 10));
+    }
+
+    #[test]
+    fn test_mute_played_covers_only_the_playback() {
+        let mut recorder = AudioRecorder::new(RecorderConfig {
+            sample_rate: 100,
+            channels: 2,
+            ..RecorderConfig::default()
+        })
+        .unwrap();
+        recorder.buffer.push(&[0.5; 20]);
+
+        // Played from 30ms to 60ms after the trigger at position 20, with a 10ms tail
+        let at = Instant::now();
+        let played = at + Duration::from_millis(30)..at + Duration::from_millis(60);
+        recorder.mute_played(20, at, played, Duration::from_millis(10));
+        recorder.buffer.push(&[0.5; 20]);
+
+        let mut expected = vec![0.5; 26];
+        expected.extend([0.0; 8]);
+        expected.extend([0.5; 6]);
+        assert_eq!(recorder.get_audio_data().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_standby_keeps_capturing_after_stop() {
+        let mut recorder = AudioRecorder::new(RecorderConfig {
+            sample_rate: 100,
+            channels: 1,
+            pre_roll: Duration::from_millis(10),
+            ..RecorderConfig::default()
+        })
+        .unwrap();
+        recorder.standby = true;
+        recorder.recording_end = Some(0);
+
+        // Idle capture is not part of a recording until it starts
+        recorder.buffer.push(&[0.1, 0.2, 0.3]);
+        assert!(recorder.get_audio_data().unwrap().is_empty());
+        assert_eq!(recorder.get_recording_duration_seconds().unwrap(), 0.0);
+
+        // Starting keeps the pre-roll from before the trigger
+        recorder.recording_end = None;
+        recorder.is_recording.store(true, Ordering::Relaxed);
+        recorder.trim_pre_roll(recorder.position());
+        recorder.buffer.push(&[0.4]);
+        recorder.stop_recording().unwrap();
+
+        // The stream keeps capturing, but the recording ends where it stopped
+        recorder.buffer.push(&[0.5, 0.6]);
+        assert!(!recorder.is_recording());
+        assert_eq!(recorder.get_audio_data().unwrap(), vec![0.3, 0.4]);
+        assert!((recorder.get_recording_duration_seconds().unwrap() - 0.02).abs() < 1e-6);
+
+        recorder.clear_buffer().unwrap();
+        assert!(recorder.get_audio_data().unwrap().is_empty());
     }
 
     #[test]
     fn test_memory_management() {
-        let recorder = AudioRecorder::new().unwrap();
+        let recorder = AudioRecorder::new(RecorderConfig::default()).unwrap();
 
         // Test buffer operations
         let data = recorder.get_audio_data().unwrap();
@@ -278,7 +895,7 @@ mod tests {
 
     #[test]
     fn test_buffer_size_limit() {
-        let recorder = AudioRecorder::new().unwrap();
+        let recorder = AudioRecorder::new(RecorderConfig::default()).unwrap();
 
         // Test that we can get recording duration (should be 0 for empty buffer)
         assert_eq!(recorder.get_recording_duration_seconds().unwrap(), 0.0);
@@ -291,7 +908,7 @@ mod tests {
     #[test]
     fn test_buffer_thread_safety() {
         // Test that the buffer is thread-safe for data access
-        let recorder = AudioRecorder::new().unwrap();
+        let recorder = AudioRecorder::new(RecorderConfig::default()).unwrap();
 
         // Test buffer operations are thread-safe
         let data = recorder.get_audio_data().unwrap();
@@ -304,9 +921,43 @@ mod tests {
         assert_eq!(data1.len(), 0);
     }
 
+    #[test]
+    fn test_match_device_by_index_and_name() {
+        let names = vec![
+            "HDA Intel PCH: ALC257 Analog".to_string(),
+            "Jabra Speak 510 USB".to_string(),
+            "pipewire".to_string(),
+        ];
+
+        assert_eq!(match_device(&names, "1"), Some(1));
+        assert_eq!(match_device(&names, "pipewire"), Some(2));
+        assert_eq!(match_device(&names, "jabra"), Some(1));
+        assert_eq!(match_device(&names, " ALC257 "), Some(0));
+        assert_eq!(match_device(&names, "webcam"), None);
+        // Out-of-range indices fall back to name matching
+        assert_eq!(match_device(&names, "9"), None);
+    }
+
+    #[test]
+    fn test_match_device_prefers_exact_name() {
+        let names = vec!["USB Mic Pro".to_string(), "USB Mic".to_string()];
+        assert_eq!(match_device(&names, "USB Mic"), Some(1));
+        assert_eq!(match_device(&names, "usb mic"), Some(0));
+    }
+
+    #[test]
+    fn test_list_input_devices() {
+        // May return an error or an empty list without audio hardware
+        if let Ok(devices) = list_input_devices() {
+            for (position, device) in devices.iter().enumerate() {
+                assert_eq!(device.index, position);
+            }
+        }
+    }
+
     #[test]
     fn test_audio_processing_events() {
-        let recorder = AudioRecorder::new().unwrap();
+        let recorder = AudioRecorder::new(RecorderConfig::default()).unwrap();
 
         // Test that process_audio_events doesn't fail
         assert!(recorder.process_audio_events().is_ok());
@@ -8,9 +8,10 @@
 
 use anyhow::Result;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use std::ops::Range;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 
 /// Types of beeps for different events
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -23,6 +24,8 @@ pub enum BeepType {
     Success,
     /// Error occurred - low, warbling tone (200Hz, 300ms)
     Error,
+    /// Recording cancelled - single falling glide (E4→A3, 300ms)
+    Cancel,
 }
 
 /// Configuration for audio feedback
@@ -54,28 +57,37 @@ impl BeepPlayer {
 
     /// Play a beep asynchronously (non-blocking)
     pub async fn play_async(&self, beep_type: BeepType) -> Result<()> {
+        self.play_timed(beep_type).await?;
+        Ok(())
+    }
+
+    /// Play a beep and return when it was played, from the first samples
+    /// handed to the output device until the end of the tone
+    /// Returns `None` when beeps are disabled or no output device could play it.
+    pub async fn play_timed(&self, beep_type: BeepType) -> Result<Option<Range<Instant>>> {
         if !self.config.enabled {
-            return Ok(());
+            return Ok(None);
         }
 
         let beep_type_copy = beep_type;
         let volume = self.config.volume;
 
-        tokio::task::spawn_blocking(move || Self::play_beep_internal(beep_type_copy, volume))
-            .await??;
+        let played =
+            tokio::task::spawn_blocking(move || Self::play_beep_internal(beep_type_copy, volume))
+                .await??;
 
-        Ok(())
+        Ok(played)
     }
 
     /// Internal beep generation using CPAL
-    fn play_beep_internal(beep_type: BeepType, volume: f32) -> Result<()> {
+    fn play_beep_internal(beep_type: BeepType, volume: f32) -> Result<Option<Range<Instant>>> {
         // Gracefully handle audio device conflicts
         let host = cpal::default_host();
         let device = match host.default_output_device() {
             Some(device) => device,
             None => {
                 eprintln!("Warning: No audio output device available for beeps");
-                return Ok(());
+                return Ok(None);
             }
         };
 
@@ -86,7 +98,7 @@ impl BeepPlayer {
                     "Warning: Failed to get audio output config for beeps: {}",
                     e
                 );
-                return Ok(());
+                return Ok(None);
             }
         };
 
@@ -98,6 +110,8 @@ impl BeepPlayer {
 
         let playing = Arc::new(AtomicBool::new(true));
         let playing_clone = playing.clone();
+        let started = Arc::new(OnceLock::new());
+        let started_clone = started.clone();
 
         let mut sample_index = 0usize;
         let mut phase = 0.0f32;
@@ -107,6 +121,7 @@ impl BeepPlayer {
                 match device.build_output_stream(
                     &config.into(),
                     move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                        started_clone.get_or_init(Instant::now);
                         Self::fill_audio_buffer_f32(
                             data,
                             &mut sample_index,
@@ -126,7 +141,7 @@ impl BeepPlayer {
                     Ok(stream) => stream,
                     Err(e) => {
                         eprintln!("Warning: Failed to create audio output stream: {}", e);
-                        return Ok(());
+                        return Ok(None);
                     }
                 }
             }
@@ -134,6 +149,7 @@ impl BeepPlayer {
                 match device.build_output_stream(
                     &config.into(),
                     move |data: &mut [i16], _: &cpal::OutputCallbackInfo| {
+                        started_clone.get_or_init(Instant::now);
                         Self::fill_audio_buffer_i16(
                             data,
                             &mut sample_index,
@@ -153,19 +169,19 @@ impl BeepPlayer {
                     Ok(stream) => stream,
                     Err(e) => {
                         eprintln!("Warning: Failed to create audio output stream: {}", e);
-                        return Ok(());
+                        return Ok(None);
                     }
                 }
             }
             _ => {
                 eprintln!("Warning: Unsupported audio format for beeps");
-                return Ok(());
+                return Ok(None);
             }
         };
 
         if let Err(e) = stream.play() {
             eprintln!("Warning: Failed to start audio stream for beep: {}", e);
-            return Ok(());
+            return Ok(None);
         }
 
         // Wait for beep to complete
@@ -176,7 +192,8 @@ impl BeepPlayer {
         // Explicitly drop the stream to release resources
         drop(stream);
 
-        Ok(())
+        let duration = Duration::from_secs_f32(duration_ms / 1000.0);
+        Ok(started.get().map(|&start| start..start + duration))
     }
 
     /// Get frequency and duration parameters for different beep types
@@ -186,6 +203,7 @@ impl BeepPlayer {
             BeepType::RecordingStop => (329.63, 500.0), // E major (E4), 500ms total for "dong ding"
             BeepType::Success => (329.63, 400.0),       // E major (E4), 400ms total for "ding ding"
             BeepType::Error => (200.0, 300.0),          // 200Hz, 300ms (unchanged)
+            BeepType::Cancel => (329.63, 300.0),        // E major (E4) gliding down, 300ms
         }
     }
 
@@ -290,6 +308,7 @@ impl BeepPlayer {
             BeepType::RecordingStop => 2.0,  // Twice as loud
             BeepType::Success => 1.0,        // Normal volume
             BeepType::Error => 1.0,          // Normal volume
+            BeepType::Cancel => 2.0,         // As loud as the start/stop beeps
         }
     }
 
@@ -300,6 +319,7 @@ impl BeepPlayer {
         base_frequency: f32,
         beep_type: BeepType,
     ) -> f32 {
+        const A3: f32 = 220.0; // A minor (A3)
         const C4: f32 = 261.63; // C major (C4)
         const E4: f32 = 329.63; // E major (E4)
 
@@ -344,6 +364,11 @@ impl BeepPlayer {
                         .sin();
                 base_frequency + (20.0 * wobble)
             }
+            BeepType::Cancel => {
+                // Single falling glide from E major down to A, unlike the two-note stop beep
+                let progress = sample_index as f32 / total_samples as f32;
+                base_frequency + (A3 - base_frequency) * progress
+            }
         }
     }
 }
@@ -415,6 +440,7 @@ mod tests {
 
         assert_ne!(BeepType::RecordingStart, BeepType::RecordingStop);
         assert_ne!(BeepType::Success, BeepType::Error);
+        assert_ne!(BeepType::Cancel, BeepType::RecordingStop);
     }
 
     #[test]
@@ -425,6 +451,7 @@ mod tests {
             BeepType::RecordingStop,
             BeepType::Success,
             BeepType::Error,
+            BeepType::Cancel,
         ];
 
         for beep_type in types {
@@ -459,6 +486,7 @@ mod tests {
             BeepType::RecordingStop,
             BeepType::Success,
             BeepType::Error,
+            BeepType::Cancel,
         ];
 
         for beep_type in beep_types {
@@ -490,6 +518,7 @@ mod tests {
             (BeepType::RecordingStop, 329.63, 500.0),  // E major (E4)
             (BeepType::Success, 329.63, 400.0),        // E major (E4)
             (BeepType::Error, 200.0, 300.0),           // Unchanged
+            (BeepType::Cancel, 329.63, 300.0),         // E major (E4) gliding down
         ];
 
         for (beep_type, expected_freq, expected_duration) in params {
@@ -520,6 +549,7 @@ mod tests {
         );
         assert_eq!(BeepPlayer::get_volume_multiplier(BeepType::Success), 1.0);
         assert_eq!(BeepPlayer::get_volume_multiplier(BeepType::Error), 1.0);
+        assert_eq!(BeepPlayer::get_volume_multiplier(BeepType::Cancel), 2.0);
     }
 
     #[test]
@@ -571,5 +601,23 @@ mod tests {
         assert_eq!(first_beep, E4, "Success first beep should be E major");
         assert_eq!(gap_freq, 0.0, "Success should have silence in the middle");
         assert_eq!(second_beep, E4, "Success second beep should be E major");
+
+        // Test cancel: one continuous glide down from E major, no gap
+        let start_freq =
+            BeepPlayer::get_frequency_at_sample(0, total_samples, E4, BeepType::Cancel);
+        let mid_freq = BeepPlayer::get_frequency_at_sample(
+            total_samples / 2,
+            total_samples,
+            E4,
+            BeepType::Cancel,
+        );
+        let end_freq =
+            BeepPlayer::get_frequency_at_sample(total_samples, total_samples, E4, BeepType::Cancel);
+        assert_eq!(start_freq, E4, "Cancel should begin at E major");
+        assert!(
+            mid_freq < E4 && mid_freq > 220.0,
+            "Cancel should glide down"
+        );
+        assert_eq!(end_freq, 220.0, "Cancel should end at A3");
     }
 }
@@ -3,24 +3,55 @@
 #![allow(clippy::cast_sign_loss)]
 
 use anyhow::Result;
+use std::collections::HashSet;
+use std::fmt;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::models;
+use crate::profile::Profile;
+use crate::transcription::openai::ResponseFormat;
+use crate::transcription::whisper_language_code;
 
 /// Configuration for waystt loaded from environment variables
 #[derive(Debug, Clone)]
 pub struct Config {
     pub openai_api_key: Option<String>,
     pub openai_base_url: Option<String>,
+    pub openai_prompt: Option<String>,
+    pub openai_temperature: Option<f32>,
+    pub openai_response_format: String,
     pub transcription_provider: String,
     pub audio_buffer_duration_seconds: usize,
     pub audio_sample_rate: u32,
     pub audio_channels: u16,
+    pub audio_input_device: Option<String>,
+    pub audio_pre_roll_ms: u64,
+    /// Keep the microphone open while the daemon is idle
+    pub audio_standby: bool,
     pub whisper_model: String,
     pub whisper_language: String,
     pub whisper_timeout_seconds: u64,
     pub whisper_max_retries: u32,
+    pub whisper_model_mirror: String,
+    /// Languages automatic detection may choose, any language when empty
+    pub whisper_allowed_languages: Vec<String>,
+    /// Translate speech to English, with every provider in the chain
+    pub translate: bool,
+    // Local whisper decoding
+    pub local_whisper_threads: Option<u32>,
+    pub local_whisper_beam_size: u32,
+    pub local_whisper_temperature_increment: f32,
+    pub local_whisper_prompt: Option<String>,
+    pub local_whisper_no_context: bool,
     pub rust_log: String,
     pub enable_audio_feedback: bool,
     pub beep_volume: f32,
+    // Transcription history
+    pub history_enabled: bool,
+    pub history_save_audio: bool,
+    pub history_max_entries: usize,
+    pub history_max_age_days: u64,
     // Google Speech-to-Text configuration
     pub google_application_credentials: Option<String>,
     pub google_speech_language_code: String,
@@ -33,17 +64,35 @@ impl Default for Config {
         Self {
             openai_api_key: None,
             openai_base_url: None,
+            openai_prompt: None,
+            openai_temperature: None, // Endpoint default
+            openai_response_format: "auto".to_string(),
             transcription_provider: "openai".to_string(),
             audio_buffer_duration_seconds: 300, // 5 minutes
             audio_sample_rate: 16000,           // Optimized for Whisper
             audio_channels: 1,                  // Mono
+            audio_input_device: None,           // System default input
+            audio_pre_roll_ms: 500,             // Speech just before the trigger
+            audio_standby: false,               // The microphone is closed while idle
             whisper_model: "whisper-1".to_string(),
             whisper_language: "auto".to_string(),
             whisper_timeout_seconds: 60,
             whisper_max_retries: 3,
+            whisper_model_mirror: models::DEFAULT_MIRROR.to_string(),
+            whisper_allowed_languages: vec![],
+            translate: false,
+            local_whisper_threads: None, // whisper.cpp default, up to 4
+            local_whisper_beam_size: 1,  // Greedy decoding
+            local_whisper_temperature_increment: 0.2,
+            local_whisper_prompt: None,
+            local_whisper_no_context: true,
             rust_log: "info".to_string(),
             enable_audio_feedback: true,
             beep_volume: 0.1,
+            history_enabled: false,    // Opt-in, transcripts stay private
+            history_save_audio: false, // Text only
+            history_max_entries: 1000,
+            history_max_age_days: 30,
             // Google Speech-to-Text defaults
             google_application_credentials: None,
             google_speech_language_code: "en-US".to_string(),
@@ -62,95 +111,193 @@ impl Config {
     }
 
     /// Full path to a model file in the model directory
+    /// Catalog names such as `base.en` resolve to their file name.
     pub fn model_path(model: &str) -> PathBuf {
-        Self::model_dir().join(model)
+        Self::model_dir().join(models::file_name(model))
+    }
+
+    /// Load configuration from environment variables over the defaults
+    /// waystt itself also layers the selected profile, see `load`.
+    #[cfg(test)]
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::default().with_env()
+    }
+
+    /// Override settings with the environment variables that are set
+    /// Settings without a variable keep their value.
+    #[cfg(test)]
+    pub fn with_env(self) -> Result<Self, ConfigError> {
+        let mut env = EnvReader::new(&|_| true);
+        let config = self.overlay(&mut env);
+        env.finish(config)
     }
 
-    /// Load configuration from environment variables
-    #[allow(clippy::field_reassign_with_default)]
-    pub fn from_env() -> Self {
-        let mut config = Config::default();
+    /// Load the configuration from every source, lowest precedence first
+    /// Defaults, then the environment file, then the profile, and last the
+    /// variables in `preset`, which were set before the file was loaded.
+    /// Every value that cannot be parsed is reported, none are skipped.
+    pub fn load(profile: Option<&Profile>, preset: &HashSet<&str>) -> Result<Self, ConfigError> {
+        // A value in the file that the profile overrides is never used
+        let from_file =
+            |key: &str| !preset.contains(key) && !profile.is_some_and(|profile| profile.sets(key));
+        let mut file = EnvReader::new(&from_file);
+        let mut config = Self::default().overlay(&mut file);
+        if let Some(profile) = profile {
+            config = profile.apply(config);
+        }
 
-        // Load OpenAI API key
-        config.openai_api_key = std::env::var("OPENAI_API_KEY").ok();
+        let from_env = |key: &str| preset.contains(key);
+        let mut env = EnvReader::new(&from_env);
+        config = config.overlay(&mut env);
+        env.invalid.splice(0..0, file.invalid);
+        env.finish(config)
+    }
 
-        // Load OpenAI base URL
-        config.openai_base_url = std::env::var("OPENAI_BASE_URL").ok();
+    /// Override settings with the variables `env` reads
+    fn overlay(self, env: &mut EnvReader) -> Self {
+        let mut config = self;
+
+        // Load OpenAI configuration
+        if let Some(api_key) = env.string("OPENAI_API_KEY") {
+            config.openai_api_key = Some(api_key);
+        }
+        if let Some(base_url) = env.string("OPENAI_BASE_URL") {
+            config.openai_base_url = Some(base_url);
+        }
+        // An empty prompt sends none
+        if let Some(prompt) = env.string("OPENAI_PROMPT") {
+            config.openai_prompt = Some(prompt).filter(|prompt| !prompt.trim().is_empty());
+        }
+        if let Some(temperature) = env.parse("OPENAI_TEMPERATURE", "a number from 0.0 to 1.0") {
+            config.openai_temperature = Some(temperature);
+        }
+        if let Some(format) = env.string("OPENAI_RESPONSE_FORMAT") {
+            config.openai_response_format = format;
+        }
 
         // Load transcription provider
-        if let Ok(provider) = std::env::var("TRANSCRIPTION_PROVIDER") {
+        if let Some(provider) = env.string("TRANSCRIPTION_PROVIDER") {
             config.transcription_provider = provider;
         }
 
         // Load audio configuration
-        if let Ok(duration) = std::env::var("AUDIO_BUFFER_DURATION_SECONDS") {
-            if let Ok(parsed) = duration.parse::<usize>() {
-                config.audio_buffer_duration_seconds = parsed;
-            }
+        if let Some(duration) =
+            env.parse("AUDIO_BUFFER_DURATION_SECONDS", "a whole number of seconds")
+        {
+            config.audio_buffer_duration_seconds = duration;
         }
-
-        if let Ok(sample_rate) = std::env::var("AUDIO_SAMPLE_RATE") {
-            if let Ok(parsed) = sample_rate.parse::<u32>() {
-                config.audio_sample_rate = parsed;
-            }
+        if let Some(pre_roll) = env.parse("AUDIO_PRE_ROLL_MS", "a whole number of milliseconds") {
+            config.audio_pre_roll_ms = pre_roll;
         }
-
-        if let Ok(channels) = std::env::var("AUDIO_CHANNELS") {
-            if let Ok(parsed) = channels.parse::<u16>() {
-                config.audio_channels = parsed;
-            }
+        if let Some(standby) = env.bool("AUDIO_STANDBY") {
+            config.audio_standby = standby;
+        }
+        if let Some(sample_rate) =
+            env.parse("AUDIO_SAMPLE_RATE", "a whole number of Hz, e.g. 16000")
+        {
+            config.audio_sample_rate = sample_rate;
+        }
+        if let Some(channels) = env.parse("AUDIO_CHANNELS", "a whole number of channels, e.g. 1") {
+            config.audio_channels = channels;
+        }
+        // An empty value selects the system default input
+        if let Some(device) = env.string("AUDIO_INPUT_DEVICE") {
+            config.audio_input_device = Some(device).filter(|device| !device.trim().is_empty());
         }
 
         // Load transcription configuration
-        if let Ok(model) = std::env::var("WHISPER_MODEL") {
+        if let Some(model) = env.string("WHISPER_MODEL") {
             config.whisper_model = model;
         }
-
-        if let Ok(language) = std::env::var("WHISPER_LANGUAGE") {
+        if let Some(language) = env.string("WHISPER_LANGUAGE") {
             config.whisper_language = language;
         }
-
-        if let Ok(timeout) = std::env::var("WHISPER_TIMEOUT_SECONDS") {
-            if let Ok(parsed) = timeout.parse::<u64>() {
-                config.whisper_timeout_seconds = parsed;
-            }
+        if let Some(timeout) = env.parse("WHISPER_TIMEOUT_SECONDS", "a whole number of seconds") {
+            config.whisper_timeout_seconds = timeout;
+        }
+        if let Some(retries) = env.parse("WHISPER_MAX_RETRIES", "a whole number") {
+            config.whisper_max_retries = retries;
+        }
+        if let Some(mirror) = env.string("WHISPER_MODEL_MIRROR") {
+            config.whisper_model_mirror = mirror;
+        }
+        if let Some(languages) = env.string("WHISPER_ALLOWED_LANGUAGES") {
+            config.whisper_allowed_languages = languages
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect();
+        }
+        if let Some(translate) = env.bool("TRANSLATE") {
+            config.translate = translate;
         }
 
-        if let Ok(retries) = std::env::var("WHISPER_MAX_RETRIES") {
-            if let Ok(parsed) = retries.parse::<u32>() {
-                config.whisper_max_retries = parsed;
-            }
+        // Load local whisper configuration
+        if let Some(threads) = env.parse("LOCAL_WHISPER_THREADS", "a whole number of threads") {
+            config.local_whisper_threads = Some(threads);
+        }
+        if let Some(beam_size) =
+            env.parse("LOCAL_WHISPER_BEAM_SIZE", "a whole number, 1 for greedy")
+        {
+            config.local_whisper_beam_size = beam_size;
+        }
+        if let Some(increment) = env.parse(
+            "LOCAL_WHISPER_TEMPERATURE_INCREMENT",
+            "a number from 0.0 to 1.0",
+        ) {
+            config.local_whisper_temperature_increment = increment;
+        }
+        // An empty prompt sends none
+        if let Some(prompt) = env.string("LOCAL_WHISPER_PROMPT") {
+            config.local_whisper_prompt = Some(prompt).filter(|prompt| !prompt.trim().is_empty());
+        }
+        if let Some(no_context) = env.bool("LOCAL_WHISPER_NO_CONTEXT") {
+            config.local_whisper_no_context = no_context;
         }
 
         // Load logging configuration
-        if let Ok(log_level) = std::env::var("RUST_LOG") {
+        if let Some(log_level) = env.string("RUST_LOG") {
             config.rust_log = log_level;
         }
 
         // Load audio feedback configuration
-        if let Ok(enabled) = std::env::var("ENABLE_AUDIO_FEEDBACK") {
-            config.enable_audio_feedback = enabled.to_lowercase() == "true";
+        if let Some(enabled) = env.bool("ENABLE_AUDIO_FEEDBACK") {
+            config.enable_audio_feedback = enabled;
+        }
+        if let Some(volume) = env.parse("BEEP_VOLUME", "a number from 0.0 to 1.0") {
+            config.beep_volume = volume;
         }
 
-        if let Ok(volume) = std::env::var("BEEP_VOLUME") {
-            if let Ok(parsed) = volume.parse::<f32>() {
-                config.beep_volume = parsed.clamp(0.0, 1.0);
-            }
+        // Load history configuration
+        if let Some(enabled) = env.bool("HISTORY_ENABLED") {
+            config.history_enabled = enabled;
+        }
+        if let Some(save_audio) = env.bool("HISTORY_SAVE_AUDIO") {
+            config.history_save_audio = save_audio;
+        }
+        if let Some(max_entries) =
+            env.parse("HISTORY_MAX_ENTRIES", "a whole number, 0 for no limit")
+        {
+            config.history_max_entries = max_entries;
+        }
+        if let Some(max_age) = env.parse(
+            "HISTORY_MAX_AGE_DAYS",
+            "a whole number of days, 0 for no limit",
+        ) {
+            config.history_max_age_days = max_age;
         }
 
         // Load Google Speech-to-Text configuration
-        config.google_application_credentials =
-            std::env::var("GOOGLE_APPLICATION_CREDENTIALS").ok();
-
-        if let Ok(language) = std::env::var("GOOGLE_SPEECH_LANGUAGE_CODE") {
+        if let Some(credentials) = env.string("GOOGLE_APPLICATION_CREDENTIALS") {
+            config.google_application_credentials = Some(credentials);
+        }
+        if let Some(language) = env.string("GOOGLE_SPEECH_LANGUAGE_CODE") {
             config.google_speech_language_code = language;
         }
-
-        if let Ok(model) = std::env::var("GOOGLE_SPEECH_MODEL") {
+        if let Some(model) = env.string("GOOGLE_SPEECH_MODEL") {
             config.google_speech_model = model;
         }
-
-        if let Ok(alt_languages) = std::env::var("GOOGLE_SPEECH_ALTERNATIVE_LANGUAGES") {
+        if let Some(alt_languages) = env.string("GOOGLE_SPEECH_ALTERNATIVE_LANGUAGES") {
             config.google_speech_alternative_languages = alt_languages
                 .split(',')
                 .map(|s| s.trim().to_string())
@@ -161,75 +308,476 @@ impl Config {
         config
     }
 
-    /// Load environment file and return config
-    pub fn load_env_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+    /// Load variables from an environment file into the process environment
+    /// Variables that are already set win over the file.
+    pub fn load_env_file<P: AsRef<Path>>(path: P) -> Result<()> {
         dotenvy::from_path(path)?;
-        Ok(Self::from_env())
+        Ok(())
+    }
+
+    /// Value of a setting as it would be written in the environment
+    /// Secrets are masked and unset optional settings are `None`.
+    pub fn display_value(&self, key: &str) -> Option<String> {
+        let value = match key {
+            "TRANSCRIPTION_PROVIDER" => self.transcription_provider.clone(),
+            "OPENAI_API_KEY" => return self.openai_api_key.as_deref().map(mask_secret),
+            "OPENAI_BASE_URL" => return self.openai_base_url.clone(),
+            "OPENAI_PROMPT" => return self.openai_prompt.clone(),
+            "OPENAI_TEMPERATURE" => return self.openai_temperature.map(|t| t.to_string()),
+            "OPENAI_RESPONSE_FORMAT" => self.openai_response_format.clone(),
+            "WHISPER_MODEL" => self.whisper_model.clone(),
+            "WHISPER_LANGUAGE" => self.whisper_language.clone(),
+            "WHISPER_TIMEOUT_SECONDS" => self.whisper_timeout_seconds.to_string(),
+            "WHISPER_MAX_RETRIES" => self.whisper_max_retries.to_string(),
+            "WHISPER_MODEL_MIRROR" => self.whisper_model_mirror.clone(),
+            "WHISPER_ALLOWED_LANGUAGES" => {
+                return Some(self.whisper_allowed_languages.join(","))
+                    .filter(|languages| !languages.is_empty())
+            }
+            "TRANSLATE" => self.translate.to_string(),
+            "LOCAL_WHISPER_THREADS" => {
+                return self
+                    .local_whisper_threads
+                    .map(|threads| threads.to_string())
+            }
+            "LOCAL_WHISPER_BEAM_SIZE" => self.local_whisper_beam_size.to_string(),
+            "LOCAL_WHISPER_TEMPERATURE_INCREMENT" => {
+                self.local_whisper_temperature_increment.to_string()
+            }
+            "LOCAL_WHISPER_PROMPT" => return self.local_whisper_prompt.clone(),
+            "LOCAL_WHISPER_NO_CONTEXT" => self.local_whisper_no_context.to_string(),
+            "GOOGLE_APPLICATION_CREDENTIALS" => return self.google_application_credentials.clone(),
+            "GOOGLE_SPEECH_LANGUAGE_CODE" => self.google_speech_language_code.clone(),
+            "GOOGLE_SPEECH_MODEL" => self.google_speech_model.clone(),
+            "GOOGLE_SPEECH_ALTERNATIVE_LANGUAGES" => {
+                return Some(self.google_speech_alternative_languages.join(","))
+                    .filter(|languages| !languages.is_empty())
+            }
+            "AUDIO_INPUT_DEVICE" => return self.audio_input_device.clone(),
+            "AUDIO_SAMPLE_RATE" => self.audio_sample_rate.to_string(),
+            "AUDIO_CHANNELS" => self.audio_channels.to_string(),
+            "AUDIO_BUFFER_DURATION_SECONDS" => self.audio_buffer_duration_seconds.to_string(),
+            "AUDIO_PRE_ROLL_MS" => self.audio_pre_roll_ms.to_string(),
+            "AUDIO_STANDBY" => self.audio_standby.to_string(),
+            "ENABLE_AUDIO_FEEDBACK" => self.enable_audio_feedback.to_string(),
+            "BEEP_VOLUME" => self.beep_volume.to_string(),
+            "HISTORY_ENABLED" => self.history_enabled.to_string(),
+            "HISTORY_SAVE_AUDIO" => self.history_save_audio.to_string(),
+            "HISTORY_MAX_ENTRIES" => self.history_max_entries.to_string(),
+            "HISTORY_MAX_AGE_DAYS" => self.history_max_age_days.to_string(),
+            "RUST_LOG" => self.rust_log.clone(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Providers to try in order, from a comma-separated `TRANSCRIPTION_PROVIDER`
+    pub fn transcription_providers(&self) -> Vec<&str> {
+        self.transcription_provider
+            .split(',')
+            .map(str::trim)
+            .filter(|provider| !provider.is_empty())
+            .collect()
+    }
+
+    /// Model used by the configured transcription provider
+    pub fn transcription_model(&self) -> &str {
+        self.model_for(&self.transcription_provider)
+    }
+
+    /// Model used by a single transcription provider
+    pub fn model_for(&self, provider: &str) -> &str {
+        match provider {
+            "google" | "google-grpc" => &self.google_speech_model,
+            _ => &self.whisper_model,
+        }
+    }
+
+    /// Validate configuration, reporting every problem at once
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut invalid = Vec::new();
+
+        let providers = self.transcription_providers();
+        if providers.is_empty() {
+            invalid.push(InvalidSetting::new(
+                "TRANSCRIPTION_PROVIDER",
+                Some(&self.transcription_provider),
+                "at least one provider",
+            ));
+        }
+        for provider in providers {
+            self.validate_provider(provider, &mut invalid);
+        }
+        for language in &self.whisper_allowed_languages {
+            if whisper_language_code(language) != Some(language.as_str()) {
+                invalid.push(InvalidSetting::new(
+                    "WHISPER_ALLOWED_LANGUAGES",
+                    Some(language),
+                    "comma-separated whisper language codes such as en,de",
+                ));
+            }
+        }
+        self.validate_audio(&mut invalid);
+
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError { invalid })
+        }
     }
 
-    /// Validate configuration
-    pub fn validate(&self) -> Result<()> {
-        // Provider-specific validation
-        match self.transcription_provider.as_str() {
+    /// Check that a single provider has everything it needs
+    fn validate_provider(&self, provider: &str, invalid: &mut Vec<InvalidSetting>) {
+        match provider {
             "openai" => {
                 if self.openai_api_key.is_none() {
-                    return Err(anyhow::anyhow!(
-                        "OPENAI_API_KEY is required when using OpenAI provider. Please set it in your .env file."
+                    invalid.push(InvalidSetting::new(
+                        "OPENAI_API_KEY",
+                        None,
+                        "when using OpenAI provider. Please set it in your .env file.",
+                    ));
+                }
+                if let Some(temperature) = self.openai_temperature {
+                    if !(0.0..=1.0).contains(&temperature) {
+                        invalid.push(InvalidSetting::new(
+                            "OPENAI_TEMPERATURE",
+                            Some(&temperature.to_string()),
+                            "between 0.0 and 1.0",
+                        ));
+                    }
+                }
+                if self
+                    .openai_response_format
+                    .parse::<ResponseFormat>()
+                    .is_err()
+                {
+                    invalid.push(InvalidSetting::new(
+                        "OPENAI_RESPONSE_FORMAT",
+                        Some(&self.openai_response_format),
+                        "auto, json or verbose_json",
                     ));
                 }
             }
             "local" => {
                 let model_path = Config::model_path(&self.whisper_model);
                 if !model_path.exists() {
-                    return Err(anyhow::anyhow!(
-                        "Local model not found at {}. Use --download-model to fetch it.",
-                        model_path.display()
+                    invalid.push(InvalidSetting::new(
+                        "WHISPER_MODEL",
+                        Some(&self.whisper_model),
+                        format!(
+                            "a model in {}. Use `waystt models download` to fetch it.",
+                            Config::model_dir().display()
+                        ),
                     ));
                 }
+                self.validate_local_whisper(invalid);
             }
-            "google" => {
+            "google" | "google-grpc" => {
                 if self.google_application_credentials.is_none() {
-                    return Err(anyhow::anyhow!(
-                        "GOOGLE_APPLICATION_CREDENTIALS is required when using Google provider. Please set it to the path of your service account JSON file."
+                    invalid.push(InvalidSetting::new(
+                        "GOOGLE_APPLICATION_CREDENTIALS",
+                        None,
+                        "when using Google provider. Please set it to the path of your service account JSON file.",
+                    ));
+                }
+                if self.translate {
+                    invalid.push(InvalidSetting::new(
+                        "TRANSLATE",
+                        Some("true"),
+                        "false when using Google provider, which cannot translate",
                     ));
                 }
             }
-            _ => {
-                return Err(anyhow::anyhow!(
-                    "Unsupported transcription provider: {}. Supported providers: openai, google, local",
-                    self.transcription_provider
-                ));
-            }
+            _ => invalid.push(InvalidSetting::new(
+                "TRANSCRIPTION_PROVIDER",
+                Some(provider),
+                "a supported provider: openai, google, google-grpc or local",
+            )),
+        }
+    }
+
+    /// Check the local whisper decoding settings
+    fn validate_local_whisper(&self, invalid: &mut Vec<InvalidSetting>) {
+        if self.local_whisper_threads == Some(0) {
+            invalid.push(InvalidSetting::new(
+                "LOCAL_WHISPER_THREADS",
+                Some("0"),
+                "greater than 0",
+            ));
         }
 
+        // whisper.cpp runs at most 8 decoders
+        if !(1..=8).contains(&self.local_whisper_beam_size) {
+            invalid.push(InvalidSetting::new(
+                "LOCAL_WHISPER_BEAM_SIZE",
+                Some(&self.local_whisper_beam_size.to_string()),
+                "between 1 and 8",
+            ));
+        }
+
+        if !(0.0..=1.0).contains(&self.local_whisper_temperature_increment) {
+            invalid.push(InvalidSetting::new(
+                "LOCAL_WHISPER_TEMPERATURE_INCREMENT",
+                Some(&self.local_whisper_temperature_increment.to_string()),
+                "between 0.0 and 1.0",
+            ));
+        }
+    }
+
+    /// Check the audio settings
+    fn validate_audio(&self, invalid: &mut Vec<InvalidSetting>) {
         if self.audio_buffer_duration_seconds == 0 {
-            return Err(anyhow::anyhow!(
-                "AUDIO_BUFFER_DURATION_SECONDS must be greater than 0"
+            invalid.push(InvalidSetting::new(
+                "AUDIO_BUFFER_DURATION_SECONDS",
+                Some("0"),
+                "greater than 0",
+            ));
+        } else if self.audio_pre_roll_ms >= self.audio_buffer_duration_seconds as u64 * 1000 {
+            invalid.push(InvalidSetting::new(
+                "AUDIO_PRE_ROLL_MS",
+                Some(&self.audio_pre_roll_ms.to_string()),
+                "shorter than AUDIO_BUFFER_DURATION_SECONDS",
             ));
         }
 
         if self.audio_sample_rate == 0 {
-            return Err(anyhow::anyhow!("AUDIO_SAMPLE_RATE must be greater than 0"));
+            invalid.push(InvalidSetting::new(
+                "AUDIO_SAMPLE_RATE",
+                Some("0"),
+                "greater than 0",
+            ));
         }
 
         if self.audio_channels == 0 {
-            return Err(anyhow::anyhow!("AUDIO_CHANNELS must be greater than 0"));
+            invalid.push(InvalidSetting::new(
+                "AUDIO_CHANNELS",
+                Some("0"),
+                "greater than 0",
+            ));
         }
 
-        if self.beep_volume < 0.0 || self.beep_volume > 1.0 {
-            return Err(anyhow::anyhow!(
-                "BEEP_VOLUME must be between 0.0 and 1.0, got: {}",
-                self.beep_volume
+        if !(0.0..=1.0).contains(&self.beep_volume) {
+            invalid.push(InvalidSetting::new(
+                "BEEP_VOLUME",
+                Some(&self.beep_volume.to_string()),
+                "between 0.0 and 1.0",
             ));
         }
+    }
+}
 
-        Ok(())
+/// Environment variable of every setting, in the order `waystt config check` lists them
+pub const SETTINGS: &[&str] = &[
+    "TRANSCRIPTION_PROVIDER",
+    "OPENAI_API_KEY",
+    "OPENAI_BASE_URL",
+    "OPENAI_PROMPT",
+    "OPENAI_TEMPERATURE",
+    "OPENAI_RESPONSE_FORMAT",
+    "WHISPER_MODEL",
+    "WHISPER_LANGUAGE",
+    "WHISPER_TIMEOUT_SECONDS",
+    "WHISPER_MAX_RETRIES",
+    "WHISPER_MODEL_MIRROR",
+    "WHISPER_ALLOWED_LANGUAGES",
+    "TRANSLATE",
+    "LOCAL_WHISPER_THREADS",
+    "LOCAL_WHISPER_BEAM_SIZE",
+    "LOCAL_WHISPER_TEMPERATURE_INCREMENT",
+    "LOCAL_WHISPER_PROMPT",
+    "LOCAL_WHISPER_NO_CONTEXT",
+    "GOOGLE_APPLICATION_CREDENTIALS",
+    "GOOGLE_SPEECH_LANGUAGE_CODE",
+    "GOOGLE_SPEECH_MODEL",
+    "GOOGLE_SPEECH_ALTERNATIVE_LANGUAGES",
+    "AUDIO_INPUT_DEVICE",
+    "AUDIO_SAMPLE_RATE",
+    "AUDIO_CHANNELS",
+    "AUDIO_BUFFER_DURATION_SECONDS",
+    "AUDIO_PRE_ROLL_MS",
+    "AUDIO_STANDBY",
+    "ENABLE_AUDIO_FEEDBACK",
+    "BEEP_VOLUME",
+    "HISTORY_ENABLED",
+    "HISTORY_SAVE_AUDIO",
+    "HISTORY_MAX_ENTRIES",
+    "HISTORY_MAX_AGE_DAYS",
+    "RUST_LOG",
+];
+
+/// Where the value of a setting came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    Default,
+    /// A `[profile.<name>]` table in `config.toml`
+    Profile(String),
+    /// The environment file loaded at startup
+    EnvFile(PathBuf),
+    /// A variable set before waystt started
+    Environment,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::Profile(name) => write!(f, "profile {}", name),
+            Source::EnvFile(path) => write!(f, "{}", path.display()),
+            Source::Environment => write!(f, "environment"),
+        }
     }
 }
 
-/// Load configuration from environment variables
-pub fn load_config() -> Config {
-    Config::from_env()
+/// Settings whose variable is currently set in the process environment
+pub fn variables_set() -> HashSet<&'static str> {
+    SETTINGS
+        .iter()
+        .copied()
+        .filter(|key| std::env::var_os(key).is_some())
+        .collect()
+}
+
+/// Work out where a setting's value came from
+/// `preset` holds the variables set before the environment file was loaded.
+pub fn source_of(
+    key: &str,
+    preset: &HashSet<&str>,
+    envfile: Option<&Path>,
+    profile: Option<&Profile>,
+) -> Source {
+    if preset.contains(key) {
+        return Source::Environment;
+    }
+    match profile {
+        Some(profile) if profile.sets(key) => Source::Profile(profile.name.clone()),
+        _ if std::env::var_os(key).is_none() => Source::Default,
+        _ => match envfile {
+            Some(path) => Source::EnvFile(path.to_path_buf()),
+            None => Source::Environment,
+        },
+    }
+}
+
+/// Show only the last four characters of a secret
+fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() < 12 {
+        return "****".to_string();
+    }
+    let last: String = chars[chars.len() - 4..].iter().collect();
+    format!("****{}", last)
+}
+
+/// A setting with a missing or unusable value
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidSetting {
+    /// Environment variable of the setting
+    pub key: &'static str,
+    /// The rejected value, or `None` when a required setting is missing
+    pub value: Option<String>,
+    /// What the value has to be, or when a missing setting is required
+    pub expected: String,
+}
+
+impl InvalidSetting {
+    fn new(key: &'static str, value: Option<&str>, expected: impl Into<String>) -> Self {
+        Self {
+            key,
+            value: value.map(str::to_string),
+            expected: expected.into(),
+        }
+    }
+}
+
+impl fmt::Display for InvalidSetting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}={:?}: expected {}", self.key, value, self.expected),
+            None => write!(f, "{} is required {}", self.key, self.expected),
+        }
+    }
+}
+
+/// Every invalid setting found while loading or validating the configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub invalid: Vec<InvalidSetting>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.invalid.as_slice() {
+            [single] => write!(f, "{}", single),
+            invalid => {
+                write!(f, "{} invalid settings:", invalid.len())?;
+                for setting in invalid {
+                    write!(f, "\n  {}", setting)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Reads environment variables, collecting every value that fails to parse
+struct EnvReader<'a> {
+    /// Whether a variable belongs to the source being read
+    reads: &'a dyn Fn(&str) -> bool,
+    invalid: Vec<InvalidSetting>,
+}
+
+impl<'a> EnvReader<'a> {
+    fn new(reads: &'a dyn Fn(&str) -> bool) -> Self {
+        Self {
+            reads,
+            invalid: Vec::new(),
+        }
+    }
+
+    fn string(&self, key: &str) -> Option<String> {
+        if !(self.reads)(key) {
+            return None;
+        }
+        std::env::var(key).ok()
+    }
+
+    /// The configuration, or every value that failed to parse
+    fn finish(self, config: Config) -> Result<Config, ConfigError> {
+        if self.invalid.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError {
+                invalid: self.invalid,
+            })
+        }
+    }
+
+    fn parse<T: FromStr>(&mut self, key: &'static str, expected: &str) -> Option<T> {
+        let value = self.string(key)?;
+        match value.trim().parse() {
+            Ok(parsed) => Some(parsed),
+            Err(_) => {
+                self.invalid
+                    .push(InvalidSetting::new(key, Some(&value), expected));
+                None
+            }
+        }
+    }
+
+    fn bool(&mut self, key: &'static str) -> Option<bool> {
+        let value = self.string(key)?;
+        match value.trim().to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Some(true),
+            "false" | "no" | "off" | "0" => Some(false),
+            _ => {
+                self.invalid
+                    .push(InvalidSetting::new(key, Some(&value), "true or false"));
+                None
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -244,17 +792,35 @@ mod tests {
     fn clear_env_vars() {
         env::remove_var("OPENAI_API_KEY");
         env::remove_var("OPENAI_BASE_URL");
+        env::remove_var("OPENAI_PROMPT");
+        env::remove_var("OPENAI_TEMPERATURE");
+        env::remove_var("OPENAI_RESPONSE_FORMAT");
         env::remove_var("TRANSCRIPTION_PROVIDER");
         env::remove_var("AUDIO_BUFFER_DURATION_SECONDS");
         env::remove_var("AUDIO_SAMPLE_RATE");
         env::remove_var("AUDIO_CHANNELS");
+        env::remove_var("AUDIO_INPUT_DEVICE");
+        env::remove_var("AUDIO_PRE_ROLL_MS");
+        env::remove_var("AUDIO_STANDBY");
         env::remove_var("WHISPER_MODEL");
         env::remove_var("WHISPER_LANGUAGE");
         env::remove_var("WHISPER_TIMEOUT_SECONDS");
         env::remove_var("WHISPER_MAX_RETRIES");
+        env::remove_var("WHISPER_MODEL_MIRROR");
+        env::remove_var("WHISPER_ALLOWED_LANGUAGES");
+        env::remove_var("LOCAL_WHISPER_THREADS");
+        env::remove_var("LOCAL_WHISPER_BEAM_SIZE");
+        env::remove_var("LOCAL_WHISPER_TEMPERATURE_INCREMENT");
+        env::remove_var("LOCAL_WHISPER_PROMPT");
+        env::remove_var("TRANSLATE");
+        env::remove_var("LOCAL_WHISPER_NO_CONTEXT");
         env::remove_var("RUST_LOG");
         env::remove_var("ENABLE_AUDIO_FEEDBACK");
         env::remove_var("BEEP_VOLUME");
+        env::remove_var("HISTORY_ENABLED");
+        env::remove_var("HISTORY_SAVE_AUDIO");
+        env::remove_var("HISTORY_MAX_ENTRIES");
+        env::remove_var("HISTORY_MAX_AGE_DAYS");
         env::remove_var("GOOGLE_APPLICATION_CREDENTIALS");
         env::remove_var("GOOGLE_SPEECH_LANGUAGE_CODE");
         env::remove_var("GOOGLE_SPEECH_MODEL");
@@ -270,6 +836,9 @@ mod tests {
         assert_eq!(config.audio_buffer_duration_seconds, 300);
         assert_eq!(config.audio_sample_rate, 16000);
         assert_eq!(config.audio_channels, 1);
+        assert_eq!(config.audio_input_device, None);
+        assert_eq!(config.audio_pre_roll_ms, 500);
+        assert!(!config.audio_standby);
         assert_eq!(config.whisper_model, "whisper-1");
         assert_eq!(config.whisper_language, "auto");
         assert_eq!(config.rust_log, "info");
@@ -291,7 +860,7 @@ mod tests {
             // Clear all environment variables first
             clear_env_vars();
 
-            let config = Config::from_env();
+            let config = Config::from_env().unwrap();
             assert_eq!(config.openai_api_key, None);
             assert_eq!(config.openai_base_url, None);
             assert_eq!(config.transcription_provider, "openai");
@@ -323,6 +892,9 @@ mod tests {
             env::set_var("AUDIO_BUFFER_DURATION_SECONDS", "600");
             env::set_var("AUDIO_SAMPLE_RATE", "44100");
             env::set_var("AUDIO_CHANNELS", "2");
+            env::set_var("AUDIO_INPUT_DEVICE", "Jabra");
+            env::set_var("AUDIO_PRE_ROLL_MS", "250");
+            env::set_var("AUDIO_STANDBY", "true");
             env::set_var("WHISPER_MODEL", "whisper-large");
             env::set_var("WHISPER_LANGUAGE", "en");
             env::set_var("WHISPER_TIMEOUT_SECONDS", "120");
@@ -331,7 +903,7 @@ mod tests {
             env::set_var("TRANSCRIPTION_PROVIDER", "google");
             env::set_var("OPENAI_BASE_URL", "http://localhost:8080");
 
-            let config = Config::from_env();
+            let config = Config::from_env().unwrap();
             assert_eq!(config.openai_api_key, Some("test-api-key".to_string()));
             assert_eq!(
                 config.openai_base_url,
@@ -341,6 +913,9 @@ mod tests {
             assert_eq!(config.audio_buffer_duration_seconds, 600);
             assert_eq!(config.audio_sample_rate, 44100);
             assert_eq!(config.audio_channels, 2);
+            assert_eq!(config.audio_input_device, Some("Jabra".to_string()));
+            assert_eq!(config.audio_pre_roll_ms, 250);
+            assert!(config.audio_standby);
             assert_eq!(config.whisper_model, "whisper-large");
             assert_eq!(config.whisper_language, "en");
             assert_eq!(config.whisper_timeout_seconds, 120);
@@ -368,14 +943,32 @@ mod tests {
             env::set_var("WHISPER_TIMEOUT_SECONDS", "invalid");
             env::set_var("WHISPER_MAX_RETRIES", "bad");
 
-            let config = Config::from_env();
+            let error = Config::from_env().unwrap_err();
 
-            // Should fallback to defaults for invalid values
-            assert_eq!(config.audio_buffer_duration_seconds, 300);
-            assert_eq!(config.audio_sample_rate, 16000);
-            assert_eq!(config.audio_channels, 1);
-            assert_eq!(config.whisper_timeout_seconds, 60);
-            assert_eq!(config.whisper_max_retries, 3);
+            // Every invalid value is reported instead of falling back to defaults
+            let keys: Vec<&str> = error.invalid.iter().map(|setting| setting.key).collect();
+            assert_eq!(
+                keys,
+                vec![
+                    "AUDIO_BUFFER_DURATION_SECONDS",
+                    "AUDIO_SAMPLE_RATE",
+                    "AUDIO_CHANNELS",
+                    "WHISPER_TIMEOUT_SECONDS",
+                    "WHISPER_MAX_RETRIES",
+                ]
+            );
+            assert_eq!(
+                error.invalid[1],
+                InvalidSetting {
+                    key: "AUDIO_SAMPLE_RATE",
+                    value: Some("not-a-number".to_string()),
+                    expected: "a whole number of Hz, e.g. 16000".to_string(),
+                }
+            );
+
+            let message = error.to_string();
+            assert!(message.starts_with("5 invalid settings:"));
+            assert!(message.contains("AUDIO_CHANNELS=\"bad\": expected a whole number"));
 
             clear_env_vars();
         }
@@ -399,7 +992,8 @@ mod tests {
             writeln!(temp_file, "OPENAI_BASE_URL=http://localhost:8080").unwrap();
 
             // Load config from file
-            let config = Config::load_env_file(temp_file.path()).unwrap();
+            Config::load_env_file(temp_file.path()).unwrap();
+            let config = Config::from_env().unwrap();
 
             assert_eq!(config.openai_api_key, Some("file-api-key".to_string()));
             assert_eq!(
@@ -420,6 +1014,190 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_transcription_model_follows_provider() {
+        let mut config = Config {
+            whisper_model: "ggml-base.en.bin".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(config.transcription_model(), "ggml-base.en.bin");
+
+        config.transcription_provider = "google-grpc".to_string();
+        assert_eq!(config.transcription_model(), "latest_long");
+    }
+
+    #[test]
+    fn test_fallback_chain_validates_every_provider() {
+        let config = Config {
+            transcription_provider: "openai, google ,".to_string(),
+            openai_api_key: Some("test-key".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(config.transcription_providers(), vec!["openai", "google"]);
+        assert_eq!(config.model_for("google"), "latest_long");
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("GOOGLE_APPLICATION_CREDENTIALS"));
+
+        let config = Config {
+            google_application_credentials: Some("/tmp/key.json".to_string()),
+            ..config
+        };
+        assert!(config.validate().is_ok());
+
+        let config = Config {
+            transcription_provider: " , ".to_string(),
+            ..config
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validation_reports_every_problem() {
+        let config = Config {
+            audio_sample_rate: 0,
+            beep_volume: -1.0,
+            ..Default::default()
+        };
+
+        let keys: Vec<&str> = config
+            .validate()
+            .unwrap_err()
+            .invalid
+            .iter()
+            .map(|setting| setting.key)
+            .collect();
+        assert_eq!(
+            keys,
+            vec!["OPENAI_API_KEY", "AUDIO_SAMPLE_RATE", "BEEP_VOLUME"]
+        );
+    }
+
+    #[test]
+    fn test_display_value_masks_secrets() {
+        let config = Config {
+            openai_api_key: Some("sk-proj-abcdefgh1234".to_string()),
+            google_speech_alternative_languages: vec!["de-DE".to_string(), "fr-FR".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.display_value("OPENAI_API_KEY").as_deref(),
+            Some("****1234")
+        );
+        assert_eq!(
+            config
+                .display_value("GOOGLE_SPEECH_ALTERNATIVE_LANGUAGES")
+                .as_deref(),
+            Some("de-DE,fr-FR")
+        );
+        assert_eq!(config.display_value("AUDIO_INPUT_DEVICE"), None);
+        assert_eq!(mask_secret("short"), "****");
+
+        // Every listed setting is known
+        let config = Config {
+            openai_base_url: Some("http://localhost".to_string()),
+            openai_prompt: Some("waystt".to_string()),
+            openai_temperature: Some(0.0),
+            whisper_allowed_languages: vec!["en".to_string(), "de".to_string()],
+            local_whisper_threads: Some(16),
+            local_whisper_prompt: Some("waystt".to_string()),
+            audio_input_device: Some("USB".to_string()),
+            google_application_credentials: Some("/tmp/key.json".to_string()),
+            ..config
+        };
+        for key in SETTINGS {
+            assert!(config.display_value(key).is_some(), "{}", key);
+        }
+    }
+
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_source_of() {
+        let _lock = ENV_MUTEX.lock().await;
+        clear_env_vars();
+
+        let mut profile_file = NamedTempFile::new().unwrap();
+        writeln!(
+            profile_file,
+            "[profile.code]\nwhisper_model = \"ggml-base.en.bin\""
+        )
+        .unwrap();
+        let profile = crate::profile::load(profile_file.path(), "code").unwrap();
+
+        env::set_var("WHISPER_LANGUAGE", "de");
+        let preset = variables_set();
+        // As if loaded from the environment file afterwards
+        env::set_var("BEEP_VOLUME", "0.5");
+        let envfile = Path::new("/home/user/.config/waystt/.env");
+
+        let source = |key| source_of(key, &preset, Some(envfile), Some(&profile));
+        assert_eq!(source("WHISPER_LANGUAGE"), Source::Environment);
+        assert_eq!(
+            source("BEEP_VOLUME"),
+            Source::EnvFile(envfile.to_path_buf())
+        );
+        assert_eq!(source("WHISPER_MODEL"), Source::Profile("code".to_string()));
+        assert_eq!(source("AUDIO_CHANNELS"), Source::Default);
+        assert_eq!(
+            Source::Profile("code".to_string()).to_string(),
+            "profile code"
+        );
+
+        clear_env_vars();
+    }
+
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_load_precedence() {
+        let _lock = ENV_MUTEX.lock().await;
+        clear_env_vars();
+
+        let mut profile_file = NamedTempFile::new().unwrap();
+        writeln!(
+            profile_file,
+            "[profile.code]\nwhisper_model = \"ggml-base.en.bin\"\nwhisper_language = \"fr\"\naudio_channels = 2"
+        )
+        .unwrap();
+        let profile = crate::profile::load(profile_file.path(), "code").unwrap();
+
+        env::set_var("WHISPER_LANGUAGE", "de");
+        let preset = variables_set();
+        let mut envfile = NamedTempFile::new().unwrap();
+        writeln!(envfile, "WHISPER_MODEL=ggml-small.bin").unwrap();
+        writeln!(envfile, "WHISPER_LANGUAGE=es").unwrap();
+        writeln!(envfile, "BEEP_VOLUME=0.5").unwrap();
+        // Never used, the profile sets the channels
+        writeln!(envfile, "AUDIO_CHANNELS=bad").unwrap();
+        Config::load_env_file(envfile.path()).unwrap();
+
+        let config = Config::load(Some(&profile), &preset).unwrap();
+        // The profile wins over the environment file
+        assert_eq!(config.whisper_model, "ggml-base.en.bin");
+        assert_eq!(config.audio_channels, 2);
+        // The environment wins over both
+        assert_eq!(config.whisper_language, "de");
+        // The environment file wins over the defaults
+        assert_eq!(config.beep_volume, 0.5);
+        assert_eq!(config.audio_sample_rate, 16000);
+
+        let source = |key| source_of(key, &preset, Some(envfile.path()), Some(&profile));
+        assert_eq!(source("WHISPER_MODEL"), Source::Profile("code".to_string()));
+        assert_eq!(source("WHISPER_LANGUAGE"), Source::Environment);
+        assert_eq!(
+            source("BEEP_VOLUME"),
+            Source::EnvFile(envfile.path().to_path_buf())
+        );
+
+        // Without a profile the environment file applies
+        let error = Config::load(None, &preset).unwrap_err();
+        assert_eq!(error.invalid.len(), 1);
+        assert_eq!(error.invalid[0].key, "AUDIO_CHANNELS");
+
+        clear_env_vars();
+    }
+
     #[test]
     fn test_load_nonexistent_env_file() {
         let result = Config::load_env_file("/nonexistent/path/.env");
@@ -464,6 +1242,22 @@ mod tests {
             .contains("AUDIO_BUFFER_DURATION_SECONDS"));
     }
 
+    #[test]
+    fn test_config_validation_pre_roll_exceeds_buffer() {
+        let config = Config {
+            openai_api_key: Some("test-key".to_string()),
+            audio_buffer_duration_seconds: 1,
+            audio_pre_roll_ms: 1000,
+            ..Default::default()
+        };
+
+        let result = config.validate();
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("AUDIO_PRE_ROLL_MS"));
+    }
+
     #[test]
     fn test_config_validation_invalid_sample_rate() {
         let config = Config {
@@ -527,7 +1321,7 @@ mod tests {
             env::set_var("ENABLE_AUDIO_FEEDBACK", "true");
             env::set_var("BEEP_VOLUME", "0.5");
 
-            let config = Config::from_env();
+            let config = Config::from_env().unwrap();
             assert!(config.enable_audio_feedback);
             assert_eq!(config.beep_volume, 0.5);
 
@@ -537,7 +1331,7 @@ mod tests {
             env::set_var("ENABLE_AUDIO_FEEDBACK", "false");
             env::set_var("BEEP_VOLUME", "0.8");
 
-            let config = Config::from_env();
+            let config = Config::from_env().unwrap();
             assert!(!config.enable_audio_feedback);
             assert_eq!(config.beep_volume, 0.8);
 
@@ -545,6 +1339,207 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_env_overrides_base_config() {
+        let _lock = ENV_MUTEX.lock().await;
+        clear_env_vars();
+
+        // e.g. a profile selecting the local provider with its own key and device
+        let base = Config {
+            transcription_provider: "local".to_string(),
+            whisper_model: "ggml-base.en.bin".to_string(),
+            openai_api_key: Some("profile-key".to_string()),
+            audio_input_device: Some("USB".to_string()),
+            ..Config::default()
+        };
+
+        let config = base.clone().with_env().unwrap();
+        assert_eq!(config.transcription_provider, "local");
+        assert_eq!(config.openai_api_key.as_deref(), Some("profile-key"));
+        assert_eq!(config.audio_input_device.as_deref(), Some("USB"));
+
+        env::set_var("WHISPER_MODEL", "ggml-small.bin");
+        env::set_var("OPENAI_API_KEY", "env-key");
+        env::set_var("AUDIO_INPUT_DEVICE", "");
+
+        let config = base.with_env().unwrap();
+        assert_eq!(config.transcription_provider, "local");
+        assert_eq!(config.whisper_model, "ggml-small.bin");
+        assert_eq!(config.openai_api_key.as_deref(), Some("env-key"));
+        assert_eq!(config.audio_input_device, None);
+
+        clear_env_vars();
+    }
+
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_history_config_from_env() {
+        let _lock = ENV_MUTEX.lock().await;
+        clear_env_vars();
+
+        let config = Config::from_env().unwrap();
+        assert!(!config.history_enabled);
+        assert!(!config.history_save_audio);
+        assert_eq!(config.history_max_entries, 1000);
+        assert_eq!(config.history_max_age_days, 30);
+
+        env::set_var("HISTORY_ENABLED", "TRUE");
+        env::set_var("HISTORY_SAVE_AUDIO", "true");
+        env::set_var("HISTORY_MAX_ENTRIES", "50");
+        env::set_var("HISTORY_MAX_AGE_DAYS", "0");
+
+        let config = Config::from_env().unwrap();
+        assert!(config.history_enabled);
+        assert!(config.history_save_audio);
+        assert_eq!(config.history_max_entries, 50);
+        assert_eq!(config.history_max_age_days, 0);
+
+        clear_env_vars();
+    }
+
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_openai_request_options_from_env() {
+        let _lock = ENV_MUTEX.lock().await;
+        clear_env_vars();
+
+        let config = Config::from_env().unwrap();
+        assert_eq!(config.openai_prompt, None);
+        assert_eq!(config.openai_temperature, None);
+        assert_eq!(config.openai_response_format, "auto");
+
+        env::set_var("OPENAI_PROMPT", "waystt, PipeWire, Hyprland");
+        env::set_var("OPENAI_TEMPERATURE", "0.2");
+        env::set_var("OPENAI_RESPONSE_FORMAT", "verbose_json");
+
+        let config = Config::from_env().unwrap();
+        assert_eq!(
+            config.openai_prompt.as_deref(),
+            Some("waystt, PipeWire, Hyprland")
+        );
+        assert_eq!(config.openai_temperature, Some(0.2));
+        assert_eq!(config.openai_response_format, "verbose_json");
+
+        env::set_var("OPENAI_PROMPT", " ");
+        env::set_var("OPENAI_TEMPERATURE", "warm");
+        let error = Config::from_env().unwrap_err();
+        assert_eq!(error.invalid.len(), 1);
+        assert_eq!(error.invalid[0].key, "OPENAI_TEMPERATURE");
+
+        env::remove_var("OPENAI_TEMPERATURE");
+        assert_eq!(Config::from_env().unwrap().openai_prompt, None);
+
+        clear_env_vars();
+    }
+
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_translate_applies_to_the_whole_chain() {
+        let _lock = ENV_MUTEX.lock().await;
+        clear_env_vars();
+
+        assert!(!Config::from_env().unwrap().translate);
+        env::set_var("TRANSLATE", "yes");
+        let config = Config::from_env().unwrap();
+        assert!(config.translate);
+        assert_eq!(config.display_value("TRANSLATE").as_deref(), Some("true"));
+
+        // Every provider in a chain has to translate, or the output language
+        // would depend on which one answered
+        let config = Config {
+            transcription_provider: "openai,google".to_string(),
+            openai_api_key: Some("key".to_string()),
+            google_application_credentials: Some("/tmp/key.json".to_string()),
+            ..config
+        };
+        let error = config.validate().unwrap_err();
+        assert_eq!(error.invalid.len(), 1);
+        assert_eq!(error.invalid[0].key, "TRANSLATE");
+        assert!(Config {
+            translate: false,
+            ..config
+        }
+        .validate()
+        .is_ok());
+
+        clear_env_vars();
+    }
+
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_local_whisper_options_from_env() {
+        let _lock = ENV_MUTEX.lock().await;
+        clear_env_vars();
+
+        let config = Config::from_env().unwrap();
+        assert_eq!(config.local_whisper_threads, None);
+        assert_eq!(config.local_whisper_beam_size, 1);
+        assert_eq!(config.local_whisper_temperature_increment, 0.2);
+        assert!(config.local_whisper_no_context);
+
+        env::set_var("LOCAL_WHISPER_THREADS", "16");
+        env::set_var("LOCAL_WHISPER_BEAM_SIZE", "5");
+        env::set_var("LOCAL_WHISPER_TEMPERATURE_INCREMENT", "0");
+        env::set_var("LOCAL_WHISPER_PROMPT", "Kubernetes, kubectl, etcd");
+        env::set_var("LOCAL_WHISPER_NO_CONTEXT", "false");
+
+        let config = Config::from_env().unwrap();
+        assert_eq!(config.local_whisper_threads, Some(16));
+        assert_eq!(config.local_whisper_beam_size, 5);
+        assert_eq!(config.local_whisper_temperature_increment, 0.0);
+        assert_eq!(
+            config.local_whisper_prompt.as_deref(),
+            Some("Kubernetes, kubectl, etcd")
+        );
+        assert!(!config.local_whisper_no_context);
+
+        env::set_var("LOCAL_WHISPER_THREADS", "all");
+        let error = Config::from_env().unwrap_err();
+        assert_eq!(error.invalid[0].key, "LOCAL_WHISPER_THREADS");
+
+        clear_env_vars();
+    }
+
+    #[test]
+    fn test_config_validation_local_whisper_options() {
+        let config = Config {
+            transcription_provider: "local".to_string(),
+            local_whisper_threads: Some(0),
+            local_whisper_beam_size: 0,
+            local_whisper_temperature_increment: 2.0,
+            ..Default::default()
+        };
+        let error = config.validate().unwrap_err();
+        let keys: Vec<&str> = error.invalid.iter().map(|setting| setting.key).collect();
+        assert!(keys.ends_with(&[
+            "LOCAL_WHISPER_THREADS",
+            "LOCAL_WHISPER_BEAM_SIZE",
+            "LOCAL_WHISPER_TEMPERATURE_INCREMENT"
+        ]));
+    }
+
+    #[test]
+    fn test_config_validation_openai_request_options() {
+        let config = Config {
+            openai_api_key: Some("test-key".to_string()),
+            openai_temperature: Some(1.5),
+            openai_response_format: "srt".to_string(),
+            ..Default::default()
+        };
+        let error = config.validate().unwrap_err();
+        let keys: Vec<&str> = error.invalid.iter().map(|setting| setting.key).collect();
+        assert_eq!(keys, vec!["OPENAI_TEMPERATURE", "OPENAI_RESPONSE_FORMAT"]);
+
+        // Only checked when OpenAI is used
+        let config = Config {
+            transcription_provider: "google".to_string(),
+            google_application_credentials: Some("/tmp/key.json".to_string()),
+            ..config
+        };
+        assert!(config.validate().is_ok());
+    }
+
     #[tokio::test]
     async fn test_config_audio_feedback_invalid_env_vars() {
         #[allow(clippy::await_holding_lock)]
@@ -554,17 +1549,32 @@ mod tests {
 
             // Test invalid volume values
             env::set_var("BEEP_VOLUME", "invalid");
-            let config = Config::from_env();
-            assert_eq!(config.beep_volume, 0.1); // Should use default
+            let error = Config::from_env().unwrap_err();
+            assert_eq!(error.invalid[0].key, "BEEP_VOLUME");
 
-            // Test volume clamping
+            // Out of range volumes are reported by validation instead of clamped
             env::set_var("BEEP_VOLUME", "2.0");
-            let config = Config::from_env();
-            assert_eq!(config.beep_volume, 1.0); // Should be clamped to 1.0
+            let config = Config::from_env().unwrap();
+            assert_eq!(config.beep_volume, 2.0);
+            assert!(config
+                .validate()
+                .unwrap_err()
+                .to_string()
+                .contains("BEEP_VOLUME=\"2\""));
+
+            // Booleans must be recognizable
+            env::remove_var("BEEP_VOLUME");
+            env::set_var("ENABLE_AUDIO_FEEDBACK", "maybe");
+            let error = Config::from_env().unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "ENABLE_AUDIO_FEEDBACK=\"maybe\": expected true or false"
+            );
 
-            env::set_var("BEEP_VOLUME", "-0.5");
-            let config = Config::from_env();
-            assert_eq!(config.beep_volume, 0.0); // Should be clamped to 0.0
+            env::set_var("ENABLE_AUDIO_FEEDBACK", "yes");
+            assert!(Config::from_env().unwrap().enable_audio_feedback);
+            env::set_var("ENABLE_AUDIO_FEEDBACK", "0");
+            assert!(!Config::from_env().unwrap().enable_audio_feedback);
 
             clear_env_vars();
         }
@@ -578,12 +1588,12 @@ mod tests {
             clear_env_vars();
 
             // Test default provider
-            let config = Config::from_env();
+            let config = Config::from_env().unwrap();
             assert_eq!(config.transcription_provider, "openai");
 
             // Test custom provider
             env::set_var("TRANSCRIPTION_PROVIDER", "google");
-            let config = Config::from_env();
+            let config = Config::from_env().unwrap();
             assert_eq!(config.transcription_provider, "google");
 
             clear_env_vars();
@@ -599,24 +1609,24 @@ mod tests {
 
             // Test that OpenAI provider requires API key
             env::set_var("TRANSCRIPTION_PROVIDER", "openai");
-            let config = Config::from_env();
+            let config = Config::from_env().unwrap();
             assert!(config.validate().is_err());
 
             // Test that OpenAI provider works with API key
             env::set_var("OPENAI_API_KEY", "test-key");
-            let config = Config::from_env();
+            let config = Config::from_env().unwrap();
             assert!(config.validate().is_ok());
 
             // Test that Google provider requires Google credentials (but not OpenAI key)
             env::remove_var("OPENAI_API_KEY");
             env::set_var("TRANSCRIPTION_PROVIDER", "google");
-            let config = Config::from_env();
+            let config = Config::from_env().unwrap();
             // This should fail validation without Google credentials
             assert!(config.validate().is_err());
 
             // Test that Google provider works with credentials
             env::set_var("GOOGLE_APPLICATION_CREDENTIALS", "/path/to/creds.json");
-            let config = Config::from_env();
+            let config = Config::from_env().unwrap();
             // This should pass validation with Google credentials (no OpenAI key needed)
             assert!(config.validate().is_ok());
 
@@ -641,7 +1651,7 @@ mod tests {
             env::set_var("GOOGLE_SPEECH_MODEL", "latest_short");
             env::set_var("GOOGLE_SPEECH_ALTERNATIVE_LANGUAGES", "en-US,fr-FR,de-DE");
 
-            let config = Config::from_env();
+            let config = Config::from_env().unwrap();
             assert_eq!(config.transcription_provider, "google");
             assert_eq!(
                 config.google_application_credentials,
@@ -670,7 +1680,7 @@ mod tests {
                 "GOOGLE_SPEECH_ALTERNATIVE_LANGUAGES",
                 "en-US, fr-FR , , de-DE,",
             );
-            let config = Config::from_env();
+            let config = Config::from_env().unwrap();
             assert_eq!(
                 config.google_speech_alternative_languages,
                 vec!["en-US", "fr-FR", "de-DE"]
@@ -678,13 +1688,44 @@ mod tests {
 
             // Test empty string
             env::set_var("GOOGLE_SPEECH_ALTERNATIVE_LANGUAGES", "");
-            let config = Config::from_env();
+            let config = Config::from_env().unwrap();
             assert!(config.google_speech_alternative_languages.is_empty());
 
             clear_env_vars();
         }
     }
 
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_whisper_allowed_languages() {
+        let _lock = ENV_MUTEX.lock().await;
+        clear_env_vars();
+
+        assert!(Config::from_env()
+            .unwrap()
+            .whisper_allowed_languages
+            .is_empty());
+
+        env::set_var("OPENAI_API_KEY", "test-key");
+        env::set_var("WHISPER_ALLOWED_LANGUAGES", "en, de,,pl");
+        let config = Config::from_env().unwrap();
+        assert_eq!(config.whisper_allowed_languages, vec!["en", "de", "pl"]);
+        assert!(config.validate().is_ok());
+
+        // Names and regional codes are not whisper language codes
+        env::set_var("WHISPER_ALLOWED_LANGUAGES", "en,german,de-DE");
+        let error = Config::from_env().unwrap().validate().unwrap_err();
+        let values: Vec<Option<&str>> = error
+            .invalid
+            .iter()
+            .map(|setting| setting.value.as_deref())
+            .collect();
+        assert_eq!(values, vec![Some("german"), Some("de-DE")]);
+        assert_eq!(error.invalid[0].key, "WHISPER_ALLOWED_LANGUAGES");
+
+        clear_env_vars();
+    }
+
     #[test]
     fn test_config_validation_google_missing_credentials() {
         let config = Config {
@@ -724,7 +1765,7 @@ mod tests {
         assert!(result
             .unwrap_err()
             .to_string()
-            .contains("Unsupported transcription provider: azure"));
+            .contains("TRANSCRIPTION_PROVIDER=\"azure\": expected a supported provider"));
     }
 
     #[tokio::test]
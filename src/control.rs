@@ -0,0 +1,377 @@
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::transcription::Transcript;
+
+/// A command sent to a running waystt over the control socket
+/// Requests and responses are single-line JSON objects, e.g.
+/// `{"command":"set-language","language":"de"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Subcommand)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Request {
+    /// Start recording
+    Start,
+    /// Stop recording, transcribe and return the transcript
+    #[serde(alias = "stop-and-transcribe")]
+    #[command(alias = "stop-and-transcribe")]
+    Stop,
+    /// Stop recording and discard the audio without transcribing
+    Cancel,
+    /// Start recording when idle, otherwise stop and transcribe
+    Toggle,
+    /// Report whether waystt is recording and with which settings
+    Status,
+    /// Transcribe in this language from now on ("auto" to detect)
+    SetLanguage { language: String },
+    /// Transcribe with this provider from now on
+    SwitchProvider { provider: String },
+}
+
+/// Reply to a control request
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// `recording` or `idle` after the request was handled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Seconds of audio recorded so far
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recording_seconds: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript: Option<Transcript>,
+}
+
+impl Response {
+    /// Failed request with a message for the client
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            error: Some(message.into()),
+            ..Self::default()
+        }
+    }
+}
+
+/// Control socket location: `$XDG_RUNTIME_DIR/waystt.sock`, or a per-user
+/// `waystt-$UID` directory in the temp directory
+pub fn socket_path() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir).join("waystt.sock"),
+        None => std::env::temp_dir()
+            .join(format!("waystt-{}", current_uid()))
+            .join("waystt.sock"),
+    }
+}
+
+fn current_uid() -> u32 {
+    // SAFETY: getuid has no preconditions and cannot fail
+    unsafe { libc::getuid() }
+}
+
+/// Check that the socket's directory is owned by and only accessible to the
+/// current user, so other users can neither connect to nor replace the socket
+/// With `create`, a missing directory is created with mode 0700.
+fn ensure_private_dir(path: &Path, create: bool) -> Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    if create && !dir.exists() {
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .map_err(|e| anyhow!("Failed to create {}: {}", dir.display(), e))?;
+    }
+
+    let metadata = std::fs::symlink_metadata(dir)
+        .map_err(|e| anyhow!("Failed to access {}: {}", dir.display(), e))?;
+    if !metadata.is_dir() || metadata.uid() != current_uid() || metadata.mode() & 0o077 != 0 {
+        return Err(anyhow!(
+            "{} must be a directory owned by and private to the current user",
+            dir.display()
+        ));
+    }
+    Ok(())
+}
+
+/// A request received on the control socket, waiting for its response
+pub struct PendingRequest {
+    pub request: Request,
+    reply: oneshot::Sender<Response>,
+}
+
+impl PendingRequest {
+    /// Send the response back to the client
+    pub fn respond(self, response: Response) {
+        // The client may have disconnected already
+        let _ = self.reply.send(response);
+    }
+}
+
+/// Listens on the control socket and hands requests to the main loop
+/// The socket file is removed again when the server is dropped.
+pub struct ControlServer {
+    path: PathBuf,
+    requests: mpsc::UnboundedReceiver<PendingRequest>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl ControlServer {
+    /// Bind the socket, replacing a stale socket file left by a crashed instance
+    pub fn bind(path: &Path) -> Result<Self> {
+        ensure_private_dir(path, true)?;
+        if path.exists() {
+            if std::os::unix::net::UnixStream::connect(path).is_ok() {
+                return Err(anyhow!(
+                    "another waystt is already listening on {}",
+                    path.display()
+                ));
+            }
+            std::fs::remove_file(path)
+                .map_err(|e| anyhow!("Failed to remove stale socket {}: {}", path.display(), e))?;
+        }
+
+        let listener = UnixListener::bind(path)
+            .map_err(|e| anyhow!("Failed to bind {}: {}", path.display(), e))?;
+        let (sender, requests) = mpsc::unbounded_channel();
+
+        let task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(handle_connection(stream, sender.clone()));
+                    }
+                    Err(e) => eprintln!("Control socket accept failed: {}", e),
+                }
+            }
+        });
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            requests,
+            task,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Wait for the next request from any client
+    pub async fn next(&mut self) -> Option<PendingRequest> {
+        self.requests.recv().await
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Answer each request line on a connection until the client hangs up
+async fn handle_connection(stream: UnixStream, sender: mpsc::UnboundedSender<PendingRequest>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                let (reply, response) = oneshot::channel();
+                if sender.send(PendingRequest { request, reply }).is_err() {
+                    return;
+                }
+                response
+                    .await
+                    .unwrap_or_else(|_| Response::error("request was dropped"))
+            }
+            Err(e) => Response::error(format!("invalid request: {}", e)),
+        };
+
+        let mut json = serde_json::to_string(&response).expect("responses always serialize");
+        json.push('\n');
+        if writer.write_all(json.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Send one request to a running waystt and wait for its response
+pub async fn send(path: &Path, request: &Request) -> Result<Response> {
+    ensure_private_dir(path, false)?;
+    let stream = UnixStream::connect(path).await.map_err(|e| {
+        anyhow!(
+            "Failed to connect to {}: {} (is waystt running?)",
+            path.display(),
+            e
+        )
+    })?;
+    let (reader, mut writer) = stream.into_split();
+
+    let mut json = serde_json::to_string(request)?;
+    json.push('\n');
+    writer.write_all(json.as_bytes()).await?;
+
+    let line = BufReader::new(reader)
+        .lines()
+        .next_line()
+        .await?
+        .ok_or_else(|| anyhow!("waystt closed the connection without responding"))?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_json() {
+        assert_eq!(
+            serde_json::to_string(&Request::SetLanguage {
+                language: "de".to_string()
+            })
+            .unwrap(),
+            r#"{"command":"set-language","language":"de"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"command":"stop-and-transcribe"}"#).unwrap(),
+            Request::Stop
+        );
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"command":"switch-provider","provider":"local"}"#)
+                .unwrap(),
+            Request::SwitchProvider {
+                provider: "local".to_string()
+            }
+        );
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"command":"toggle"}"#).unwrap(),
+            Request::Toggle
+        );
+        assert!(serde_json::from_str::<Request>(r#"{"command":"explode"}"#).is_err());
+    }
+
+    #[test]
+    fn test_response_json_omits_empty_fields() {
+        assert_eq!(
+            serde_json::to_string(&Response::error("nope")).unwrap(),
+            r#"{"ok":false,"error":"nope"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run").join("waystt.sock");
+        let mut server = ControlServer::bind(&path).unwrap();
+        assert_eq!(server.path(), path);
+
+        let client = tokio::spawn({
+            let path = path.clone();
+            async move { send(&path, &Request::Status).await }
+        });
+
+        let pending = server.next().await.unwrap();
+        assert_eq!(pending.request, Request::Status);
+        pending.respond(Response {
+            ok: true,
+            state: Some("recording".to_string()),
+            ..Response::default()
+        });
+
+        let response = client.await.unwrap().unwrap();
+        assert!(response.ok);
+        assert_eq!(response.state.as_deref(), Some("recording"));
+
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_request_gets_error_response() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run").join("waystt.sock");
+        let _server = ControlServer::bind(&path).unwrap();
+
+        let stream = UnixStream::connect(&path).await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        writer.write_all(b"not json\n").await.unwrap();
+        let line = BufReader::new(reader)
+            .lines()
+            .next_line()
+            .await
+            .unwrap()
+            .unwrap();
+
+        let response: Response = serde_json::from_str(&line).unwrap();
+        assert!(!response.ok);
+        assert!(response.error.unwrap().starts_with("invalid request"));
+    }
+
+    #[tokio::test]
+    async fn test_bind_replaces_stale_socket_but_not_live_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run").join("waystt.sock");
+
+        // A socket file nobody listens on is left behind by a crash
+        std::fs::DirBuilder::new()
+            .mode(0o700)
+            .create(path.parent().unwrap())
+            .unwrap();
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let _server = ControlServer::bind(&path).unwrap();
+        assert!(ControlServer::bind(&path).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bind_requires_private_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run").join("waystt.sock");
+        drop(ControlServer::bind(&path).unwrap());
+        let mode = std::fs::metadata(path.parent().unwrap()).unwrap().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        // A directory other users can write to may hold someone else's socket
+        std::fs::set_permissions(path.parent().unwrap(), PermissionsExt::from_mode(0o777)).unwrap();
+        assert!(ControlServer::bind(&path).is_err());
+        assert!(send(&path, &Request::Status).await.is_err());
+    }
+
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_socket_path_uses_runtime_dir() {
+        use crate::test_utils::{EnvGuard, ENV_MUTEX};
+        let _lock = ENV_MUTEX.lock().await;
+
+        let runtime_dir = EnvGuard::set("XDG_RUNTIME_DIR", "/run/user/1000");
+        assert_eq!(socket_path(), PathBuf::from("/run/user/1000/waystt.sock"));
+        drop(runtime_dir);
+
+        let _runtime_dir = EnvGuard::remove("XDG_RUNTIME_DIR");
+        let path = socket_path();
+        assert_eq!(path.file_name().unwrap(), "waystt.sock");
+        assert_eq!(
+            path.parent().unwrap(),
+            std::env::temp_dir().join(format!("waystt-{}", current_uid()))
+        );
+    }
+}
@@ -0,0 +1,409 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{DirBuilder, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// One successful transcription in the history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Unix time of the transcription in seconds
+    pub timestamp: u64,
+    pub text: String,
+    pub provider: String,
+    pub model: String,
+    /// Requested or detected language, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Length of the recording in seconds
+    pub duration: f64,
+    /// Saved recording, with `HISTORY_SAVE_AUDIO=true`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio: Option<PathBuf>,
+}
+
+/// Transcription history stored as `history.jsonl`, one entry per line,
+/// with saved recordings in `audio/`
+/// Only the owner can read the files. Entries beyond the retention limits are
+/// dropped, together with their audio, whenever a new one is added.
+pub struct History {
+    dir: PathBuf,
+    /// Maximum number of entries kept, 0 for no limit
+    max_entries: usize,
+    /// Maximum age of entries in days, 0 for no limit
+    max_age_days: u64,
+}
+
+impl History {
+    pub fn new(dir: impl Into<PathBuf>, max_entries: usize, max_age_days: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            max_entries,
+            max_age_days,
+        }
+    }
+
+    /// The history under `$XDG_DATA_HOME/waystt/history` (usually `~/.local/share`)
+    /// with the retention limits from the configuration
+    pub fn open_default(config: &Config) -> Self {
+        let data_dir = dirs::data_dir().unwrap_or_else(|| {
+            dirs::home_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(".local/share")
+        });
+        Self::new(
+            data_dir.join("waystt").join("history"),
+            config.history_max_entries,
+            config.history_max_age_days,
+        )
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Add an entry, saving `wav_data` next to it if given, then apply the retention limits
+    pub fn append(&self, mut entry: HistoryEntry, wav_data: Option<&[u8]>) -> Result<()> {
+        create_private_dir(&self.dir)?;
+
+        if let Some(wav_data) = wav_data {
+            let audio_dir = self.dir.join("audio");
+            create_private_dir(&audio_dir)?;
+            let path = unique_path(&audio_dir);
+            private_file(&path, false)?.write_all(wav_data)?;
+            entry.audio = Some(path);
+        }
+
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        private_file(&self.log_path(), true)?
+            .write_all(line.as_bytes())
+            .map_err(|e| anyhow!("Failed to write {}: {}", self.log_path().display(), e))?;
+
+        self.prune(entry.timestamp)
+    }
+
+    /// Every entry, oldest first
+    /// Lines that cannot be parsed, e.g. after a partial write, are skipped.
+    pub fn entries(&self) -> Result<Vec<HistoryEntry>> {
+        let log = match std::fs::read_to_string(self.log_path()) {
+            Ok(log) => log,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(anyhow!(
+                    "Failed to read {}: {}",
+                    self.log_path().display(),
+                    e
+                ))
+            }
+        };
+
+        Ok(log
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Drop entries older than the age limit or beyond the entry limit at time `now`
+    pub fn prune(&self, now: u64) -> Result<()> {
+        let entries = self.entries()?;
+        let oldest = if self.max_age_days == 0 {
+            0
+        } else {
+            now.saturating_sub(self.max_age_days * SECONDS_PER_DAY)
+        };
+        let (mut kept, mut dropped): (Vec<_>, Vec<_>) = entries
+            .into_iter()
+            .partition(|entry| entry.timestamp >= oldest);
+        if self.max_entries > 0 && kept.len() > self.max_entries {
+            let excess = kept.len() - self.max_entries;
+            dropped.extend(kept.drain(..excess));
+        }
+        if dropped.is_empty() {
+            return Ok(());
+        }
+
+        for path in dropped.iter().filter_map(|entry| entry.audio.as_ref()) {
+            let _ = std::fs::remove_file(path);
+        }
+        self.rewrite(&kept)
+    }
+
+    /// Delete every entry and saved recording, returning how many entries there were
+    pub fn clear(&self) -> Result<usize> {
+        let count = self.entries()?.len();
+        match std::fs::remove_dir_all(&self.dir) {
+            Ok(()) => Ok(count),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(count),
+            Err(e) => Err(anyhow!("Failed to delete {}: {}", self.dir.display(), e)),
+        }
+    }
+
+    fn rewrite(&self, entries: &[HistoryEntry]) -> Result<()> {
+        // Write a new log and swap it in so readers never see a partial file
+        let temp_path = self.dir.join("history.jsonl.tmp");
+        let mut file = private_file(&temp_path, false)?;
+        for entry in entries {
+            writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        }
+        std::fs::rename(&temp_path, self.log_path())
+            .map_err(|e| anyhow!("Failed to replace {}: {}", self.log_path().display(), e))
+    }
+
+    fn log_path(&self) -> PathBuf {
+        self.dir.join("history.jsonl")
+    }
+}
+
+/// The `n`th most recent entry, counting from 1
+pub fn nth_newest(entries: &[HistoryEntry], n: usize) -> Option<&HistoryEntry> {
+    n.checked_sub(1)
+        .and_then(|index| entries.iter().rev().nth(index))
+}
+
+/// Entries whose text contains `query`, ignoring case, newest first with their numbers
+pub fn search<'a>(entries: &'a [HistoryEntry], query: &str) -> Vec<(usize, &'a HistoryEntry)> {
+    let query = query.to_lowercase();
+    entries
+        .iter()
+        .rev()
+        .enumerate()
+        .filter(|(_, entry)| entry.text.to_lowercase().contains(&query))
+        .map(|(index, entry)| (index + 1, entry))
+        .collect()
+}
+
+/// First line of `text`, shortened to `max_chars` characters
+pub fn preview(text: &str, max_chars: usize) -> String {
+    let line = text.trim().lines().next().unwrap_or_default();
+    if line.chars().count() <= max_chars && !text.trim().contains('\n') {
+        return line.to_string();
+    }
+    let shortened: String = line.chars().take(max_chars.saturating_sub(1)).collect();
+    format!("{}…", shortened.trim_end())
+}
+
+/// Format a Unix time as `YYYY-MM-DD HH:MM:SS UTC`
+pub fn format_utc(timestamp: u64) -> String {
+    let days = timestamp / SECONDS_PER_DAY;
+    let seconds = timestamp % SECONDS_PER_DAY;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Create a directory, and any missing parents, that only the owner can access
+pub fn create_private_dir(path: &Path) -> Result<()> {
+    DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(path)
+        .map_err(|e| anyhow!("Failed to create {}: {}", path.display(), e))
+}
+
+/// Open a file for writing, creating it readable only by the owner
+pub fn private_file(path: &Path, append: bool) -> Result<std::fs::File> {
+    OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .mode(0o600)
+        .open(path)
+        .map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))
+}
+
+/// A WAV path in `dir` named after the current time in milliseconds
+fn unique_path(dir: &Path) -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let mut path = dir.join(format!("{}.wav", millis));
+    let mut suffix = 1;
+    while path.exists() {
+        suffix += 1;
+        path = dir.join(format!("{}-{}.wav", millis, suffix));
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn entry(timestamp: u64, text: &str) -> HistoryEntry {
+        HistoryEntry {
+            timestamp,
+            text: text.to_string(),
+            provider: "openai".to_string(),
+            model: "whisper-1".to_string(),
+            language: Some("en".to_string()),
+            duration: 1.5,
+            audio: None,
+        }
+    }
+
+    #[test]
+    fn test_append_and_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path().join("history"), 0, 0);
+        assert!(history.entries().unwrap().is_empty());
+
+        history.append(entry(100, "first"), None).unwrap();
+        history.append(entry(200, "second"), None).unwrap();
+
+        let entries = history.entries().unwrap();
+        assert_eq!(entries, vec![entry(100, "first"), entry(200, "second")]);
+        assert_eq!(nth_newest(&entries, 1).unwrap().text, "second");
+        assert_eq!(nth_newest(&entries, 2).unwrap().text, "first");
+        assert!(nth_newest(&entries, 0).is_none());
+        assert!(nth_newest(&entries, 3).is_none());
+    }
+
+    #[test]
+    fn test_files_are_private() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path().join("history"), 0, 0);
+        history.append(entry(100, "secret"), Some(b"RIFF")).unwrap();
+
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(history.dir()), 0o700);
+        assert_eq!(mode(&history.dir().join("history.jsonl")), 0o600);
+
+        let audio = history.entries().unwrap()[0].audio.clone().unwrap();
+        assert_eq!(mode(&audio), 0o600);
+        assert_eq!(std::fs::read(audio).unwrap(), b"RIFF");
+    }
+
+    #[test]
+    fn test_entry_limit_drops_oldest_with_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path(), 2, 0);
+        history
+            .append(entry(100, "one"), Some(b"RIFF one"))
+            .unwrap();
+        let first_audio = history.entries().unwrap()[0].audio.clone().unwrap();
+
+        history.append(entry(200, "two"), None).unwrap();
+        history.append(entry(300, "three"), None).unwrap();
+
+        let texts: Vec<String> = history
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.text)
+            .collect();
+        assert_eq!(texts, vec!["two", "three"]);
+        assert!(!first_audio.exists());
+    }
+
+    #[test]
+    fn test_age_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path(), 0, 1);
+        history.append(entry(0, "old"), None).unwrap();
+        history
+            .append(entry(2 * SECONDS_PER_DAY, "new"), None)
+            .unwrap();
+
+        let entries = history.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].text, "new");
+    }
+
+    #[test]
+    fn test_clear() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path().join("history"), 0, 0);
+        assert_eq!(history.clear().unwrap(), 0);
+
+        history.append(entry(100, "one"), Some(b"RIFF")).unwrap();
+        history.append(entry(200, "two"), None).unwrap();
+        assert_eq!(history.clear().unwrap(), 2);
+        assert!(!history.dir().exists());
+        assert!(history.entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_malformed_lines_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path(), 0, 0);
+        history.append(entry(100, "kept"), None).unwrap();
+        let mut log = OpenOptions::new()
+            .append(true)
+            .open(dir.path().join("history.jsonl"))
+            .unwrap();
+        writeln!(log, "{{\"timestamp\":").unwrap();
+
+        assert_eq!(history.entries().unwrap(), vec![entry(100, "kept")]);
+    }
+
+    #[test]
+    fn test_search_numbers_newest_first() {
+        let entries = vec![
+            entry(100, "Buy milk"),
+            entry(200, "Call the bank"),
+            entry(300, "buy bread"),
+        ];
+        let found: Vec<(usize, &str)> = search(&entries, "BUY")
+            .into_iter()
+            .map(|(n, entry)| (n, entry.text.as_str()))
+            .collect();
+        assert_eq!(found, vec![(1, "buy bread"), (3, "Buy milk")]);
+        assert!(search(&entries, "train").is_empty());
+    }
+
+    #[test]
+    fn test_preview() {
+        assert_eq!(preview("  short  ", 10), "short");
+        assert_eq!(preview("a longer sentence", 10), "a longer…");
+        assert_eq!(preview("first line\nsecond", 40), "first line…");
+    }
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_utc(951_782_400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_utc(1_718_000_000), "2024-06-10 06:13:20 UTC");
+    }
+
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_default_history_uses_data_dir() {
+        use crate::test_utils::{EnvGuard, ENV_MUTEX};
+        let _lock = ENV_MUTEX.lock().await;
+
+        let _data_home = EnvGuard::set("XDG_DATA_HOME", "/tmp/data");
+        assert_eq!(
+            History::open_default(&Config::default()).dir(),
+            Path::new("/tmp/data/waystt/history")
+        );
+    }
+}
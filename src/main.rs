@@ -10,16 +10,17 @@
 #![allow(clippy::single_match_else)]
 #![allow(clippy::match_bool)]
 
-use anyhow::{anyhow, Result};
-use clap::Parser;
-use std::{io::Write, path::PathBuf};
+#[cfg(not(test))]
+use anyhow::anyhow;
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
-use std::time::Instant;
-use tokio::io::AsyncWriteExt;
+use std::time::{Duration, Instant};
 
 use futures::stream::StreamExt;
 #[cfg(not(test))]
-use signal_hook::consts::{SIGTERM, SIGUSR1};
+use signal_hook::consts::{SIGTERM, SIGUSR1, SIGUSR2};
 #[cfg(not(test))]
 use signal_hook_tokio::Signals;
 
@@ -28,27 +29,63 @@ mod audio_processing;
 mod beep;
 mod command;
 mod config;
+mod control;
+mod history;
+mod models;
+mod output;
+mod profile;
+mod resample;
+mod ring_buffer;
+mod spool;
 mod transcription;
+mod vad;
 mod wav;
 
 #[cfg(test)]
 mod test_utils;
-use audio::AudioRecorder;
+use audio::{AudioRecorder, RecorderConfig};
 use audio_processing::AudioProcessor;
 use beep::{BeepConfig, BeepPlayer, BeepType};
 use config::Config;
-use transcription::{TranscriptionError, TranscriptionFactory};
+use output::{OutputFormat, TranscriptionMetadata};
+use transcription::{Transcript, TranscriptionError, TranscriptionFactory, TranscriptionProvider};
+#[cfg(not(test))]
+use vad::{VadConfig, VadEvent, VoiceActivityDetector};
 use wav::WavEncoder;
 
+// Exit code when a recording is cancelled, outside the 128 + signal range shells
+// report for killed processes, so it cannot be mistaken for Ctrl+C
+#[cfg(not(test))]
+const CANCELLED_EXIT_CODE: i32 = 3;
+
+// Exit code when SIGTERM interrupts a transcription, 128 + SIGTERM
+const TERMINATED_EXIT_CODE: i32 = 143;
+
+// Audio muted after the start beep stops playing, covering output and input latency
+const BEEP_TAIL_MS: u64 = 100;
+
+// Audio kept after each detected utterance in continuous mode; before it, the pre-roll is kept
+#[cfg(not(test))]
+const UTTERANCE_PADDING_MS: u64 = 200;
+
 #[derive(Parser)]
 #[command(name = "waystt")]
 #[command(about = "Wayland Speech-to-Text Tool - Signal-driven transcription")]
 #[command(version)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path to environment file
-    #[arg(long)]
+    #[arg(long, global = true)]
     envfile: Option<PathBuf>,
 
+    /// Use the settings of a [profile.NAME] table in ~/.config/waystt/config.toml
+    /// Environment variables and the environment file still take precedence.
+    /// Example: waystt --profile meeting
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+
     /// Pipe transcribed text to the specified command
     /// Usage: waystt --pipe-to command args
     /// Example: waystt --pipe-to wl-copy
@@ -59,6 +96,168 @@ struct Args {
     /// Download the configured local model and exit
     #[arg(long)]
     download_model: bool,
+
+    /// List available audio input devices and exit
+    #[arg(long)]
+    list_devices: bool,
+
+    /// Stop and transcribe automatically after this much silence following speech
+    /// Example: waystt --auto-stop 1500ms
+    #[arg(long, value_name = "DURATION", value_parser = vad::parse_duration)]
+    auto_stop: Option<Duration>,
+
+    /// Keep recording and transcribe each utterance as it ends, until SIGTERM or SIGUSR2
+    /// The silence that ends an utterance can be set with --auto-stop
+    #[arg(long)]
+    continuous: bool,
+
+    /// Stream audio to the provider while recording and show partial results on stderr
+    /// Providers without native streaming transcribe once recording stops
+    #[arg(long, conflicts_with_all = ["continuous", "auto_stop"])]
+    stream: bool,
+
+    /// Stay running with the provider loaded, idle until SIGUSR1 starts a recording
+    /// The next SIGUSR1 transcribes it and returns to idle; SIGTERM exits
+    #[arg(long, conflicts_with_all = ["continuous", "stream"])]
+    daemon: bool,
+
+    /// Format of the transcription written to stdout or the pipe command
+    #[arg(long, global = true, value_enum, value_name = "FORMAT", default_value_t = OutputFormat::Text)]
+    output_format: OutputFormat,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Transcribe existing WAV files instead of recording, printing one result per file
+    ///
+    /// Example: waystt transcribe --output-format srt meeting.wav
+    Transcribe {
+        /// WAV files to transcribe, in order
+        #[arg(required = true, value_name = "FILE")]
+        files: Vec<PathBuf>,
+
+        /// Pipe each transcription to the specified command; must follow the files
+        /// Example: waystt transcribe notes.wav --pipe-to wl-copy
+        #[arg(long, short = 'p', num_args = 1.., value_name = "COMMAND", allow_hyphen_values = true)]
+        pipe_to: Option<Vec<String>>,
+    },
+    /// Transcribe recordings saved after a failed transcription again
+    ///
+    /// Without an ID or --all, lists the saved recordings. Recordings are
+    /// deleted once they have been transcribed and output successfully.
+    /// Example: waystt retry --all --pipe-to wl-copy
+    Retry {
+        /// ID of the saved recording to retry
+        #[arg(value_name = "ID", conflicts_with = "all")]
+        id: Option<String>,
+
+        /// Retry every saved recording, oldest first
+        #[arg(long)]
+        all: bool,
+
+        /// Pipe each transcription to the specified command
+        #[arg(long, short = 'p', num_args = 1.., value_name = "COMMAND", allow_hyphen_values = true)]
+        pipe_to: Option<Vec<String>>,
+    },
+    /// Read back past transcriptions saved with HISTORY_ENABLED=true
+    ///
+    /// Entries are numbered from the most recent, which is 1.
+    /// Example: waystt history copy 2
+    History {
+        #[command(subcommand)]
+        action: HistoryCommand,
+    },
+    /// List, inspect, verify, download and remove local whisper models
+    ///
+    /// NAME is a catalog name such as base.en or a file name such as
+    /// ggml-base.en.bin, and defaults to WHISPER_MODEL.
+    /// Example: waystt models verify large-v3
+    Models {
+        #[command(subcommand)]
+        action: ModelsCommand,
+    },
+    /// Inspect the configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// Send a command to a running waystt over its control socket
+    ///
+    /// Example: waystt ctl stop | wl-copy
+    Ctl {
+        #[command(subcommand)]
+        request: control::Request,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print every setting with its effective value and where it came from,
+    /// then validate the configuration
+    ///
+    /// Secrets are masked. Exits non-zero if the configuration is invalid.
+    Check,
+}
+
+#[derive(Subcommand)]
+enum ModelsCommand {
+    /// List catalog models and the models in the model directory
+    List,
+    /// Show the size, source and checksum of a model
+    Info {
+        #[arg(value_name = "NAME")]
+        name: Option<String>,
+    },
+    /// Check a downloaded model against its SHA-256 checksum
+    ///
+    /// Exits non-zero if the model is missing or does not match.
+    Verify {
+        #[arg(value_name = "NAME")]
+        name: Option<String>,
+    },
+    /// Download a model, replacing it only once the download is complete
+    Download {
+        #[arg(value_name = "NAME")]
+        name: Option<String>,
+    },
+    /// Delete a downloaded model
+    Remove {
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// List the most recent transcriptions
+    List {
+        /// Number of entries to show
+        #[arg(long, short = 'n', default_value_t = 20)]
+        limit: usize,
+    },
+    /// Print a transcription to stdout, with its details on stderr
+    Show {
+        /// Entry number, 1 being the most recent
+        #[arg(value_name = "N")]
+        number: usize,
+    },
+    /// List transcriptions containing the given text, ignoring case
+    Search {
+        #[arg(value_name = "TEXT")]
+        query: String,
+    },
+    /// Send a transcription to the clipboard with wl-copy, or to another command
+    Copy {
+        /// Entry number, 1 being the most recent
+        #[arg(value_name = "N")]
+        number: usize,
+
+        /// Pipe the text to this command instead of wl-copy
+        #[arg(long, short = 'p', num_args = 1.., value_name = "COMMAND", allow_hyphen_values = true)]
+        pipe_to: Option<Vec<String>>,
+    },
+    /// Delete all saved transcriptions and recordings
+    Clear,
 }
 
 fn get_default_config_path() -> PathBuf {
@@ -68,72 +267,154 @@ fn get_default_config_path() -> PathBuf {
         .join(".env")
 }
 
-async fn download_model(model: &str) -> Result<PathBuf> {
-    let base_url = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
-    let url = format!("{}/{}", base_url, model);
-    let dir = Config::model_dir();
-    tokio::fs::create_dir_all(&dir).await?;
-    let path = dir.join(model);
+/// Download a model from the configured mirror into the model directory
+/// `name` is a catalog name such as `base.en` or a file name.
+async fn download_model(config: &Config, name: &str) -> Result<PathBuf> {
+    models::check_name(name)?;
+    let file = models::file_name(name);
+    let path = Config::model_path(&file);
+    let url = models::model_url(&config.whisper_model_mirror, &file);
+    let pinned = models::find(name).map(|model| model.sha256);
+    models::download(&url, &path, pinned).await?;
+    Ok(path)
+}
 
-    let resp = reqwest::get(&url).await.map_err(|e| anyhow!("{}", e))?;
-    if !resp.status().is_success() {
-        return Err(anyhow!("Failed to download model: {}", resp.status()));
+/// Print input devices in the format expected by `AUDIO_INPUT_DEVICE`
+fn print_input_devices(devices: &[audio::InputDeviceInfo]) {
+    if devices.is_empty() {
+        println!("No audio input devices found");
+        return;
     }
 
-    let total_size = resp.content_length();
-    let mut file = tokio::fs::File::create(&path).await?;
-    let mut stream = resp.bytes_stream();
+    println!("Audio input devices (set AUDIO_INPUT_DEVICE to an index or name):");
+    for device in devices {
+        let default_marker = if device.is_default { " (default)" } else { "" };
+        println!("  {}: {}{}", device.index, device.name, default_marker);
+        for config in &device.configs {
+            println!("       {}", config);
+        }
+    }
+}
 
-    let mut downloaded = 0u64;
-    let start_time = Instant::now();
+/// Render a transcription, send it to the pipe command or stdout and play the matching beep
+/// Returns the exit code of the pipe command, or 1 if it could not be run
+async fn output_transcription(
+    transcript: &Transcript,
+    metadata: &TranscriptionMetadata,
+    output_format: OutputFormat,
+    pipe_command: Option<&Vec<String>>,
+    beep_player: &BeepPlayer,
+) -> i32 {
+    eprintln!("Transcription successful: \"{}\"", transcript.text);
+    let output = output::render(output_format, transcript, metadata);
+
+    // Handle piping to command or stdout
+    let exit_code = if let Some(cmd) = pipe_command {
+        match command::execute_with_input(cmd, &output).await {
+            Ok(exit_code) => exit_code,
+            Err(e) => {
+                eprintln!("Failed to execute pipe command: {}", e);
+                // Play error beep for command execution failure
+                if let Err(beep_err) = beep_player.play_async(BeepType::Error).await {
+                    eprintln!("Warning: Failed to play error beep: {}", beep_err);
+                }
+                return 1;
+            }
+        }
+    } else {
+        // Output transcription to stdout (existing behavior)
+        println!("{}", output);
+        0
+    };
 
-    print!("{}... ", model);
-    std::io::stdout().flush().unwrap();
+    // Play success beep after successful transcription
+    if let Err(e) = beep_player.play_async(BeepType::Success).await {
+        eprintln!("Warning: Failed to play success beep: {}", e);
+    }
 
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| anyhow!("Download error: {}", e))?;
-        file.write_all(&chunk).await?;
-        downloaded += chunk.len() as u64;
+    exit_code
+}
 
-        if let Some(total) = total_size {
-            let percentage = (downloaded as f64 / total as f64) * 100.0;
-            let elapsed = start_time.elapsed().as_secs_f64();
+/// Run `future` to completion unless `terminate` completes first
+/// The future is dropped when terminated, which stops local whisper inference.
+async fn until_terminated<F, T>(future: F, terminate: T) -> Option<F::Output>
+where
+    F: std::future::Future,
+    T: std::future::Future,
+{
+    tokio::select! {
+        output = future => Some(output),
+        _ = terminate => None,
+    }
+}
 
-            if elapsed > 0.0 {
-                let speed = downloaded as f64 / elapsed / 1024.0 / 1024.0; // MB/s
-                let eta = if speed > 0.0 {
-                    (total - downloaded) as f64 / (speed * 1024.0 * 1024.0)
-                } else {
-                    0.0
-                };
+/// Completes when SIGTERM arrives, or never if it cannot be listened for
+/// Only for modes that handle SIGTERM themselves, as listening replaces the
+/// default action of terminating the process.
+#[cfg(not(test))]
+async fn sigterm() {
+    use tokio::signal::unix::{signal, SignalKind};
 
-                print!(
-                    "\r{}... {:.1}% ({:.1} MB/s, ETA: {:.0}s)    ",
-                    model, percentage, speed, eta
-                );
-                std::io::stdout().flush().unwrap();
-            }
-        } else {
-            print!(
-                "\r{}... {:.1} MB downloaded    ",
-                model,
-                downloaded as f64 / 1024.0 / 1024.0
-            );
-            std::io::stdout().flush().unwrap();
+    match signal(SignalKind::terminate()) {
+        Ok(mut terminate) => {
+            terminate.recv().await;
         }
+        Err(_) => std::future::pending().await,
     }
-
-    file.flush().await?;
-    Ok(path)
 }
 
-/// Process recorded audio for transcription
+/// Process recorded audio for transcription with the configured provider
+/// Audio the provider could not transcribe is saved for `waystt retry`.
+/// Returns the exit code and, if transcription succeeded, the transcript.
 async fn process_audio_for_transcription(
     audio_data: Vec<f32>,
     sample_rate: u32,
     config: &Config,
     pipe_command: Option<&Vec<String>>,
-) -> Result<i32> {
+    output_format: OutputFormat,
+    terminate: impl std::future::Future,
+) -> Result<(i32, Option<Transcript>)> {
+    transcribe_recording(
+        audio_data,
+        sample_rate,
+        config,
+        pipe_command,
+        output_format,
+        None,
+        AudioSource::Recording,
+        terminate,
+    )
+    .await
+}
+
+/// Where the audio given to `transcribe_recording` comes from
+#[derive(Debug, Clone, Copy)]
+enum AudioSource<'a> {
+    /// Audio recorded by waystt, saved for `waystt retry` if it fails to transcribe
+    Recording,
+    /// A file given to `waystt transcribe`, named in the JSON metadata
+    File(&'a std::path::Path),
+    /// A recording saved earlier, which `waystt retry` removes once it succeeds
+    Spooled,
+}
+
+/// Process recorded audio with an already initialized provider, or create one from the configuration
+/// Audio recorded by waystt that the provider could not transcribe is saved for `waystt retry`.
+/// `terminate` interrupts the provider, returning 143 after saving the recording;
+/// once the provider has answered, output and history always finish.
+/// Returns the exit code and, if transcription succeeded, the transcript.
+#[allow(clippy::too_many_arguments)]
+async fn transcribe_recording(
+    audio_data: Vec<f32>,
+    sample_rate: u32,
+    config: &Config,
+    pipe_command: Option<&Vec<String>>,
+    output_format: OutputFormat,
+    provider: Option<&dyn TranscriptionProvider>,
+    source: AudioSource<'_>,
+    terminate: impl std::future::Future,
+) -> Result<(i32, Option<Transcript>)> {
+    let spool_failures = matches!(source, AudioSource::Recording);
     // Initialize beep player
     let beep_config = BeepConfig {
         enabled: config.enable_audio_feedback,
@@ -142,6 +423,15 @@ async fn process_audio_for_transcription(
     let beep_player = BeepPlayer::new(beep_config)?;
     eprintln!("Processing audio: {} samples", audio_data.len());
 
+    // Speech recognition works on mono audio
+    let audio_data = if config.audio_channels > 1 {
+        let mut mono = Vec::with_capacity(audio_data.len() / usize::from(config.audio_channels));
+        resample::convert_channels(&audio_data, config.audio_channels, 1, &mut mono);
+        mono
+    } else {
+        audio_data
+    };
+
     // Initialize audio processor
     let processor = AudioProcessor::new(sample_rate);
 
@@ -167,10 +457,39 @@ async fn process_audio_for_transcription(
                         wav_data.len()
                     );
 
-                    // Initialize transcription provider with configuration
-                    let provider =
-                        TranscriptionFactory::create_provider(&config.transcription_provider)
-                            .await?;
+                    // Initialize transcription provider with configuration unless one was given
+                    let created;
+                    let provider = match provider {
+                        Some(provider) => provider,
+                        None => match TranscriptionFactory::create_provider(
+                            &config.transcription_provider,
+                            config,
+                        )
+                        .await
+                        {
+                            Ok(provider) => {
+                                created = provider;
+                                created.as_ref()
+                            }
+                            Err(e) => {
+                                eprintln!("❌ Failed to initialize transcription provider: {}", e);
+                                if let Err(beep_err) = beep_player.play_async(BeepType::Error).await
+                                {
+                                    eprintln!("Warning: Failed to play error beep: {}", beep_err);
+                                }
+                                // Keep the recording, e.g. to retry once the API key or model is set up
+                                if spool_failures {
+                                    spool_recording(
+                                        &wav_data,
+                                        config,
+                                        f64::from(processed_duration),
+                                        &e,
+                                    );
+                                }
+                                return Ok((1, None));
+                            }
+                        },
+                    };
 
                     // Send to transcription service
                     eprintln!(
@@ -182,76 +501,71 @@ async fn process_audio_for_transcription(
                     } else {
                         Some(config.whisper_language.clone())
                     };
-                    match provider.transcribe_with_language(wav_data, language).await {
-                        Ok(transcribed_text) => {
-                            if transcribed_text.trim().is_empty() {
+                    // Keep a copy to save if the provider fails, or with the history entry
+                    let wav_copy = (spool_failures
+                        || (config.history_enabled && config.history_save_audio))
+                        .then(|| wav_data.clone());
+                    let started = Instant::now();
+                    let detect_language = language.is_none();
+                    let transcription = provider.transcribe_with_language(wav_data, language);
+                    let Some(transcription) = until_terminated(transcription, terminate).await
+                    else {
+                        eprintln!("Received SIGTERM: Transcription cancelled");
+                        if let Some(wav_data) = wav_copy.filter(|_| spool_failures) {
+                            spool_recording(
+                                &wav_data,
+                                config,
+                                f64::from(processed_duration),
+                                &"Transcription interrupted by SIGTERM",
+                            );
+                        }
+                        return Ok((TERMINATED_EXIT_CODE, None));
+                    };
+                    match transcription {
+                        Ok(transcript) => {
+                            if transcript.text.trim().is_empty() {
                                 eprintln!("Warning: Received empty transcription from Whisper API");
                                 eprintln!("This might indicate silent audio or unclear speech");
-
-                                // Empty transcription is still a successful transcription, so pipe it
-                                let exit_code = if let Some(cmd) = pipe_command {
-                                    match command::execute_with_input(cmd, "").await {
-                                        Ok(exit_code) => exit_code,
-                                        Err(e) => {
-                                            eprintln!("Failed to execute pipe command: {}", e);
-                                            // Play error beep for command execution failure
-                                            if let Err(beep_err) =
-                                                beep_player.play_async(BeepType::Error).await
-                                            {
-                                                eprintln!(
-                                                    "Warning: Failed to play error beep: {}",
-                                                    beep_err
-                                                );
-                                            }
-                                            1
-                                        }
-                                    }
-                                } else {
-                                    // Output empty transcription to stdout (existing behavior)
-                                    println!("{}", transcribed_text);
-                                    0
-                                };
-
-                                // Play success beep for successful (but empty) transcription
-                                if let Err(e) = beep_player.play_async(BeepType::Success).await {
-                                    eprintln!("Warning: Failed to play success beep: {}", e);
-                                }
-
-                                return Ok(exit_code);
+                                // Empty transcription is still a successful transcription, so output it
                             }
-
-                            eprintln!("Transcription successful: \"{}\"", transcribed_text);
-
-                            // Handle piping to command or stdout
-                            let exit_code = if let Some(cmd) = pipe_command {
-                                match command::execute_with_input(cmd, &transcribed_text).await {
-                                    Ok(exit_code) => exit_code,
-                                    Err(e) => {
-                                        eprintln!("Failed to execute pipe command: {}", e);
-                                        // Play error beep for command execution failure
-                                        if let Err(beep_err) =
-                                            beep_player.play_async(BeepType::Error).await
-                                        {
-                                            eprintln!(
-                                                "Warning: Failed to play error beep: {}",
-                                                beep_err
-                                            );
-                                        }
-                                        return Ok(1);
-                                    }
+                            if let Some(detected) =
+                                transcript.language.as_ref().filter(|_| detect_language)
+                            {
+                                match transcript.language_probability {
+                                    Some(p) => eprintln!(
+                                        "Detected language: {} ({:.0}%)",
+                                        detected,
+                                        p * 100.0
+                                    ),
+                                    None => eprintln!("Detected language: {}", detected),
                                 }
-                            } else {
-                                // Output transcribed text to stdout (existing behavior)
-                                println!("{}", transcribed_text);
-                                0
-                            };
-
-                            // Play success beep after successful transcription
-                            if let Err(e) = beep_player.play_async(BeepType::Success).await {
-                                eprintln!("Warning: Failed to play success beep: {}", e);
                             }
 
-                            Ok(exit_code)
+                            // A fallback chain reports which of its providers answered
+                            let provider_name = transcript
+                                .provider
+                                .clone()
+                                .unwrap_or_else(|| config.transcription_provider.clone());
+                            let metadata = TranscriptionMetadata {
+                                model: config.model_for(&provider_name).to_string(),
+                                provider: provider_name,
+                                audio_duration: f64::from(original_duration),
+                                processing_latency: started.elapsed().as_secs_f64(),
+                                file: match source {
+                                    AudioSource::File(path) => Some(path.display().to_string()),
+                                    AudioSource::Recording | AudioSource::Spooled => None,
+                                },
+                            };
+                            let exit_code = output_transcription(
+                                &transcript,
+                                &metadata,
+                                output_format,
+                                pipe_command,
+                                &beep_player,
+                            )
+                            .await;
+                            record_history(config, &transcript, &metadata, wav_copy.as_deref());
+                            Ok((exit_code, Some(transcript)))
                         }
                         Err(e) => {
                             eprintln!("❌ Transcription failed: {}", e);
@@ -361,8 +675,17 @@ async fn process_audio_for_transcription(
                                 }
                             }
 
+                            if let Some(wav_data) = wav_copy.filter(|_| spool_failures) {
+                                spool_recording(
+                                    &wav_data,
+                                    config,
+                                    f64::from(processed_duration),
+                                    &e,
+                                );
+                            }
+
                             // Don't execute pipe command when transcription fails
-                            Ok(1) // Return exit code 1 for transcription failure
+                            Ok((1, None)) // Return exit code 1 for transcription failure
                         }
                     }
                 }
@@ -370,42 +693,1407 @@ async fn process_audio_for_transcription(
                     eprintln!("Failed to encode WAV: {}", e);
 
                     // Don't execute pipe command when WAV encoding fails
-                    Ok(1) // Return exit code 1 for WAV encoding failure
+                    Ok((1, None)) // Return exit code 1 for WAV encoding failure
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("Audio processing failed: {}", e);
+
+            // Play error beep for audio processing failure
+            if let Err(beep_err) = beep_player.play_async(BeepType::Error).await {
+                eprintln!("Warning: Failed to play error beep: {}", beep_err);
+            }
+
+            if e.to_string().contains("too short") {
+                eprintln!("Tip: Try speaking for at least 0.1 seconds before sending signal");
+            } else if e.to_string().contains("only silence") {
+                eprintln!("Tip: Make sure your microphone is working and you're speaking clearly");
+            }
+
+            // Don't execute pipe command when audio processing fails
+            Ok((1, None)) // Return exit code 1 for audio processing failure
+        }
+    }
+}
+
+/// Save audio that failed to transcribe and tell the user how to retry it
+fn spool_recording(wav_data: &[u8], config: &Config, duration: f64, error: &dyn std::fmt::Display) {
+    let details = spool::FailedRecording {
+        failed_at: spool::unix_time(),
+        provider: config.transcription_provider.clone(),
+        language: config.whisper_language.clone(),
+        duration,
+        error: error.to_string(),
+    };
+    match spool::Spool::open_default().save(wav_data, details) {
+        Ok(saved) => {
+            eprintln!("💾 Recording saved to {}", saved.wav_path.display());
+            eprintln!("💡 Run `waystt retry {}` to transcribe it again", saved.id);
+        }
+        Err(e) => eprintln!("Warning: Failed to save the recording for retry: {}", e),
+    }
+}
+
+/// Save recorded audio whose transcription SIGTERM interrupted
+/// The audio is stored as recorded, `waystt retry` processes it like a new recording.
+fn spool_interrupted(audio_data: &[f32], sample_rate: u32, config: &Config) {
+    let mut mono = Vec::with_capacity(audio_data.len() / usize::from(config.audio_channels));
+    resample::convert_channels(audio_data, config.audio_channels, 1, &mut mono);
+    let duration = mono.len() as f64 / f64::from(sample_rate);
+    match WavEncoder::new(sample_rate, 1).encode_to_wav(&mono) {
+        Ok(wav_data) => spool_recording(
+            &wav_data,
+            config,
+            duration,
+            &"Transcription interrupted by SIGTERM",
+        ),
+        Err(e) => eprintln!("Warning: Failed to save the recording for retry: {}", e),
+    }
+}
+
+/// Convert decoded file audio to the configured recording rate and channel layout
+fn convert_file_audio(audio: wav::DecodedAudio, config: &Config) -> Vec<f32> {
+    let mut converted = Vec::with_capacity(audio.samples.len());
+    resample::convert_channels(
+        &audio.samples,
+        audio.channels,
+        config.audio_channels,
+        &mut converted,
+    );
+
+    let mut resampler = resample::Resampler::new(
+        audio.sample_rate,
+        config.audio_sample_rate,
+        config.audio_channels,
+    );
+    if resampler.is_passthrough() {
+        return converted;
+    }
+
+    let mut resampled = Vec::new();
+    resampler.process(&converted, &mut resampled);
+    resampled
+}
+
+/// Transcribe WAV files through the same pipeline as recordings, one output per file
+/// Several outputs on stdout are each preceded by a `==> FILE <==` header, and
+/// JSON names the file in its metadata. Returns 0 if every file was transcribed, otherwise the last failing exit code
+async fn transcribe_files(
+    files: &[PathBuf],
+    config: &Config,
+    pipe_command: Option<&Vec<String>>,
+    output_format: OutputFormat,
+    provider: &dyn TranscriptionProvider,
+) -> i32 {
+    let mut exit_code = 0;
+
+    // Name each file before its output when several go to stdout; JSON carries the name instead
+    let headers = files.len() > 1 && pipe_command.is_none() && output_format != OutputFormat::Json;
+    for path in files {
+        eprintln!("Transcribing {}", path.display());
+        if headers {
+            println!("==> {} <==", path.display());
+        }
+        let audio = match wav::decode_wav_file(path) {
+            Ok(audio) => audio,
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                exit_code = 1;
+                continue;
+            }
+        };
+
+        let audio_data = convert_file_audio(audio, config);
+        match transcribe_recording(
+            audio_data,
+            config.audio_sample_rate,
+            config,
+            pipe_command,
+            output_format,
+            Some(provider),
+            AudioSource::File(path),
+            std::future::pending::<()>(),
+        )
+        .await
+        {
+            Ok((0, _)) => {}
+            Ok((code, _)) => exit_code = code,
+            Err(e) => {
+                eprintln!("Failed to transcribe {}: {}", path.display(), e);
+                exit_code = 1;
+            }
+        }
+    }
+
+    exit_code
+}
+
+/// Print the effective configuration and validate it, returning the exit code
+fn check_config(
+    loaded: &std::result::Result<Config, config::ConfigError>,
+    source_of: impl Fn(&str) -> config::Source,
+) -> i32 {
+    let config = match loaded {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            return 1;
+        }
+    };
+
+    let width = config::SETTINGS
+        .iter()
+        .map(|key| key.len())
+        .max()
+        .unwrap_or(0);
+    for key in config::SETTINGS {
+        let value = config
+            .display_value(key)
+            .unwrap_or_else(|| "(not set)".to_string());
+        println!(
+            "{:width$}  {}  ({})",
+            key,
+            value,
+            source_of(key),
+            width = width
+        );
+    }
+
+    match config.validate() {
+        Ok(()) => {
+            eprintln!("✅ Configuration is valid");
+            0
+        }
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            1
+        }
+    }
+}
+
+/// Print the recordings waiting in the spool, oldest first
+fn print_spooled_recordings(spool: &spool::Spool, recordings: &[spool::SpooledRecording]) {
+    if recordings.is_empty() {
+        eprintln!("No saved recordings in {}", spool.dir().display());
+        return;
+    }
+
+    let now = spool::unix_time();
+    for recording in recordings {
+        println!(
+            "{}\t{}\t{:.1}s\t{}\t{}",
+            recording.id,
+            spool::describe_age(recording.details.failed_at, now),
+            recording.details.duration,
+            recording.details.provider,
+            recording.details.error
+        );
+    }
+}
+
+/// Transcribe saved recordings again in their original language
+/// Each recording is deleted once its transcription has been output successfully.
+/// Returns a non-zero exit code if any recording failed again.
+async fn retry_recordings(
+    spool: &spool::Spool,
+    recordings: Vec<spool::SpooledRecording>,
+    config: &Config,
+    pipe_command: Option<&Vec<String>>,
+    output_format: OutputFormat,
+    provider: &dyn TranscriptionProvider,
+) -> i32 {
+    let mut exit_code = 0;
+
+    for recording in recordings {
+        eprintln!("Retrying recording {}", recording.id);
+        let audio = match wav::decode_wav_file(&recording.wav_path) {
+            Ok(audio) => audio,
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                exit_code = 1;
+                continue;
+            }
+        };
+
+        let config = Config {
+            whisper_language: recording.details.language.clone(),
+            ..config.clone()
+        };
+        let audio_data = convert_file_audio(audio, &config);
+        match transcribe_recording(
+            audio_data,
+            config.audio_sample_rate,
+            &config,
+            pipe_command,
+            output_format,
+            Some(provider),
+            AudioSource::Spooled,
+            std::future::pending::<()>(),
+        )
+        .await
+        {
+            Ok((0, Some(_))) => {
+                if let Err(e) = spool.remove(&recording) {
+                    eprintln!(
+                        "Warning: Failed to remove recording {}: {}",
+                        recording.id, e
+                    );
+                }
+            }
+            Ok((code, _)) => exit_code = code.max(1),
+            Err(e) => {
+                eprintln!("Failed to transcribe recording {}: {}", recording.id, e);
+                exit_code = 1;
+            }
+        }
+    }
+
+    exit_code
+}
+
+/// Append a successful transcription to the history, if enabled
+/// The recording is saved with it when `HISTORY_SAVE_AUDIO` is set.
+fn record_history(
+    config: &Config,
+    transcript: &Transcript,
+    metadata: &TranscriptionMetadata,
+    wav_data: Option<&[u8]>,
+) {
+    if !config.history_enabled || transcript.text.trim().is_empty() {
+        return;
+    }
+
+    let language = transcript
+        .language
+        .clone()
+        .or_else(|| (config.whisper_language != "auto").then(|| config.whisper_language.clone()));
+    let entry = history::HistoryEntry {
+        timestamp: spool::unix_time(),
+        text: transcript.text.trim().to_string(),
+        provider: metadata.provider.clone(),
+        model: metadata.model.clone(),
+        language,
+        duration: metadata.audio_duration,
+        audio: None,
+    };
+    let wav_data = wav_data.filter(|_| config.history_save_audio);
+    if let Err(e) = history::History::open_default(config).append(entry, wav_data) {
+        eprintln!("Warning: Failed to save transcription to history: {}", e);
+    }
+}
+
+/// Print numbered history entries, one per line
+fn print_history_entries<'a>(
+    entries: impl IntoIterator<Item = (usize, &'a history::HistoryEntry)>,
+) {
+    let now = spool::unix_time();
+    for (number, entry) in entries {
+        println!(
+            "{}\t{}\t{:.1}s\t{}",
+            number,
+            spool::describe_age(entry.timestamp, now),
+            entry.duration,
+            history::preview(&entry.text, 60)
+        );
+    }
+}
+
+/// Print the catalog and installed models, marking the configured one
+fn print_models(config: &Config, installed: &[models::InstalledModel]) {
+    let configured = models::file_name(&config.whisper_model);
+    println!(
+        "Models in {} (set WHISPER_MODEL to a name):",
+        Config::model_dir().display()
+    );
+
+    let is_installed = |file: &str| installed.iter().any(|model| model.file == file);
+    for model in models::CATALOG {
+        let file = model.file_name();
+        let marker = if file == configured { "*" } else { " " };
+        let language = if model.english_only {
+            "English"
+        } else {
+            "multilingual"
+        };
+        let status = if is_installed(&file) { "installed" } else { "" };
+        println!(
+            "{} {:<16} {:>8}  {:<12}  {}",
+            marker,
+            model.name,
+            models::format_size(model.size_mib * 1024 * 1024),
+            language,
+            status
+        );
+    }
+
+    // Models added by hand, such as fine-tunes
+    for model in installed
+        .iter()
+        .filter(|model| models::find(&model.file).is_none())
+    {
+        let marker = if model.file == configured { "*" } else { " " };
+        println!(
+            "{} {:<16} {:>8}  {:<12}  installed",
+            marker,
+            model.file,
+            models::format_size(model.size),
+            ""
+        );
+    }
+}
+
+/// Run a `waystt models` command, returning the exit code
+/// Models default to the configured `WHISPER_MODEL`, except for `remove`.
+async fn run_models(action: &ModelsCommand, config: &Config) -> i32 {
+    if let ModelsCommand::List = action {
+        return match models::installed(&Config::model_dir()) {
+            Ok(installed) => {
+                print_models(config, &installed);
+                0
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                1
+            }
+        };
+    }
+
+    let name = match action {
+        ModelsCommand::List => unreachable!("handled above"),
+        ModelsCommand::Info { name }
+        | ModelsCommand::Verify { name }
+        | ModelsCommand::Download { name } => {
+            name.clone().unwrap_or_else(|| config.whisper_model.clone())
+        }
+        ModelsCommand::Remove { name } => name.clone(),
+    };
+    if let Err(e) = models::check_name(&name) {
+        eprintln!("{}", e);
+        return 1;
+    }
+    let file = models::file_name(&name);
+    let path = Config::model_path(&file);
+    let catalog = models::find(&name);
+    let pinned = catalog.map(|model| model.sha256);
+
+    match action {
+        ModelsCommand::List => unreachable!("handled above"),
+        ModelsCommand::Info { .. } => {
+            println!("File:      {}", file);
+            if let Some(model) = catalog {
+                println!(
+                    "Size:      about {}",
+                    models::format_size(model.size_mib * 1024 * 1024)
+                );
+                println!(
+                    "Languages: {}",
+                    if model.english_only {
+                        "English only"
+                    } else {
+                        "multilingual"
+                    }
+                );
+            } else {
+                println!("Catalog:   not in the catalog");
+            }
+            println!(
+                "URL:       {}",
+                models::model_url(&config.whisper_model_mirror, &file)
+            );
+            println!("Path:      {}", path.display());
+            match std::fs::metadata(&path) {
+                Ok(metadata) => println!("Installed: yes, {}", models::format_size(metadata.len())),
+                Err(_) => println!("Installed: no"),
+            }
+            let checksum = pinned
+                .map(|sha256| format!("{} (pinned)", sha256))
+                .or_else(|| {
+                    models::recorded_checksum(&path)
+                        .map(|sha256| format!("{} (recorded at download)", sha256))
+                })
+                .unwrap_or_else(|| "unknown".to_string());
+            println!("SHA-256:   {}", checksum);
+            0
+        }
+        ModelsCommand::Verify { .. } => {
+            if !path.exists() {
+                eprintln!("Model {} is not downloaded", file);
+                eprintln!("💡 Run `waystt models download {}` to fetch it", name);
+                return 1;
+            }
+            match models::verify(&path, pinned) {
+                Ok(models::Verification::Verified { sha256 }) => {
+                    println!("{}: OK ({})", file, sha256);
+                    0
+                }
+                Ok(models::Verification::Mismatch { expected, actual }) => {
+                    println!("{}: FAILED", file);
+                    eprintln!("Expected SHA-256 {}", expected);
+                    eprintln!("Actual SHA-256   {}", actual);
+                    eprintln!("💡 Run `waystt models download {}` to fetch it again", name);
+                    1
+                }
+                Ok(models::Verification::Unknown { sha256 }) => {
+                    println!("{}: no checksum to compare ({})", file, sha256);
+                    0
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    1
+                }
+            }
+        }
+        ModelsCommand::Download { .. } => match download_model(config, &name).await {
+            Ok(path) => {
+                eprintln!("Model downloaded to {}", path.display());
+                0
+            }
+            Err(e) => {
+                eprintln!("Failed to download model: {}", e);
+                1
+            }
+        },
+        ModelsCommand::Remove { .. } => match models::remove(&path) {
+            Ok(()) => {
+                eprintln!("Removed {}", path.display());
+                0
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                1
+            }
+        },
+    }
+}
+
+/// Run a `waystt history` command, returning the exit code
+async fn run_history(action: &HistoryCommand, config: &Config) -> i32 {
+    let history = history::History::open_default(config);
+    if let HistoryCommand::Clear = action {
+        return match history.clear() {
+            Ok(count) => {
+                eprintln!("Deleted {} transcriptions from history", count);
+                0
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                1
+            }
+        };
+    }
+
+    let entries = match history.entries() {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    if entries.is_empty() {
+        eprintln!("No transcriptions in {}", history.dir().display());
+        if !config.history_enabled {
+            eprintln!("💡 Set HISTORY_ENABLED=true to keep a history of transcriptions");
+        }
+        // An empty list is still a successful listing
+        return match action {
+            HistoryCommand::List { .. } => 0,
+            _ => 1,
+        };
+    }
+
+    match action {
+        HistoryCommand::List { limit } => {
+            print_history_entries(
+                entries
+                    .iter()
+                    .rev()
+                    .take(*limit)
+                    .enumerate()
+                    .map(|(index, entry)| (index + 1, entry)),
+            );
+            0
+        }
+        HistoryCommand::Search { query } => {
+            let found = history::search(&entries, query);
+            if found.is_empty() {
+                eprintln!("No transcriptions contain \"{}\"", query);
+                return 1;
+            }
+            print_history_entries(found);
+            0
+        }
+        HistoryCommand::Show { number } | HistoryCommand::Copy { number, .. } => {
+            let Some(entry) = history::nth_newest(&entries, *number) else {
+                eprintln!(
+                    "No history entry {}; there are {} entries",
+                    number,
+                    entries.len()
+                );
+                return 1;
+            };
+
+            if let HistoryCommand::Copy { pipe_to, .. } = action {
+                let wl_copy = vec!["wl-copy".to_string()];
+                let command = pipe_to.as_ref().unwrap_or(&wl_copy);
+                return match command::execute_with_input(command, &entry.text).await {
+                    Ok(exit_code) => exit_code,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        1
+                    }
+                };
+            }
+
+            eprintln!(
+                "{} ({})",
+                history::format_utc(entry.timestamp),
+                spool::describe_age(entry.timestamp, spool::unix_time())
+            );
+            eprintln!("Provider: {} ({})", entry.provider, entry.model);
+            if let Some(language) = &entry.language {
+                eprintln!("Language: {}", language);
+            }
+            eprintln!("Duration: {:.1}s", entry.duration);
+            if let Some(audio) = &entry.audio {
+                eprintln!("Audio: {}", audio.display());
+            }
+            println!("{}", entry.text);
+            0
+        }
+        HistoryCommand::Clear => unreachable!("handled above"),
+    }
+}
+
+/// Stop recording and transcribe everything captured so far
+/// Without a provider one is created from the configuration.
+/// Returns the transcription exit code and transcript, or an error if no audio could be read
+#[cfg(not(test))]
+async fn stop_and_transcribe(
+    recorder: &mut AudioRecorder,
+    beep_player: &BeepPlayer,
+    config: &Config,
+    pipe_command: Option<&Vec<String>>,
+    output_format: OutputFormat,
+    provider: Option<&dyn TranscriptionProvider>,
+) -> Result<(i32, Option<Transcript>)> {
+    // Stop recording
+    if let Err(e) = recorder.stop_recording() {
+        eprintln!("Failed to stop recording: {}", e);
+    } else {
+        // Play recording stop beep
+        if let Err(e) = beep_player.play_async(BeepType::RecordingStop).await {
+            eprintln!("Warning: Failed to play recording stop beep: {}", e);
+        }
+    }
+
+    // Get recorded audio data and process it
+    let audio_data = recorder.get_audio_data()?;
+    let duration = recorder.get_recording_duration_seconds().unwrap_or(0.0);
+    eprintln!(
+        "Captured {} audio samples ({:.2} seconds)",
+        audio_data.len(),
+        duration
+    );
+
+    // Process audio for transcription, SIGTERM cancels the provider
+    let sample_rate = recorder.sample_rate();
+    let transcription = match provider {
+        // The daemon keeps its provider loaded between recordings
+        Some(provider) => {
+            transcribe_recording(
+                audio_data,
+                sample_rate,
+                config,
+                pipe_command,
+                output_format,
+                Some(provider),
+                AudioSource::Recording,
+                sigterm(),
+            )
+            .await
+        }
+        None => {
+            process_audio_for_transcription(
+                audio_data,
+                sample_rate,
+                config,
+                pipe_command,
+                output_format,
+                sigterm(),
+            )
+            .await
+        }
+    };
+    let result = match transcription {
+        Ok((exit_code, transcript)) => {
+            eprintln!("Audio processing completed with exit code: {}", exit_code);
+            (exit_code, transcript)
+        }
+        Err(e) => {
+            eprintln!("Audio processing failed: {}", e);
+            (1, None)
+        }
+    };
+
+    // Clear buffer to free memory
+    if let Err(e) = recorder.clear_buffer() {
+        eprintln!("Failed to clear audio buffer: {}", e);
+    }
+
+    Ok(result)
+}
+
+/// Stop recording and discard the audio without sending it to any provider
+#[cfg(not(test))]
+async fn cancel_recording(recorder: &mut AudioRecorder, beep_player: &BeepPlayer) {
+    if let Err(e) = recorder.stop_recording() {
+        eprintln!("Failed to stop recording: {}", e);
+    }
+    if let Err(e) = recorder.clear_buffer() {
+        eprintln!("Failed to clear audio buffer: {}", e);
+    }
+    eprintln!("Recording cancelled, audio discarded");
+
+    if let Err(e) = beep_player.play_async(BeepType::Cancel).await {
+        eprintln!("Warning: Failed to play cancel beep: {}", e);
+    }
+}
+
+/// Describe the current recording state for a control client
+#[cfg(not(test))]
+fn control_status(recorder: &AudioRecorder, config: &Config) -> control::Response {
+    control::Response {
+        ok: true,
+        state: Some(
+            if recorder.is_recording() {
+                "recording"
+            } else {
+                "idle"
+            }
+            .to_string(),
+        ),
+        provider: Some(config.transcription_provider.clone()),
+        language: Some(config.whisper_language.clone()),
+        recording_seconds: recorder.get_recording_duration_seconds().ok(),
+        ..control::Response::default()
+    }
+}
+
+/// Listen for `waystt ctl` requests; signals keep working without the socket
+#[cfg(not(test))]
+fn bind_control_socket() -> Option<control::ControlServer> {
+    match control::ControlServer::bind(&control::socket_path()) {
+        Ok(server) => {
+            eprintln!("Control socket listening on {}", server.path().display());
+            Some(server)
+        }
+        Err(e) => {
+            eprintln!("Warning: Control socket unavailable: {}", e);
+            None
+        }
+    }
+}
+
+/// Wait for the next control request, or forever if the socket is unavailable
+#[cfg(not(test))]
+async fn next_control_request(
+    control: &mut Option<control::ControlServer>,
+) -> Option<control::PendingRequest> {
+    match control {
+        Some(server) => server.next().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Send a `waystt ctl` request and print the result
+/// Transcripts go to stdout so they can be piped; everything else to stderr.
+async fn run_ctl(request: &control::Request) -> i32 {
+    let response = match control::send(&control::socket_path(), request).await {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    if let Some(transcript) = &response.transcript {
+        println!("{}", transcript.text);
+    } else if let Some(state) = &response.state {
+        eprintln!("state: {}", state);
+        if let Some(provider) = &response.provider {
+            eprintln!("provider: {}", provider);
+        }
+        if let Some(language) = &response.language {
+            eprintln!("language: {}", language);
+        }
+        if let Some(seconds) = response.recording_seconds {
+            eprintln!("recorded: {:.1}s", seconds);
+        }
+    }
+
+    match response.error {
+        Some(error) => {
+            eprintln!("Error: {}", error);
+            1
+        }
+        None if response.ok => 0,
+        None => 1,
+    }
+}
+
+/// Play the start beep while the microphone is already capturing
+/// Instead of waiting for the beep before recording, only the audio captured
+/// while it actually played is muted, and at most `AUDIO_PRE_ROLL_MS` of audio
+/// from before the trigger is kept.
+async fn play_start_beep(recorder: &mut AudioRecorder, beep_player: &BeepPlayer) {
+    let trigger = recorder.position();
+    let triggered_at = Instant::now();
+    recorder.trim_pre_roll(trigger);
+
+    match beep_player.play_timed(BeepType::RecordingStart).await {
+        Ok(Some(played)) => recorder.mute_played(
+            trigger,
+            triggered_at,
+            played,
+            Duration::from_millis(BEEP_TAIL_MS),
+        ),
+        Ok(None) => {}
+        Err(e) => eprintln!("Warning: Failed to play recording start beep: {}", e),
+    }
+}
+
+/// Start a recording and play the start beep
+/// With the microphone already open in standby, this only marks where the
+/// recording begins; audio older than the pre-roll is discarded.
+#[cfg(not(test))]
+async fn begin_recording(recorder: &mut AudioRecorder, beep_player: &BeepPlayer) -> Result<()> {
+    recorder.start_recording()?;
+    play_start_beep(recorder, beep_player).await;
+    eprintln!("Recording started");
+    Ok(())
+}
+
+/// Daemon mode: keep the provider loaded and toggle recording on each trigger
+/// SIGUSR1 and `waystt ctl toggle` start a recording when idle and transcribe it
+/// otherwise; SIGUSR2 discards it. Runs until SIGTERM and returns the exit code,
+/// which is 143 if SIGTERM interrupted a transcription.
+#[cfg(not(test))]
+async fn run_daemon(
+    recorder: &mut AudioRecorder,
+    beep_player: &BeepPlayer,
+    mut config: Config,
+    mut provider: Box<dyn TranscriptionProvider>,
+    auto_stop: Option<VadConfig>,
+    pipe_command: Option<&Vec<String>>,
+    output_format: OutputFormat,
+) -> Result<i32> {
+    let mut signals = Signals::new([SIGUSR1, SIGUSR2, SIGTERM])?;
+    let mut control = bind_control_socket();
+    let mut exit_code = 0;
+
+    // Voice activity detection for --auto-stop, restarted with each recording
+    let mut vad: Option<VoiceActivityDetector> = None;
+    let mut vad_position = 0u64;
+    let mut vad_mono = Vec::new();
+
+    eprintln!("Idle. Send SIGUSR1 to start recording, again to transcribe; SIGUSR2 cancels.");
+
+    loop {
+        let mut speech_ended = false;
+        if let Some(vad) = vad.as_mut().filter(|_| recorder.is_recording()) {
+            let (samples, position) = recorder.read_since(vad_position);
+            vad_position = position;
+
+            vad_mono.clear();
+            resample::convert_channels(&samples, config.audio_channels, 1, &mut vad_mono);
+
+            for event in vad.process(&vad_mono) {
+                match event {
+                    VadEvent::SpeechStart(_) => {
+                        eprintln!("Speech detected (noise floor {:.4} RMS)", vad.noise_floor())
+                    }
+                    VadEvent::SpeechEnd(_) => speech_ended = true,
+                }
+            }
+        }
+
+        // Signals and silence act like the equivalent control request without a client
+        let (request, pending) = if speech_ended {
+            eprintln!("Silence detected after speech: Stop recording, transcribe, and output");
+            (control::Request::Stop, None)
+        } else {
+            let event = tokio::select! {
+                signal = signals.next() => match signal {
+                    Some(signal) => Err(signal),
+                    // Signal stream ended
+                    None => break,
+                },
+                Some(pending) = next_control_request(&mut control) => Ok(pending),
+                () = tokio::time::sleep(Duration::from_millis(50)) => continue,
+            };
+
+            match event {
+                Ok(pending) => (pending.request.clone(), Some(pending)),
+                Err(SIGUSR1) => {
+                    eprintln!("Received SIGUSR1: Toggle recording");
+                    (control::Request::Toggle, None)
+                }
+                Err(SIGUSR2) => {
+                    eprintln!("Received SIGUSR2: Cancel recording without transcribing");
+                    (control::Request::Cancel, None)
+                }
+                Err(SIGTERM) => {
+                    eprintln!("Received SIGTERM: Shutting down gracefully");
+                    break;
+                }
+                Err(signal) => {
+                    eprintln!("Received unexpected signal: {}", signal);
+                    continue;
+                }
+            }
+        };
+
+        let recording = recorder.is_recording();
+        let response = match request {
+            control::Request::Start | control::Request::Toggle if !recording => {
+                match begin_recording(recorder, beep_player).await {
+                    Ok(()) => {
+                        vad = auto_stop.clone().map(|vad_config| {
+                            VoiceActivityDetector::new(recorder.sample_rate(), vad_config)
+                        });
+                        vad_position = recorder.position();
+                        control_status(recorder, &config)
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to start audio recording: {}", e);
+                        if let Err(beep_err) = beep_player.play_async(BeepType::Error).await {
+                            eprintln!("Warning: Failed to play error beep: {}", beep_err);
+                        }
+                        control::Response::error(e.to_string())
+                    }
+                }
+            }
+            control::Request::Start => control::Response::error("already recording"),
+            control::Request::Stop | control::Request::Cancel if !recording => {
+                control::Response::error("not recording")
+            }
+            control::Request::Stop | control::Request::Toggle => {
+                vad = None;
+                let result = stop_and_transcribe(
+                    recorder,
+                    beep_player,
+                    &config,
+                    pipe_command,
+                    output_format,
+                    Some(provider.as_ref()),
+                )
+                .await;
+                if let Ok((TERMINATED_EXIT_CODE, _)) = result {
+                    exit_code = TERMINATED_EXIT_CODE;
+                } else {
+                    eprintln!("Idle. Send SIGUSR1 to start the next recording.");
+                }
+                match result {
+                    Ok((exit_code, transcript)) => control::Response {
+                        ok: exit_code == 0,
+                        transcript,
+                        ..control_status(recorder, &config)
+                    },
+                    Err(e) => {
+                        eprintln!("Failed to get audio data: {}", e);
+                        control::Response::error(e.to_string())
+                    }
+                }
+            }
+            control::Request::Cancel => {
+                vad = None;
+                cancel_recording(recorder, beep_player).await;
+                control_status(recorder, &config)
+            }
+            control::Request::Status => control_status(recorder, &config),
+            control::Request::SetLanguage { language } => {
+                eprintln!("Control: Language set to {}", language);
+                config.whisper_language = language;
+                control_status(recorder, &config)
+            }
+            control::Request::SwitchProvider { provider: name } => {
+                let switched = Config {
+                    transcription_provider: name,
+                    ..config.clone()
+                };
+                // Load the new provider before dropping the old one, so a failure changes nothing
+                let created = match switched.validate() {
+                    Ok(()) => TranscriptionFactory::create_provider(
+                        &switched.transcription_provider,
+                        &switched,
+                    )
+                    .await
+                    .map_err(|e| anyhow!("Failed to initialize provider: {}", e)),
+                    Err(e) => Err(e.into()),
+                };
+                match created {
+                    Ok(created) => {
+                        eprintln!(
+                            "Control: Provider switched to {}",
+                            switched.transcription_provider
+                        );
+                        provider = created;
+                        config = switched;
+                        control_status(recorder, &config)
+                    }
+                    Err(e) => control::Response::error(e.to_string()),
+                }
+            }
+        };
+
+        match pending {
+            Some(pending) => pending.respond(response),
+            None => {
+                if let Some(error) = response.error {
+                    eprintln!("Ignored: {}", error);
+                }
+            }
+        }
+
+        // SIGTERM interrupted a transcription
+        if exit_code != 0 {
+            break;
+        }
+    }
+
+    if let Err(e) = recorder.stop_recording() {
+        eprintln!("Failed to stop recording: {}", e);
+    }
+    if let Err(e) = recorder.clear_buffer() {
+        eprintln!("Failed to clear audio buffer during shutdown: {}", e);
+    }
+    Ok(exit_code)
+}
+
+/// Continuous dictation: segment the recording at silence and transcribe
+/// each utterance as it ends, until SIGTERM
+/// SIGUSR1 transcribes the utterance in progress without waiting for silence;
+/// SIGUSR2 discards it and ends the session. Returns the exit code.
+#[cfg(not(test))]
+async fn run_continuous(
+    recorder: &mut AudioRecorder,
+    signals: &mut Signals,
+    beep_player: &BeepPlayer,
+    config: &Config,
+    vad_config: VadConfig,
+    pipe_command: Option<&Vec<String>>,
+    output_format: OutputFormat,
+) -> Result<i32> {
+    // Load the provider once for the whole session
+    let provider =
+        TranscriptionFactory::create_provider(&config.transcription_provider, config).await?;
+
+    let channels = u64::from(config.audio_channels);
+    let padding = u64::from(recorder.sample_rate()) * UTTERANCE_PADDING_MS / 1000;
+    let pre_roll = recorder.samples_for(Duration::from_millis(config.audio_pre_roll_ms)) / channels;
+    let mut vad = VoiceActivityDetector::new(recorder.sample_rate(), vad_config);
+    let mut position = 0u64;
+    let mut mono = Vec::new();
+
+    // Utterance boundaries are mono sample positions as seen by the VAD
+    let mut utterance_start: Option<u64> = None;
+    let mut emitted_until = 0u64;
+
+    loop {
+        // Process audio events to capture microphone data
+        if let Err(e) = recorder.process_audio_events() {
+            eprintln!("Error processing audio events: {}", e);
+        }
+
+        let (samples, next) = recorder.read_since(position);
+        position = next;
+        mono.clear();
+        resample::convert_channels(&samples, config.audio_channels, 1, &mut mono);
+
+        for event in vad.process(&mono) {
+            match event {
+                VadEvent::SpeechStart(start) => {
+                    eprintln!("Speech detected");
+                    utterance_start = Some(start);
+                }
+                VadEvent::SpeechEnd(end) => {
+                    if let Some(start) = utterance_start.take() {
+                        let start = start.saturating_sub(pre_roll).max(emitted_until);
+                        let end = end + padding;
+                        if end > start {
+                            let completed = transcribe_utterance(
+                                recorder,
+                                provider.as_ref(),
+                                start * channels..end * channels,
+                                config,
+                                pipe_command,
+                                output_format,
+                            )
+                            .await;
+                            if !completed {
+                                shut_down_recording(recorder);
+                                return Ok(TERMINATED_EXIT_CODE);
+                            }
+                            emitted_until = end;
+                        }
+                    }
+                }
+            }
+        }
+
+        let signal = match tokio::time::timeout(Duration::from_millis(50), signals.next()).await {
+            Ok(Some(signal)) => signal,
+            Ok(None) => break,
+            Err(_) => continue,
+        };
+
+        if signal == SIGUSR2 {
+            eprintln!("Received SIGUSR2: Cancel the utterance in progress and exit");
+            cancel_recording(recorder, beep_player).await;
+            return Ok(CANCELLED_EXIT_CODE);
+        }
+
+        if signal != SIGUSR1 && signal != SIGTERM {
+            eprintln!("Received unexpected signal: {}", signal);
+            continue;
+        }
+
+        // Flush the utterance in progress
+        if let Some(start) = utterance_start {
+            let start = start.saturating_sub(pre_roll).max(emitted_until);
+            let end = position / channels;
+            if end > start {
+                eprintln!("Transcribing utterance in progress");
+                let completed = transcribe_utterance(
+                    recorder,
+                    provider.as_ref(),
+                    start * channels..end * channels,
+                    config,
+                    pipe_command,
+                    output_format,
+                )
+                .await;
+                if !completed {
+                    shut_down_recording(recorder);
+                    return Ok(TERMINATED_EXIT_CODE);
+                }
+                emitted_until = end;
+            }
+        }
+
+        if signal == SIGTERM {
+            eprintln!("Received SIGTERM: Shutting down gracefully");
+            shut_down_recording(recorder);
+            break;
+        }
+    }
+
+    Ok(0)
+}
+
+/// Stop recording and free the buffer when SIGTERM ends a session
+#[cfg(not(test))]
+fn shut_down_recording(recorder: &mut AudioRecorder) {
+    if let Err(e) = recorder.stop_recording() {
+        eprintln!("Failed to stop recording: {}", e);
+    }
+    if let Err(e) = recorder.clear_buffer() {
+        eprintln!("Failed to clear audio buffer during shutdown: {}", e);
+    }
+}
+
+/// How the audio feed of a streaming session ended
+enum StreamEnd {
+    /// SIGUSR1: transcribe and output the final text
+    Finish,
+    /// SIGTERM or the signal stream closed
+    Abort,
+    /// SIGUSR2: discard the session
+    Cancel,
+}
+
+/// Result of a streaming session
+enum StreamOutcome {
+    /// The provider finished the stream, after the feed or on its own, e.g. on an error
+    Transcribed(Result<String, TranscriptionError>),
+    Aborted,
+    Cancelled,
+    /// `terminate` completed while the provider finished the stream
+    Terminated,
+}
+
+/// Stream the audio sent by `feed` to the provider, showing partial hypotheses
+/// The feed owns the sender, so the audio stream ends as soon as the feed does.
+/// Final hypotheses are joined into the text of the outcome. `terminate`
+/// interrupts the final transcription once the feed has finished.
+async fn stream_transcription<F, Fut, T>(
+    provider: &dyn TranscriptionProvider,
+    sample_rate: u32,
+    language: Option<String>,
+    feed: F,
+    terminate: T,
+) -> StreamOutcome
+where
+    F: FnOnce(futures::channel::mpsc::UnboundedSender<Vec<i16>>) -> Fut,
+    Fut: std::future::Future<Output = StreamEnd>,
+    T: std::future::Future,
+{
+    let (sender, receiver) = futures::channel::mpsc::unbounded();
+
+    // Show partial hypotheses and collect the final ones
+    let transcribe = async {
+        let mut hypotheses = provider
+            .transcribe_stream(Box::pin(receiver), sample_rate, language)
+            .await?;
+        let mut finals = Vec::new();
+        while let Some(hypothesis) = hypotheses.next().await {
+            let hypothesis = hypothesis?;
+            let text = hypothesis.text.trim();
+            if hypothesis.is_final {
+                eprintln!("\r\x1b[2K📝 {}", text);
+                if !text.is_empty() {
+                    finals.push(text.to_string());
+                }
+            } else {
+                eprint!("\r\x1b[2K💬 {}", text);
+            }
+        }
+        Ok::<String, TranscriptionError>(finals.join(" "))
+    };
+
+    let feed = feed(sender);
+    futures::pin_mut!(feed);
+    futures::pin_mut!(transcribe);
+    tokio::select! {
+        end = &mut feed => match end {
+            StreamEnd::Finish => until_terminated(transcribe, terminate)
+                .await
+                .map_or(StreamOutcome::Terminated, StreamOutcome::Transcribed),
+            StreamEnd::Abort => StreamOutcome::Aborted,
+            // Dropping the provider stream discards everything sent so far
+            StreamEnd::Cancel => StreamOutcome::Cancelled,
+        },
+        result = &mut transcribe => StreamOutcome::Transcribed(result),
+    }
+}
+
+/// Streaming transcription: send audio to the provider while recording and
+/// show partial hypotheses as they arrive
+/// SIGUSR1 finishes the stream and outputs the final text; SIGTERM aborts and
+/// SIGUSR2 cancels without output. Returns the exit code, or `None` if aborted.
+#[cfg(not(test))]
+async fn run_streaming(
+    recorder: &mut AudioRecorder,
+    signals: &mut Signals,
+    beep_player: &BeepPlayer,
+    config: &Config,
+    pipe_command: Option<&Vec<String>>,
+    output_format: OutputFormat,
+) -> Result<Option<i32>> {
+    let provider =
+        TranscriptionFactory::create_provider(&config.transcription_provider, config).await?;
+    let language = if config.whisper_language == "auto" {
+        None
+    } else {
+        Some(config.whisper_language.clone())
+    };
+    let sample_rate = recorder.sample_rate();
+    let mut streamed_samples = 0usize;
+    let mut finished_at = Instant::now();
+
+    // Feed captured audio to the provider until SIGUSR1, SIGUSR2 or SIGTERM
+    let feeding = &mut *recorder;
+    let streamed = &mut streamed_samples;
+    let finished = &mut finished_at;
+    let outcome = stream_transcription(
+        provider.as_ref(),
+        sample_rate,
+        language,
+        |sender| async move {
+            let recorder = feeding;
+            let encoder = WavEncoder::default();
+            let mut position = 0u64;
+            let mut mono = Vec::new();
+            let mut finishing = false;
+
+            loop {
+                // Process audio events to capture microphone data
+                if let Err(e) = recorder.process_audio_events() {
+                    eprintln!("Error processing audio events: {}", e);
+                }
+
+                let (samples, next) = recorder.read_since(position);
+                position = next;
+                mono.clear();
+                resample::convert_channels(&samples, config.audio_channels, 1, &mut mono);
+                if !mono.is_empty() {
+                    *streamed += mono.len();
+                    let _ = sender.unbounded_send(encoder.convert_samples(&mono));
+                }
+
+                if finishing {
+                    // Returning drops the sender, which ends the audio stream
+                    *finished = Instant::now();
+                    return StreamEnd::Finish;
+                }
+
+                match tokio::time::timeout(Duration::from_millis(50), signals.next()).await {
+                    Ok(Some(SIGUSR1)) => {
+                        eprintln!("Received SIGUSR1: Finish streaming transcription and output");
+                        if let Err(e) = recorder.stop_recording() {
+                            eprintln!("Failed to stop recording: {}", e);
+                        } else if let Err(e) = beep_player.play_async(BeepType::RecordingStop).await
+                        {
+                            eprintln!("Warning: Failed to play recording stop beep: {}", e);
+                        }
+                        finishing = true;
+                    }
+                    Ok(Some(SIGUSR2)) => {
+                        eprintln!("Received SIGUSR2: Cancel streaming transcription");
+                        return StreamEnd::Cancel;
+                    }
+                    Ok(Some(SIGTERM)) => {
+                        eprintln!("Received SIGTERM: Shutting down gracefully");
+                        return StreamEnd::Abort;
+                    }
+                    Ok(Some(signal)) => eprintln!("Received unexpected signal: {}", signal),
+                    Ok(None) => return StreamEnd::Abort,
+                    Err(_) => {}
                 }
             }
+        },
+        sigterm(),
+    )
+    .await;
+
+    let result = match outcome {
+        StreamOutcome::Transcribed(result) => result,
+        StreamOutcome::Aborted => {
+            recorder.stop_recording()?;
+            recorder.clear_buffer()?;
+            return Ok(None);
         }
-        Err(e) => {
-            eprintln!("Audio processing failed: {}", e);
+        StreamOutcome::Cancelled => {
+            cancel_recording(recorder, beep_player).await;
+            return Ok(Some(CANCELLED_EXIT_CODE));
+        }
+        StreamOutcome::Terminated => {
+            eprintln!("\nReceived SIGTERM: Transcription cancelled");
+            recorder.stop_recording()?;
+            spool_interrupted(&recorder.get_audio_data()?, sample_rate, config);
+            recorder.clear_buffer()?;
+            return Ok(Some(TERMINATED_EXIT_CODE));
+        }
+    };
 
-            // Play error beep for audio processing failure
+    recorder.stop_recording()?;
+    recorder.clear_buffer()?;
+
+    match result {
+        Ok(text) => {
+            // Latency is how long the final result took after recording stopped
+            let metadata = TranscriptionMetadata {
+                provider: config.transcription_provider.clone(),
+                model: config.transcription_model().to_string(),
+                audio_duration: streamed_samples as f64 / f64::from(sample_rate),
+                processing_latency: finished_at.elapsed().as_secs_f64(),
+                file: None,
+            };
+            let transcript = Transcript {
+                text,
+                ..Transcript::default()
+            };
+            let exit_code = output_transcription(
+                &transcript,
+                &metadata,
+                output_format,
+                pipe_command,
+                beep_player,
+            )
+            .await;
+            record_history(config, &transcript, &metadata, None);
+            Ok(Some(exit_code))
+        }
+        Err(e) => {
+            eprintln!("❌ Transcription failed: {}", e);
             if let Err(beep_err) = beep_player.play_async(BeepType::Error).await {
                 eprintln!("Warning: Failed to play error beep: {}", beep_err);
             }
-
-            if e.to_string().contains("too short") {
-                eprintln!("Tip: Try speaking for at least 0.1 seconds before sending signal");
-            } else if e.to_string().contains("only silence") {
-                eprintln!("Tip: Make sure your microphone is working and you're speaking clearly");
-            }
-
-            // Don't execute pipe command when audio processing fails
-            Ok(1) // Return exit code 1 for audio processing failure
+            Ok(Some(1))
         }
     }
 }
 
+/// Transcribe one utterance of a continuous session and emit the result
+/// Returns false if SIGTERM interrupted the transcription, after saving the
+/// utterance for `waystt retry`.
+#[cfg(not(test))]
+async fn transcribe_utterance(
+    recorder: &AudioRecorder,
+    provider: &dyn TranscriptionProvider,
+    range: std::ops::Range<u64>,
+    config: &Config,
+    pipe_command: Option<&Vec<String>>,
+    output_format: OutputFormat,
+) -> bool {
+    let audio_data = recorder.read_range(range.start, range.end);
+    let transcription = transcribe_recording(
+        audio_data,
+        recorder.sample_rate(),
+        config,
+        pipe_command,
+        output_format,
+        Some(provider),
+        AudioSource::Recording,
+        sigterm(),
+    )
+    .await;
+    match transcription {
+        Ok((0, _)) => {}
+        Ok((TERMINATED_EXIT_CODE, _)) => return false,
+        Ok((exit_code, _)) => eprintln!(
+            "Utterance processing finished with exit code: {}",
+            exit_code
+        ),
+        Err(e) => eprintln!("Audio processing failed: {}", e),
+    }
+    true
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
+    if let Some(Command::Ctl { request }) = &args.command {
+        std::process::exit(run_ctl(request).await);
+    }
+
     // Determine the config file path
     let envfile = args.envfile.unwrap_or_else(get_default_config_path);
 
+    // The selected profile, if any, overrides the environment file but not the environment
+    let profile = args.profile.as_deref().map(|name| {
+        let path = profile::default_path();
+        match profile::load(&path, name) {
+            Ok(profile) => {
+                eprintln!("Using profile '{}' from: {}", name, path.display());
+                profile
+            }
+            Err(e) => {
+                eprintln!("Configuration error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    });
+    // Variables set before the environment file is loaded, which the file cannot override
+    let preset = config::variables_set();
+
     // Load configuration from environment file or system environment
-    let config = if envfile.exists() {
+    let envfile_loaded = if envfile.exists() {
         eprintln!("Loading environment from: {}", envfile.display());
         match Config::load_env_file(&envfile) {
-            Ok(config) => config,
+            Ok(()) => true,
             Err(e) => {
                 eprintln!(
                     "Warning: Failed to load environment file {}: {}",
@@ -413,7 +2101,7 @@ async fn main() -> Result<()> {
                     e
                 );
                 eprintln!("Falling back to system environment");
-                Config::from_env()
+                false
             }
         }
     } else {
@@ -421,11 +2109,36 @@ async fn main() -> Result<()> {
             "Environment file {} not found, using system environment",
             envfile.display()
         );
-        Config::from_env()
+        false
+    };
+    let loaded = Config::load(profile.as_ref(), &preset);
+
+    if let Some(Command::Config {
+        action: ConfigCommand::Check,
+    }) = &args.command
+    {
+        let sources = |key: &str| {
+            config::source_of(
+                key,
+                &preset,
+                envfile_loaded.then_some(envfile.as_path()),
+                profile.as_ref(),
+            )
+        };
+        std::process::exit(check_config(&loaded, sources));
+    }
+
+    let config = match loaded {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Configuration error: {}", e);
+            eprintln!("💡 Run `waystt config check` to see the effective configuration");
+            std::process::exit(1);
+        }
     };
 
     if args.download_model {
-        match download_model(&config.whisper_model).await {
+        match download_model(&config, &config.whisper_model).await {
             Ok(path) => {
                 eprintln!("Model downloaded to {}", path.display());
                 return Ok(());
@@ -437,15 +2150,125 @@ async fn main() -> Result<()> {
         }
     }
 
-    // Validate configuration (but don't fail if API key missing, as we're just recording for now)
-    if let Err(e) = config.validate() {
-        eprintln!("Configuration warning: {}", e);
-        if config.transcription_provider == "local" {
+    if args.list_devices {
+        match audio::list_input_devices() {
+            Ok(devices) => {
+                print_input_devices(&devices);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Failed to list audio input devices: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(Command::History { action }) = &args.command {
+        std::process::exit(run_history(action, &config).await);
+    }
+
+    if let Some(Command::Models { action }) = &args.command {
+        std::process::exit(run_models(action, &config).await);
+    }
+
+    if let Some(Command::Transcribe { files, pipe_to }) = &args.command {
+        if let Err(e) = config.validate() {
+            eprintln!("Configuration error: {}", e);
             std::process::exit(1);
         }
-        eprintln!(
-            "Note: This is expected during development phase before transcription is implemented"
-        );
+
+        // Beeps are feedback for interactive recording only
+        let config = Config {
+            enable_audio_feedback: false,
+            ..config
+        };
+        let provider =
+            match TranscriptionFactory::create_provider(&config.transcription_provider, &config)
+                .await
+            {
+                Ok(provider) => provider,
+                Err(e) => {
+                    eprintln!("Failed to initialize transcription provider: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+        let exit_code = transcribe_files(
+            files,
+            &config,
+            pipe_to.as_ref(),
+            args.output_format,
+            provider.as_ref(),
+        )
+        .await;
+        std::process::exit(exit_code);
+    }
+
+    if let Some(Command::Retry { id, all, pipe_to }) = &args.command {
+        let spool = spool::Spool::open_default();
+        let recordings = match (id, all) {
+            (Some(id), _) => spool.get(id).map(|recording| vec![recording]),
+            (None, true) => spool.list(),
+            (None, false) => {
+                match spool.list() {
+                    Ok(recordings) => print_spooled_recordings(&spool, &recordings),
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        std::process::exit(1);
+                    }
+                }
+                return Ok(());
+            }
+        };
+        let recordings = match recordings {
+            Ok(recordings) if recordings.is_empty() => {
+                eprintln!("No saved recordings in {}", spool.dir().display());
+                return Ok(());
+            }
+            Ok(recordings) => recordings,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        };
+
+        if let Err(e) = config.validate() {
+            eprintln!("Configuration error: {}", e);
+            std::process::exit(1);
+        }
+
+        // Beeps are feedback for interactive recording only
+        let config = Config {
+            enable_audio_feedback: false,
+            ..config
+        };
+        let provider =
+            match TranscriptionFactory::create_provider(&config.transcription_provider, &config)
+                .await
+            {
+                Ok(provider) => provider,
+                Err(e) => {
+                    eprintln!("Failed to initialize transcription provider: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+        let exit_code = retry_recordings(
+            &spool,
+            recordings,
+            &config,
+            pipe_to.as_ref(),
+            args.output_format,
+            provider.as_ref(),
+        )
+        .await;
+        std::process::exit(exit_code);
+    }
+
+    // Fail before recording rather than after, when the audio would be lost
+    if let Err(e) = config.validate() {
+        eprintln!("Configuration error: {}", e);
+        std::process::exit(1);
     }
 
     eprintln!("waystt - Wayland Speech-to-Text Tool");
@@ -458,143 +2281,309 @@ async fn main() -> Result<()> {
     };
     let beep_player = BeepPlayer::new(beep_config)?;
 
-    // Initialize audio recorder
-    let mut recorder = AudioRecorder::new()?;
-
-    // Play recording start beep BEFORE starting recording to avoid capturing it
-    if let Err(e) = beep_player.play_async(BeepType::RecordingStart).await {
-        eprintln!("Warning: Failed to play recording start beep: {}", e);
+    // Initialize audio recorder with the configured target format
+    let mut recorder = AudioRecorder::new(RecorderConfig {
+        sample_rate: config.audio_sample_rate,
+        channels: config.audio_channels,
+        device: config.audio_input_device.clone(),
+        buffer_duration_seconds: config.audio_buffer_duration_seconds,
+        pre_roll: Duration::from_millis(config.audio_pre_roll_ms),
+    })?;
+
+    // With AUDIO_STANDBY the daemon keeps the microphone open between recordings,
+    // so starting one is instant and keeps the audio from before the trigger
+    if args.daemon && config.audio_standby {
+        if let Err(e) = recorder.start_standby() {
+            eprintln!("Warning: Failed to open the microphone in advance: {}", e);
+            eprintln!("It will be opened for each recording instead.");
+        }
     }
 
-    // Give a moment for the beep to finish before starting recording (beep is now 500ms)
-    tokio::time::sleep(tokio::time::Duration::from_millis(600)).await;
+    // The daemon keeps the provider loaded and idles until the first trigger
+    #[cfg(not(test))]
+    if args.daemon {
+        let provider =
+            match TranscriptionFactory::create_provider(&config.transcription_provider, &config)
+                .await
+            {
+                Ok(provider) => provider,
+                Err(e) => {
+                    eprintln!("Failed to initialize transcription provider: {}", e);
+                    std::process::exit(1);
+                }
+            };
+        let vad_config = args.auto_stop.map(|hangover| VadConfig {
+            hangover,
+            ..VadConfig::default()
+        });
+
+        let exit_code = run_daemon(
+            &mut recorder,
+            &beep_player,
+            config,
+            provider,
+            vad_config,
+            args.pipe_to.as_ref(),
+            args.output_format,
+        )
+        .await?;
+        eprintln!("Exiting waystt");
+        if exit_code != 0 {
+            std::process::exit(exit_code);
+        }
+        return Ok(());
+    }
 
-    // Start recording immediately
+    // Start recording immediately, so nothing said during the start beep's window is lost
     if let Err(e) = recorder.start_recording() {
         eprintln!("Failed to start audio recording: {}", e);
         eprintln!("This may be due to PipeWire not being available or insufficient permissions.");
         return Err(e);
     }
+    play_start_beep(&mut recorder, &beep_player).await;
 
     eprintln!("Audio recording started successfully!");
 
-    // Give PipeWire a moment to start capturing
-    tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-
-    eprintln!("Ready. Send SIGUSR1 to transcribe and output to stdout.");
+    eprintln!("Ready. Send SIGUSR1 to transcribe and output to stdout, or SIGUSR2 to cancel.");
+    if args.stream {
+        eprintln!("Streaming mode: partial results appear on stderr while you speak.");
+    } else if args.continuous {
+        eprintln!(
+            "Continuous mode: each utterance is transcribed when you pause. Send SIGTERM to stop."
+        );
+    } else if let Some(auto_stop) = args.auto_stop {
+        eprintln!(
+            "Auto-stop enabled: transcribing after {}ms of silence following speech.",
+            auto_stop.as_millis()
+        );
+    }
 
     // Main event loop - process audio and wait for signals
     #[cfg(not(test))]
     {
-        let mut signals = Signals::new([SIGUSR1, SIGTERM])?;
+        let mut signals = Signals::new([SIGUSR1, SIGUSR2, SIGTERM])?;
+        // Control requests can change the language and provider
+        let mut config = config;
 
-        loop {
-            // Process audio events to capture microphone data
-            if let Err(e) = recorder.process_audio_events() {
-                eprintln!("Error processing audio events: {}", e);
-            }
+        let vad_config = VadConfig {
+            hangover: args.auto_stop.unwrap_or(VadConfig::default().hangover),
+            ..VadConfig::default()
+        };
 
-            // Check for signals with timeout
-            match tokio::time::timeout(tokio::time::Duration::from_millis(50), signals.next()).await
+        if args.stream {
+            match run_streaming(
+                &mut recorder,
+                &mut signals,
+                &beep_player,
+                &config,
+                args.pipe_to.as_ref(),
+                args.output_format,
+            )
+            .await?
             {
-                Ok(Some(signal)) => {
-                    match signal {
-                        SIGUSR1 => {
-                            eprintln!("Received SIGUSR1: Stop recording, transcribe, and output");
-
-                            // Stop recording
-                            if let Err(e) = recorder.stop_recording() {
-                                eprintln!("Failed to stop recording: {}", e);
-                            } else {
-                                // Play recording stop beep
-                                if let Err(e) =
-                                    beep_player.play_async(BeepType::RecordingStop).await
-                                {
-                                    eprintln!("Warning: Failed to play recording stop beep: {}", e);
-                                }
-                            }
+                Some(exit_code) => std::process::exit(exit_code),
+                None => {
+                    eprintln!("Exiting waystt");
+                    return Ok(());
+                }
+            }
+        }
 
-                            // Get recorded audio data and process it
-                            match recorder.get_audio_data() {
-                                Ok(audio_data) => {
-                                    let duration =
-                                        recorder.get_recording_duration_seconds().unwrap_or(0.0);
-                                    eprintln!(
-                                        "Captured {} audio samples ({:.2} seconds)",
-                                        audio_data.len(),
-                                        duration
-                                    );
+        if args.continuous {
+            let exit_code = run_continuous(
+                &mut recorder,
+                &mut signals,
+                &beep_player,
+                &config,
+                vad_config,
+                args.pipe_to.as_ref(),
+                args.output_format,
+            )
+            .await?;
+            eprintln!("Exiting waystt");
+            if exit_code != 0 {
+                std::process::exit(exit_code);
+            }
+            return Ok(());
+        }
 
-                                    // Process audio for transcription
-                                    match process_audio_for_transcription(
-                                        audio_data,
-                                        16000, // Using fixed sample rate from audio module
-                                        &config,
-                                        args.pipe_to.as_ref(),
-                                    )
-                                    .await
-                                    {
-                                        Ok(exit_code) => {
-                                            eprintln!(
-                                                "Audio processing completed with exit code: {}",
-                                                exit_code
-                                            );
+        // Voice activity detection for --auto-stop, fed incrementally from the recorder
+        let mut vad = args
+            .auto_stop
+            .map(|_| VoiceActivityDetector::new(recorder.sample_rate(), vad_config));
+        let mut vad_position = 0u64;
+        let mut vad_mono = Vec::new();
 
-                                            // Clear buffer to free memory
-                                            if let Err(e) = recorder.clear_buffer() {
-                                                eprintln!("Failed to clear audio buffer: {}", e);
-                                            }
+        let mut control = bind_control_socket();
 
-                                            // Exit with the appropriate code
-                                            std::process::exit(exit_code);
-                                        }
-                                        Err(e) => {
-                                            eprintln!("Audio processing failed: {}", e);
+        let exit_code = loop {
+            // Process audio events to capture microphone data
+            if let Err(e) = recorder.process_audio_events() {
+                eprintln!("Error processing audio events: {}", e);
+            }
 
-                                            // Clear buffer to free memory
-                                            if let Err(e) = recorder.clear_buffer() {
-                                                eprintln!("Failed to clear audio buffer: {}", e);
-                                            }
+            if let Some(vad) = vad.as_mut() {
+                let (samples, position) = recorder.read_since(vad_position);
+                vad_position = position;
 
-                                            // Exit with error code
-                                            std::process::exit(1);
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    eprintln!("Failed to get audio data: {}", e);
-                                }
-                            }
+                vad_mono.clear();
+                resample::convert_channels(&samples, config.audio_channels, 1, &mut vad_mono);
 
-                            break;
+                let mut speech_ended = false;
+                for event in vad.process(&vad_mono) {
+                    match event {
+                        VadEvent::SpeechStart(_) => {
+                            eprintln!("Speech detected (noise floor {:.4} RMS)", vad.noise_floor())
                         }
-                        SIGTERM => {
-                            eprintln!("Received SIGTERM: Shutting down gracefully");
-                            if let Err(e) = recorder.stop_recording() {
-                                eprintln!("Failed to stop recording: {}", e);
-                            }
+                        VadEvent::SpeechEnd(_) => speech_ended = true,
+                    }
+                }
+                if speech_ended {
+                    eprintln!(
+                        "Silence detected after speech: Stop recording, transcribe, and output"
+                    );
+                    match stop_and_transcribe(
+                        &mut recorder,
+                        &beep_player,
+                        &config,
+                        args.pipe_to.as_ref(),
+                        args.output_format,
+                        None,
+                    )
+                    .await
+                    {
+                        Ok((exit_code, _)) => break Some(exit_code),
+                        Err(e) => eprintln!("Failed to get audio data: {}", e),
+                    }
+                    break None;
+                }
+            }
 
-                            // Clear buffer on shutdown
-                            if let Err(e) = recorder.clear_buffer() {
-                                eprintln!("Failed to clear audio buffer during shutdown: {}", e);
-                            }
+            // Wait for a signal or control request, processing audio every 50ms
+            let pending = tokio::select! {
+                signal = signals.next() => match signal {
+                    Some(signal) => Err(signal),
+                    // Signal stream ended
+                    None => break None,
+                },
+                Some(pending) = next_control_request(&mut control) => Ok(pending),
+                () = tokio::time::sleep(Duration::from_millis(50)) => continue,
+            };
+
+            let pending = match pending {
+                Ok(pending) => pending,
+                Err(SIGUSR1) => {
+                    eprintln!("Received SIGUSR1: Stop recording, transcribe, and output");
+
+                    match stop_and_transcribe(
+                        &mut recorder,
+                        &beep_player,
+                        &config,
+                        args.pipe_to.as_ref(),
+                        args.output_format,
+                        None,
+                    )
+                    .await
+                    {
+                        Ok((exit_code, _)) => break Some(exit_code),
+                        Err(e) => eprintln!("Failed to get audio data: {}", e),
+                    }
+
+                    break None;
+                }
+                Err(SIGUSR2) => {
+                    eprintln!("Received SIGUSR2: Cancel recording without transcribing");
+                    cancel_recording(&mut recorder, &beep_player).await;
+                    break Some(CANCELLED_EXIT_CODE);
+                }
+                Err(SIGTERM) => {
+                    eprintln!("Received SIGTERM: Shutting down gracefully");
+                    if let Err(e) = recorder.stop_recording() {
+                        eprintln!("Failed to stop recording: {}", e);
+                    }
+
+                    // Clear buffer on shutdown
+                    if let Err(e) = recorder.clear_buffer() {
+                        eprintln!("Failed to clear audio buffer during shutdown: {}", e);
+                    }
 
-                            break;
+                    break None;
+                }
+                Err(signal) => {
+                    eprintln!("Received unexpected signal: {}", signal);
+                    continue;
+                }
+            };
+
+            match pending.request.clone() {
+                control::Request::Start => {
+                    pending.respond(control::Response::error("already recording"));
+                }
+                control::Request::Stop | control::Request::Toggle => {
+                    eprintln!("Control: Stop recording, transcribe, and output");
+                    match stop_and_transcribe(
+                        &mut recorder,
+                        &beep_player,
+                        &config,
+                        args.pipe_to.as_ref(),
+                        args.output_format,
+                        None,
+                    )
+                    .await
+                    {
+                        Ok((exit_code, transcript)) => {
+                            pending.respond(control::Response {
+                                ok: exit_code == 0,
+                                transcript,
+                                ..control_status(&recorder, &config)
+                            });
+                            break Some(exit_code);
                         }
-                        _ => {
-                            eprintln!("Received unexpected signal: {}", signal);
+                        Err(e) => {
+                            eprintln!("Failed to get audio data: {}", e);
+                            pending.respond(control::Response::error(e.to_string()));
+                            break None;
                         }
                     }
                 }
-                Ok(None) => {
-                    // Signal stream ended
-                    break;
+                control::Request::Cancel => {
+                    eprintln!("Control: Cancel recording without transcribing");
+                    cancel_recording(&mut recorder, &beep_player).await;
+                    pending.respond(control_status(&recorder, &config));
+                    break Some(CANCELLED_EXIT_CODE);
                 }
-                Err(_) => {
-                    // Timeout occurred, continue processing audio
-                    continue;
+                control::Request::Status => {
+                    pending.respond(control_status(&recorder, &config));
+                }
+                control::Request::SetLanguage { language } => {
+                    eprintln!("Control: Language set to {}", language);
+                    config.whisper_language = language;
+                    pending.respond(control_status(&recorder, &config));
+                }
+                control::Request::SwitchProvider { provider } => {
+                    let switched = Config {
+                        transcription_provider: provider,
+                        ..config.clone()
+                    };
+                    match switched.validate() {
+                        Ok(()) => {
+                            eprintln!(
+                                "Control: Provider switched to {}",
+                                switched.transcription_provider
+                            );
+                            config = switched;
+                            pending.respond(control_status(&recorder, &config));
+                        }
+                        Err(e) => pending.respond(control::Response::error(e.to_string())),
+                    }
                 }
             }
+        };
+
+        // Remove the socket before exiting, which skips destructors
+        drop(control);
+        if let Some(exit_code) = exit_code {
+            std::process::exit(exit_code);
         }
     }
 
@@ -611,9 +2600,50 @@ async fn main() -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::RecordingProvider;
     use audio_processing::AudioProcessor;
     use wav::WavEncoder;
 
+    #[tokio::test]
+    async fn test_until_terminated() {
+        assert_eq!(
+            until_terminated(async { 42 }, std::future::pending::<()>()).await,
+            Some(42)
+        );
+
+        // Termination fires while the future is still running
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let terminated = until_terminated(
+            async {
+                tx.send(()).unwrap();
+                std::future::pending::<()>().await;
+            },
+            rx,
+        )
+        .await;
+        assert_eq!(terminated, None);
+    }
+
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_interrupted_recording_is_spooled() {
+        use crate::test_utils::{EnvGuard, ENV_MUTEX};
+        let _lock = ENV_MUTEX.lock().await;
+
+        let state = tempfile::tempdir().unwrap();
+        let _state_home = EnvGuard::set("XDG_STATE_HOME", state.path());
+
+        let config = Config {
+            audio_channels: 2,
+            ..Config::default()
+        };
+        spool_interrupted(&[0.5; 32000], 16000, &config);
+        let spooled = spool::Spool::open_default().list().unwrap();
+        assert_eq!(spooled.len(), 1);
+        assert!(spooled[0].details.error.contains("SIGTERM"));
+        assert!((spooled[0].details.duration - 1.0).abs() < 1e-9);
+    }
+
     #[tokio::test]
     async fn test_audio_processing_pipeline_integration() {
         // Create test audio: silence - speech - silence
@@ -641,10 +2671,18 @@ mod tests {
     #[tokio::test]
     async fn test_audio_processing_pipeline_empty_audio() {
         let test_config = Config::default();
-        let result = process_audio_for_transcription(vec![], 16000, &test_config, None).await;
+        let result = process_audio_for_transcription(
+            vec![],
+            16000,
+            &test_config,
+            None,
+            OutputFormat::Text,
+            std::future::pending::<()>(),
+        )
+        .await;
 
         assert!(
-            result.is_ok() && result.unwrap() == 1,
+            result.is_ok() && result.unwrap().0 == 1,
             "Audio processing should return exit code 1 with empty audio"
         );
     }
@@ -655,10 +2693,18 @@ mod tests {
         let short_audio = vec![0.5; 160]; // 0.01 seconds at 16kHz
 
         let test_config = Config::default();
-        let result = process_audio_for_transcription(short_audio, 16000, &test_config, None).await;
+        let result = process_audio_for_transcription(
+            short_audio,
+            16000,
+            &test_config,
+            None,
+            OutputFormat::Text,
+            std::future::pending::<()>(),
+        )
+        .await;
 
         assert!(
-            result.is_ok() && result.unwrap() == 1,
+            result.is_ok() && result.unwrap().0 == 1,
             "Audio processing should return exit code 1 with too short audio"
         );
     }
@@ -669,10 +2715,18 @@ mod tests {
         let silent_audio = vec![0.0; 1600]; // 0.1 seconds of silence
 
         let test_config = Config::default();
-        let result = process_audio_for_transcription(silent_audio, 16000, &test_config, None).await;
+        let result = process_audio_for_transcription(
+            silent_audio,
+            16000,
+            &test_config,
+            None,
+            OutputFormat::Text,
+            std::future::pending::<()>(),
+        )
+        .await;
 
         assert!(
-            result.is_ok() && result.unwrap() == 1,
+            result.is_ok() && result.unwrap().0 == 1,
             "Audio processing should return exit code 1 with only silence"
         );
     }
@@ -810,10 +2864,18 @@ mod tests {
         ];
 
         for (audio_data, description) in test_cases {
-            let result = process_audio_for_transcription(audio_data, 16000, &config, None).await;
+            let result = process_audio_for_transcription(
+                audio_data,
+                16000,
+                &config,
+                None,
+                OutputFormat::Text,
+                std::future::pending::<()>(),
+            )
+            .await;
 
             assert!(
-                result.is_ok() && result.unwrap() == 1,
+                result.is_ok() && result.unwrap().0 == 1,
                 "Should return exit code 1 for {}",
                 description
             );
@@ -831,12 +2893,19 @@ mod tests {
         let pipe_command = vec!["cat".to_string()];
 
         // Test with empty audio (should not execute command)
-        let result =
-            process_audio_for_transcription(vec![], 16000, &config, Some(&pipe_command)).await;
+        let result = process_audio_for_transcription(
+            vec![],
+            16000,
+            &config,
+            Some(&pipe_command),
+            OutputFormat::Text,
+            std::future::pending::<()>(),
+        )
+        .await;
 
         assert!(result.is_ok());
         // Should return exit code 1 without executing the command
-        assert_eq!(result.unwrap(), 1);
+        assert_eq!(result.unwrap().0, 1);
     }
 
     #[tokio::test]
@@ -850,12 +2919,19 @@ mod tests {
         let pipe_command = vec!["false".to_string()]; // Command that always fails
 
         // Test with empty audio (should not execute command)
-        let result =
-            process_audio_for_transcription(vec![], 16000, &config, Some(&pipe_command)).await;
+        let result = process_audio_for_transcription(
+            vec![],
+            16000,
+            &config,
+            Some(&pipe_command),
+            OutputFormat::Text,
+            std::future::pending::<()>(),
+        )
+        .await;
 
         assert!(result.is_ok());
         // Should return exit code 1 without executing the command
-        assert_eq!(result.unwrap(), 1);
+        assert_eq!(result.unwrap().0, 1);
     }
 
     #[tokio::test]
@@ -869,12 +2945,19 @@ mod tests {
         let pipe_command = vec!["nonexistent_command_12345".to_string()];
 
         // Test with empty audio (should not execute command)
-        let result =
-            process_audio_for_transcription(vec![], 16000, &config, Some(&pipe_command)).await;
+        let result = process_audio_for_transcription(
+            vec![],
+            16000,
+            &config,
+            Some(&pipe_command),
+            OutputFormat::Text,
+            std::future::pending::<()>(),
+        )
+        .await;
 
         assert!(result.is_ok());
         // Should return exit code 1 without executing the command
-        assert_eq!(result.unwrap(), 1);
+        assert_eq!(result.unwrap().0, 1);
     }
 
     #[tokio::test]
@@ -911,4 +2994,428 @@ mod tests {
         config.audio_buffer_duration_seconds = 0; // Invalid
         assert!(config.validate().is_err());
     }
+
+    #[test]
+    fn test_transcribe_subcommand_args() {
+        use clap::CommandFactory;
+        Args::command().debug_assert();
+
+        let args = Args::try_parse_from([
+            "waystt",
+            "transcribe",
+            "--output-format",
+            "srt",
+            "a.wav",
+            "b.wav",
+            "--pipe-to",
+            "ydotool",
+            "type",
+            "--file",
+            "-",
+        ])
+        .unwrap();
+        let Some(Command::Transcribe { files, pipe_to }) = args.command else {
+            panic!("expected the transcribe subcommand");
+        };
+        assert_eq!(files, vec![PathBuf::from("a.wav"), PathBuf::from("b.wav")]);
+        assert_eq!(args.output_format, OutputFormat::Srt);
+        assert_eq!(
+            pipe_to,
+            Some(vec![
+                "ydotool".to_string(),
+                "type".to_string(),
+                "--file".to_string(),
+                "-".to_string()
+            ])
+        );
+
+        assert!(Args::try_parse_from(["waystt", "transcribe"]).is_err());
+    }
+
+    #[test]
+    fn test_daemon_args() {
+        let args = Args::try_parse_from(["waystt", "--daemon", "--auto-stop", "1s"]).unwrap();
+        assert!(args.daemon);
+        assert_eq!(args.auto_stop, Some(Duration::from_secs(1)));
+
+        assert!(Args::try_parse_from(["waystt", "--daemon", "--continuous"]).is_err());
+        assert!(Args::try_parse_from(["waystt", "--daemon", "--stream"]).is_err());
+    }
+
+    #[test]
+    fn test_history_args() {
+        let args = Args::try_parse_from(["waystt", "history", "copy", "2", "-p", "cat"]).unwrap();
+        let Some(Command::History {
+            action: HistoryCommand::Copy { number, pipe_to },
+        }) = args.command
+        else {
+            panic!("expected history copy");
+        };
+        assert_eq!(number, 2);
+        assert_eq!(pipe_to, Some(vec!["cat".to_string()]));
+
+        let args = Args::try_parse_from(["waystt", "history", "list"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(Command::History {
+                action: HistoryCommand::List { limit: 20 }
+            })
+        ));
+        assert!(Args::try_parse_from(["waystt", "history", "show"]).is_err());
+    }
+
+    #[test]
+    fn test_config_check_args() {
+        let args =
+            Args::try_parse_from(["waystt", "config", "check", "--profile", "code"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(Command::Config {
+                action: ConfigCommand::Check
+            })
+        ));
+        assert_eq!(args.profile.as_deref(), Some("code"));
+        assert!(Args::try_parse_from(["waystt", "config"]).is_err());
+    }
+
+    #[test]
+    fn test_models_args() {
+        let args = Args::try_parse_from(["waystt", "models", "verify", "base.en"]).unwrap();
+        let Some(Command::Models {
+            action: ModelsCommand::Verify { name },
+        }) = args.command
+        else {
+            panic!("expected models verify");
+        };
+        assert_eq!(name.as_deref(), Some("base.en"));
+
+        let args = Args::try_parse_from(["waystt", "models", "info"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(Command::Models {
+                action: ModelsCommand::Info { name: None }
+            })
+        ));
+        assert!(Args::try_parse_from(["waystt", "models", "remove"]).is_err());
+    }
+
+    #[test]
+    fn test_retry_args() {
+        let args = Args::try_parse_from(["waystt", "retry", "--all", "-p", "wl-copy"]).unwrap();
+        let Some(Command::Retry { id, all, pipe_to }) = args.command else {
+            panic!("expected the retry command");
+        };
+        assert_eq!(id, None);
+        assert!(all);
+        assert_eq!(pipe_to, Some(vec!["wl-copy".to_string()]));
+
+        assert!(Args::try_parse_from(["waystt", "retry", "1700000000000", "--all"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stream_transcription_finishes_with_the_feed() {
+        let provider = RecordingProvider::default();
+
+        // The fallback streaming implementation only answers once the audio stream ends
+        let outcome = tokio::time::timeout(
+            Duration::from_secs(5),
+            stream_transcription(
+                &provider,
+                16000,
+                None,
+                |sender| async move {
+                    sender.unbounded_send(vec![1, 2]).unwrap();
+                    sender.unbounded_send(vec![3]).unwrap();
+                    StreamEnd::Finish
+                },
+                std::future::pending::<()>(),
+            ),
+        )
+        .await
+        .expect("the stream should end with the feed");
+
+        let StreamOutcome::Transcribed(Ok(text)) = outcome else {
+            panic!("expected a final transcript");
+        };
+        assert_eq!(text, "hello world");
+
+        let (wav, _) = provider.received.lock().unwrap().pop().unwrap();
+        assert_eq!(&wav[44..], &[1, 0, 2, 0, 3, 0]);
+
+        // Cancelling or aborting drops the provider stream without a transcript
+        let outcome = stream_transcription(
+            &provider,
+            16000,
+            None,
+            |sender| async move {
+                let _sender = sender;
+                StreamEnd::Cancel
+            },
+            std::future::pending::<()>(),
+        )
+        .await;
+        assert!(matches!(outcome, StreamOutcome::Cancelled));
+        let outcome = stream_transcription(
+            &provider,
+            16000,
+            None,
+            |sender| async move {
+                let _sender = sender;
+                StreamEnd::Abort
+            },
+            std::future::pending::<()>(),
+        )
+        .await;
+        assert!(matches!(outcome, StreamOutcome::Aborted));
+        assert!(provider.received.lock().unwrap().is_empty());
+
+        // Termination interrupts the final transcription, which cannot finish
+        // while the feed keeps the audio stream open
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let open_sender = std::sync::Mutex::new(None);
+        let outcome = stream_transcription(
+            &provider,
+            16000,
+            None,
+            |sender| {
+                *open_sender.lock().unwrap() = Some(sender);
+                async move {
+                    tx.send(()).unwrap();
+                    StreamEnd::Finish
+                }
+            },
+            rx,
+        )
+        .await;
+        assert!(matches!(outcome, StreamOutcome::Terminated));
+    }
+
+    /// Provider that never answers, like a long local inference
+    struct StalledProvider;
+
+    #[async_trait::async_trait]
+    impl TranscriptionProvider for StalledProvider {
+        async fn transcribe_with_language(
+            &self,
+            _audio_data: Vec<u8>,
+            _language: Option<String>,
+        ) -> Result<Transcript, TranscriptionError> {
+            std::future::pending().await
+        }
+    }
+
+    fn tone(seconds: usize) -> Vec<f32> {
+        (0..16000 * seconds)
+            .map(|i| 0.5 * (i as f32 * 440.0 * std::f32::consts::TAU / 16000.0).sin())
+            .collect()
+    }
+
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_termination_interrupts_the_provider_and_spools_the_recording() {
+        use crate::test_utils::{EnvGuard, ENV_MUTEX};
+        let _lock = ENV_MUTEX.lock().await;
+
+        let state = tempfile::tempdir().unwrap();
+        let _state_home = EnvGuard::set("XDG_STATE_HOME", state.path());
+
+        let config = Config {
+            enable_audio_feedback: false,
+            ..Config::default()
+        };
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        tx.send(()).unwrap();
+        let result = transcribe_recording(
+            tone(1),
+            16000,
+            &config,
+            None,
+            OutputFormat::Text,
+            Some(&StalledProvider),
+            AudioSource::Recording,
+            rx,
+        )
+        .await;
+        assert_eq!(result.unwrap(), (TERMINATED_EXIT_CODE, None));
+
+        let spooled = spool::Spool::open_default().list().unwrap();
+        assert_eq!(spooled.len(), 1);
+        assert!(spooled[0].details.error.contains("SIGTERM"));
+    }
+
+    #[tokio::test]
+    async fn test_termination_after_the_provider_answered_lets_output_finish() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("output.txt");
+        // The pipe command is still running when termination fires
+        let pipe_command = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "sleep 0.3 && cat > \"$0\"".to_string(),
+            output.display().to_string(),
+        ];
+        let config = Config {
+            enable_audio_feedback: false,
+            ..Config::default()
+        };
+        let (exit_code, transcript) = transcribe_recording(
+            tone(1),
+            16000,
+            &config,
+            Some(&pipe_command),
+            OutputFormat::Text,
+            Some(&RecordingProvider::default()),
+            AudioSource::Spooled,
+            tokio::time::sleep(Duration::from_millis(50)),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(exit_code, 0);
+        assert_eq!(transcript.unwrap().text, "hello world");
+        assert_eq!(std::fs::read_to_string(output).unwrap(), "hello world");
+    }
+
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_recording_is_spooled_when_provider_cannot_be_created() {
+        use crate::test_utils::{EnvGuard, ENV_MUTEX};
+        let _lock = ENV_MUTEX.lock().await;
+
+        let state = tempfile::tempdir().unwrap();
+        let _state_home = EnvGuard::set("XDG_STATE_HOME", state.path());
+
+        let config = Config {
+            transcription_provider: "unsupported".to_string(),
+            enable_audio_feedback: false,
+            ..Config::default()
+        };
+        let result = process_audio_for_transcription(
+            tone(1),
+            16000,
+            &config,
+            None,
+            OutputFormat::Text,
+            std::future::pending::<()>(),
+        )
+        .await;
+        assert_eq!(result.unwrap().0, 1);
+        let spooled = spool::Spool::open_default().list().unwrap();
+        assert_eq!(spooled.len(), 1);
+        assert_eq!(spooled[0].details.provider, "unsupported");
+        assert!(spooled[0].details.error.contains("Unsupported provider"));
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_files_converts_to_configured_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tone.wav");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 48000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for i in 0..48000 {
+            let sample = (0.5
+                * (i as f32 * 440.0 * std::f32::consts::TAU / 48000.0).sin()
+                * f32::from(i16::MAX)) as i16;
+            writer.write_sample(sample).unwrap();
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let config = Config {
+            enable_audio_feedback: false,
+            ..Config::default()
+        };
+        let provider = RecordingProvider::default();
+
+        let missing = dir.path().join("missing.wav");
+        let exit_code = transcribe_files(
+            &[path, missing],
+            &config,
+            None,
+            OutputFormat::Text,
+            &provider,
+        )
+        .await;
+
+        // The missing file fails the batch but does not stop it
+        assert_eq!(exit_code, 1);
+        let received = provider.received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+
+        let (wav, _) = &received[0];
+        assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), 1);
+        assert_eq!(
+            u32::from_le_bytes([wav[24], wav[25], wav[26], wav[27]]),
+            16000
+        );
+        let samples = (wav.len() - 44) / 2;
+        assert!((15900..=16000).contains(&samples), "{} samples", samples);
+    }
+
+    #[tokio::test]
+    async fn test_download_model_rejects_path_like_names() {
+        for name in ["../../../x", "models/base.bin", ""] {
+            let error = download_model(&Config::default(), name).await.unwrap_err();
+            assert!(
+                error.to_string().contains("Invalid model name"),
+                "{}",
+                error
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transcribe_files_names_each_file_in_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let tone: Vec<f32> = (0..16000)
+            .map(|i| 0.5 * (i as f32 * 440.0 * std::f32::consts::TAU / 16000.0).sin())
+            .collect();
+        let wav = WavEncoder::new(16000, 1).encode_to_wav(&tone).unwrap();
+        let files = [dir.path().join("first.wav"), dir.path().join("second.wav")];
+        for file in &files {
+            std::fs::write(file, &wav).unwrap();
+        }
+
+        let config = Config {
+            enable_audio_feedback: false,
+            ..Config::default()
+        };
+        let output = dir.path().join("output.jsonl");
+        let pipe_command = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "cat >> \"$0\" && echo >> \"$0\"".to_string(),
+            output.display().to_string(),
+        ];
+        let exit_code = transcribe_files(
+            &files,
+            &config,
+            Some(&pipe_command),
+            OutputFormat::Json,
+            &RecordingProvider::default(),
+        )
+        .await;
+        assert_eq!(exit_code, 0);
+
+        let output = std::fs::read_to_string(output).unwrap();
+        let named: Vec<String> = output
+            .lines()
+            .map(|line| {
+                let json: serde_json::Value = serde_json::from_str(line).unwrap();
+                json["metadata"]["file"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(
+            named,
+            files
+                .iter()
+                .map(|file| file.display().to_string())
+                .collect::<Vec<_>>()
+        );
+    }
 }
@@ -0,0 +1,1074 @@
+use anyhow::{anyhow, Result};
+use futures::stream::StreamExt;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+
+/// Where ggml models are downloaded from unless `WHISPER_MODEL_MIRROR` is set
+pub const DEFAULT_MIRROR: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
+
+/// A ggml model published by whisper.cpp
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogModel {
+    /// Short name such as `base.en`
+    pub name: &'static str,
+    /// Approximate download size in MiB
+    pub size_mib: u64,
+    /// Whether the model only transcribes English
+    pub english_only: bool,
+    /// SHA-256 of the file as published on the default mirror
+    pub sha256: &'static str,
+}
+
+impl CatalogModel {
+    /// File name in the mirror and the model directory, e.g. `ggml-base.en.bin`
+    pub fn file_name(&self) -> String {
+        format!("ggml-{}.bin", self.name)
+    }
+}
+
+const fn model(
+    name: &'static str,
+    size_mib: u64,
+    english_only: bool,
+    sha256: &'static str,
+) -> CatalogModel {
+    CatalogModel {
+        name,
+        size_mib,
+        english_only,
+        sha256,
+    }
+}
+
+/// Models that `waystt models` knows about, smallest first
+pub const CATALOG: &[CatalogModel] = &[
+    model(
+        "tiny",
+        75,
+        false,
+        "be07e048e1e599ad46341c8d2a135645097a538221678b7acdd1b1919c6e1b21",
+    ),
+    model(
+        "tiny.en",
+        75,
+        true,
+        "921e4cf8686fdd993dcd081a5da5b6c365bfde1162e72b08d75ac75289920b1f",
+    ),
+    model(
+        "base",
+        142,
+        false,
+        "60ed5bc3dd14eea856493d334349b405782ddcaf0028d4b5df4088345fba2efe",
+    ),
+    model(
+        "base.en",
+        142,
+        true,
+        "a03779c86df3323075f5e796cb2ce5029f00ec8869eee3fdfb897afe36c6d002",
+    ),
+    model(
+        "small",
+        466,
+        false,
+        "1be3a9b2063867b937e64e2ec7483364a79917e157fa98c5d94b5c1fffea987b",
+    ),
+    model(
+        "small.en",
+        466,
+        true,
+        "c6138d6d58ecc8322097e0f987c32f1be8bb0a18532a3f88f734d1bbf9c41e5d",
+    ),
+    model(
+        "medium",
+        1533,
+        false,
+        "6c14d5adee5f86394037b4e4e8b59f1673b6cee10e3cf0b11bbdbee79c156208",
+    ),
+    model(
+        "medium.en",
+        1533,
+        true,
+        "cc37e93478338ec7700281a7ac30a10128929eb8f427dda2e865faa8f6da4356",
+    ),
+    model(
+        "large-v3-turbo",
+        1624,
+        false,
+        "1fc70f774d38eb169993ac391eea357ef47c88757ef72ee5943879b7e8e2bc69",
+    ),
+    model(
+        "large-v1",
+        2951,
+        false,
+        "7d99f41a10525d0206bddadd86760181fa920438b6b33237e3118ff6c83bb53d",
+    ),
+    model(
+        "large-v2",
+        2951,
+        false,
+        "9a423fe4d40c82774b6af34115b8b935f34152246eb19e80e376071d3f999487",
+    ),
+    model(
+        "large-v3",
+        2951,
+        false,
+        "64d182b440b98d5203c4f9bd541544d84c605196c4f7b845dfa11fb23594d1e2",
+    ),
+];
+
+/// Find a catalog model by short name (`base.en`) or file name (`ggml-base.en.bin`)
+pub fn find(name: &str) -> Option<&'static CatalogModel> {
+    let short = name
+        .strip_prefix("ggml-")
+        .and_then(|rest| rest.strip_suffix(".bin"))
+        .unwrap_or(name);
+    CATALOG.iter().find(|model| model.name == short)
+}
+
+/// File name for a model given by catalog name or by file name
+/// Names outside the catalog are used as they are.
+pub fn file_name(name: &str) -> String {
+    find(name).map_or_else(|| name.to_string(), CatalogModel::file_name)
+}
+
+/// Check that a model name cannot point outside the model directory
+pub fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+        return Err(anyhow!(
+            "Invalid model name '{}': expected a catalog name or a file name in the model directory",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Download URL of a model file on a mirror
+pub fn model_url(mirror: &str, file: &str) -> String {
+    format!("{}/{}", mirror.trim_end_matches('/'), file)
+}
+
+/// Sidecar holding the SHA-256 of a downloaded model, in `sha256sum` format
+/// Only models outside the catalog have one; catalog models are pinned.
+fn checksum_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".sha256");
+    PathBuf::from(name)
+}
+
+/// Checksum recorded when the model at `path` was downloaded
+pub fn recorded_checksum(path: &Path) -> Option<String> {
+    let contents = std::fs::read_to_string(checksum_path(path)).ok()?;
+    contents.split_whitespace().next().map(str::to_lowercase)
+}
+
+/// SHA-256 of a file as lowercase hex
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Result of checking a model file
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verification {
+    /// The file matches the pinned or recorded checksum
+    Verified {
+        sha256: String,
+    },
+    Mismatch {
+        expected: String,
+        actual: String,
+    },
+    /// Neither a pinned nor a recorded checksum exists to compare against
+    Unknown {
+        sha256: String,
+    },
+}
+
+/// Check the model at `path` against the pinned catalog checksum
+/// Models outside the catalog are checked against the checksum recorded when
+/// they were downloaded.
+pub fn verify(path: &Path, pinned: Option<&str>) -> Result<Verification> {
+    let actual = sha256_file(path)?;
+    let expected = pinned
+        .map(str::to_lowercase)
+        .or_else(|| recorded_checksum(path));
+    Ok(match expected {
+        Some(expected) if expected == actual => Verification::Verified { sha256: actual },
+        Some(expected) => Verification::Mismatch { expected, actual },
+        None => Verification::Unknown { sha256: actual },
+    })
+}
+
+/// A model file in the model directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledModel {
+    pub file: String,
+    pub size: u64,
+}
+
+/// Model files in `dir`, sorted by name
+/// Unfinished downloads and checksum sidecars are not models.
+pub fn installed(dir: &Path) -> Result<Vec<InstalledModel>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(anyhow!("Failed to read {}: {}", dir.display(), e)),
+    };
+
+    let mut models: Vec<InstalledModel> = entries
+        .filter_map(std::result::Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            let file = entry.file_name().to_str()?.to_string();
+            if !file.ends_with(".bin") {
+                return None;
+            }
+            let size = entry.metadata().ok()?.len();
+            Some(InstalledModel { file, size })
+        })
+        .collect();
+    models.sort_by(|a, b| a.file.cmp(&b.file));
+    Ok(models)
+}
+
+/// Delete a model file and its recorded checksum
+pub fn remove(path: &Path) -> Result<()> {
+    std::fs::remove_file(path)
+        .map_err(|e| anyhow!("Failed to remove {}: {}", path.display(), e))?;
+    let _ = std::fs::remove_file(checksum_path(path));
+    Ok(())
+}
+
+/// How a failed download is retried
+/// The delay doubles after every attempt.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Why a download attempt stopped
+enum FetchError {
+    /// Worth another attempt: network errors, HTTP 5xx and 429, cut-off transfers
+    Transient(anyhow::Error),
+    Permanent(anyhow::Error),
+}
+
+/// Download `url` to `dest`, checking it against `pinned` when given
+/// The file is written to `<dest>.part` and only renamed into place once it
+/// is complete and verified, so an interrupted download never leaves a
+/// truncated model behind. Without a pinned checksum, the one of the download
+/// is recorded next to the model for `verify`.
+pub async fn download(url: &str, dest: &Path, pinned: Option<&str>) -> Result<()> {
+    download_with_retry(url, dest, pinned, RetryPolicy::default()).await
+}
+
+/// Download like `download`, retrying failed attempts with `policy`
+/// A `.part` file left by an earlier attempt or run is resumed with an HTTP
+/// Range request guarded by `If-Range`, so a file that changed on the server
+/// is downloaded again from the start instead of being spliced together.
+pub async fn download_with_retry(
+    url: &str,
+    dest: &Path,
+    pinned: Option<&str>,
+    policy: RetryPolicy,
+) -> Result<()> {
+    if let Some(dir) = dest.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    let file_name = dest
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+    let part = part_path(dest);
+    let client = reqwest::Client::new();
+
+    let mut retries = 0;
+    loop {
+        match fetch(&client, url, &part, &file_name).await {
+            Ok(()) => break,
+            Err(FetchError::Transient(e)) if retries < policy.max_retries => {
+                let delay = policy.initial_delay * 2u32.pow(retries.min(4));
+                retries += 1;
+                eprintln!(
+                    "{}; retrying in {:.0}s ({}/{})",
+                    e,
+                    delay.as_secs_f64(),
+                    retries,
+                    policy.max_retries
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(FetchError::Transient(e) | FetchError::Permanent(e)) => return Err(e),
+        }
+    }
+
+    let _ = tokio::fs::remove_file(validator_path(&part)).await;
+    let hashed = part.clone();
+    let actual = tokio::task::spawn_blocking(move || sha256_file(&hashed)).await??;
+    if let Some(expected) = pinned.filter(|expected| !expected.eq_ignore_ascii_case(&actual)) {
+        let _ = tokio::fs::remove_file(&part).await;
+        return Err(anyhow!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            file_name,
+            expected,
+            actual
+        ));
+    }
+
+    tokio::fs::rename(&part, dest).await?;
+    if pinned.is_none() {
+        tokio::fs::write(checksum_path(dest), format!("{}  {}\n", actual, file_name)).await?;
+    }
+    Ok(())
+}
+
+/// Unfinished download of the model at `dest`
+fn part_path(dest: &Path) -> PathBuf {
+    let mut name = dest.as_os_str().to_owned();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// ETag or Last-Modified of the file being downloaded into `part`
+fn validator_path(part: &Path) -> PathBuf {
+    let mut name = part.as_os_str().to_owned();
+    name.push(".validator");
+    PathBuf::from(name)
+}
+
+/// Strong ETag, or else Last-Modified, identifying the version of a file
+/// Weak ETags cannot be used with `If-Range`.
+fn validator(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    header(reqwest::header::ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(reqwest::header::LAST_MODIFIED))
+        .map(str::to_string)
+}
+
+/// Forget a partial download so the next attempt starts from the beginning
+async fn discard(part: &Path) {
+    let _ = tokio::fs::remove_file(part).await;
+    let _ = tokio::fs::remove_file(validator_path(part)).await;
+}
+
+/// One download attempt into `part`, resuming from its current length
+/// A part file is only resumed when the validator of its first response was
+/// stored; otherwise there is no way to tell it belongs to the same file.
+async fn fetch(
+    client: &reqwest::Client,
+    url: &str,
+    part: &Path,
+    file_name: &str,
+) -> Result<(), FetchError> {
+    let stored = tokio::fs::read_to_string(validator_path(part))
+        .await
+        .ok()
+        .map(|validator| validator.trim().to_string())
+        .filter(|validator| !validator.is_empty());
+    let offset = match stored {
+        Some(_) => tokio::fs::metadata(part)
+            .await
+            .map_or(0, |metadata| metadata.len()),
+        None => 0,
+    };
+
+    let mut request = client.get(url);
+    if let Some(validator) = stored.as_deref().filter(|_| offset > 0) {
+        request = request
+            .header(reqwest::header::RANGE, format!("bytes={}-", offset))
+            .header(reqwest::header::IF_RANGE, validator);
+    }
+    let resp = request
+        .send()
+        .await
+        .map_err(|e| FetchError::Transient(anyhow!("Download error: {}", e)))?;
+
+    let status = resp.status();
+    let content_range = resp
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .map(parse_content_range);
+    let current = validator(resp.headers());
+
+    let start = match status {
+        // A server that ignores If-Range may send a range of a newer file
+        reqwest::StatusCode::PARTIAL_CONTENT if offset > 0 && current != stored => {
+            discard(part).await;
+            return Err(FetchError::Transient(anyhow!(
+                "{} changed on the server, starting over",
+                file_name
+            )));
+        }
+        reqwest::StatusCode::PARTIAL_CONTENT
+            if offset > 0 && content_range.and_then(|range| range.0) == Some(offset) =>
+        {
+            offset
+        }
+        reqwest::StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => {
+            // Nothing is left to send when the part file already holds the whole model
+            if content_range.and_then(|range| range.1) == Some(offset) {
+                return Ok(());
+            }
+            discard(part).await;
+            return Err(FetchError::Transient(anyhow!(
+                "Server cannot resume {} at byte {}, starting over",
+                file_name,
+                offset
+            )));
+        }
+        reqwest::StatusCode::PARTIAL_CONTENT => {
+            discard(part).await;
+            return Err(FetchError::Transient(anyhow!(
+                "Server sent an unexpected range for {}, starting over",
+                file_name
+            )));
+        }
+        // A full response, also when the file changed or the server ignored the range
+        status if status.is_success() => 0,
+        status if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+            return Err(FetchError::Transient(anyhow!(
+                "Failed to download model: {}",
+                status
+            )));
+        }
+        status => {
+            return Err(FetchError::Permanent(anyhow!(
+                "Failed to download model: {}",
+                status
+            )));
+        }
+    };
+
+    let total = resp.content_length().map(|length| start + length);
+    let write_error = |e: std::io::Error| {
+        FetchError::Permanent(anyhow!("Failed to write {}: {}", part.display(), e))
+    };
+    let mut file = if start > 0 {
+        tokio::fs::OpenOptions::new().append(true).open(part).await
+    } else {
+        // Remember which version of the file the part holds, for resuming it
+        match &current {
+            Some(validator) => tokio::fs::write(validator_path(part), validator).await,
+            None => {
+                let _ = tokio::fs::remove_file(validator_path(part)).await;
+                Ok(())
+            }
+        }
+        .map_err(write_error)?;
+        tokio::fs::File::create(part).await
+    }
+    .map_err(write_error)?;
+
+    let mut progress = Progress::new(file_name, start, total);
+    let mut downloaded = start;
+    let mut stream = resp.bytes_stream();
+    let mut interrupted = None;
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                interrupted = Some(e);
+                break;
+            }
+        };
+        file.write_all(&chunk).await.map_err(|e| {
+            FetchError::Permanent(anyhow!("Failed to write {}: {}", part.display(), e))
+        })?;
+        downloaded += chunk.len() as u64;
+        progress.update(downloaded);
+    }
+    // Keep what arrived so the next attempt can resume from it
+    file.flush()
+        .await
+        .and(file.sync_all().await)
+        .map_err(|e| FetchError::Permanent(anyhow!("Failed to write {}: {}", part.display(), e)))?;
+    progress.finish(downloaded);
+
+    if let Some(e) = interrupted {
+        return Err(FetchError::Transient(anyhow!("Download error: {}", e)));
+    }
+    match total {
+        Some(total) if total != downloaded => Err(FetchError::Transient(anyhow!(
+            "Download incomplete: received {} of {} bytes",
+            downloaded,
+            total
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// First byte and total length from a `Content-Range` header such as
+/// `bytes 100-199/1000` or `bytes */1000`
+fn parse_content_range(value: &str) -> (Option<u64>, Option<u64>) {
+    let Some((range, total)) = value
+        .trim()
+        .strip_prefix("bytes ")
+        .and_then(|v| v.split_once('/'))
+    else {
+        return (None, None);
+    };
+    let start = range
+        .split_once('-')
+        .and_then(|(start, _)| start.trim().parse().ok());
+    (start, total.trim().parse().ok())
+}
+
+/// Download progress on stderr, so stdout stays clean for scripts
+struct Progress<'a> {
+    file_name: &'a str,
+    total: Option<u64>,
+    /// Bytes already on disk when this attempt started
+    resumed_from: u64,
+    started: Instant,
+    last_update: Option<Instant>,
+}
+
+impl<'a> Progress<'a> {
+    fn new(file_name: &'a str, resumed_from: u64, total: Option<u64>) -> Self {
+        if resumed_from > 0 {
+            eprintln!(
+                "Resuming {} at {:.1} MB",
+                file_name,
+                resumed_from as f64 / 1024.0 / 1024.0
+            );
+        }
+        Self {
+            file_name,
+            total,
+            resumed_from,
+            started: Instant::now(),
+            last_update: None,
+        }
+    }
+
+    /// Redraw the progress line, at most a few times per second
+    fn update(&mut self, downloaded: u64) {
+        if self
+            .last_update
+            .is_some_and(|last| last.elapsed() < Duration::from_millis(200))
+        {
+            return;
+        }
+        self.last_update = Some(Instant::now());
+        self.draw(downloaded);
+    }
+
+    fn draw(&self, downloaded: u64) {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let speed = if elapsed > 0.0 {
+            (downloaded - self.resumed_from) as f64 / elapsed / 1024.0 / 1024.0 // MB/s
+        } else {
+            0.0
+        };
+
+        if let Some(total) = self.total {
+            let percentage = (downloaded as f64 / total as f64) * 100.0;
+            let eta = if speed > 0.0 {
+                total.saturating_sub(downloaded) as f64 / (speed * 1024.0 * 1024.0)
+            } else {
+                0.0
+            };
+            eprint!(
+                "\r{}... {:.1}% ({:.1} MB/s, ETA: {:.0}s)    ",
+                self.file_name, percentage, speed, eta
+            );
+        } else {
+            eprint!(
+                "\r{}... {:.1} MB downloaded    ",
+                self.file_name,
+                downloaded as f64 / 1024.0 / 1024.0
+            );
+        }
+    }
+
+    /// Draw the final state and end the progress line
+    fn finish(&self, downloaded: u64) {
+        if self.last_update.is_some() {
+            self.draw(downloaded);
+            eprintln!();
+        }
+    }
+}
+
+/// Human-readable size, e.g. `142 MiB` or `1.5 GiB`
+pub fn format_size(bytes: u64) -> String {
+    let mib = bytes as f64 / 1024.0 / 1024.0;
+    if mib >= 1024.0 {
+        format!("{:.1} GiB", mib / 1024.0)
+    } else {
+        format!("{:.0} MiB", mib)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sha256_of(data: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(data))
+    }
+
+    #[test]
+    fn test_find_by_short_or_file_name() {
+        assert_eq!(find("base.en").unwrap().name, "base.en");
+        assert_eq!(find("ggml-large-v3.bin").unwrap().name, "large-v3");
+        assert!(find("ggml-custom.bin").is_none());
+
+        assert_eq!(file_name("tiny"), "ggml-tiny.bin");
+        assert_eq!(file_name("ggml-tiny.bin"), "ggml-tiny.bin");
+        assert_eq!(file_name("my-finetune.bin"), "my-finetune.bin");
+        assert_eq!(
+            model_url("http://127.0.0.1:8080/models/", "ggml-tiny.bin"),
+            "http://127.0.0.1:8080/models/ggml-tiny.bin"
+        );
+    }
+
+    #[test]
+    fn test_catalog_names_are_unique() {
+        for (i, model) in CATALOG.iter().enumerate() {
+            assert!(CATALOG[i + 1..]
+                .iter()
+                .all(|other| other.name != model.name));
+            assert_eq!(find(&model.file_name()), Some(model));
+            assert_eq!(model.sha256.len(), 64, "{}", model.name);
+            assert!(model
+                .sha256
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)));
+        }
+    }
+
+    #[test]
+    fn test_check_name() {
+        assert!(check_name("base.en").is_ok());
+        assert!(check_name("ggml-my-finetune.bin").is_ok());
+        assert!(check_name("").is_err());
+        assert!(check_name("../.env").is_err());
+        assert!(check_name("..").is_err());
+        assert!(check_name("/etc/passwd").is_err());
+        assert!(check_name("models/ggml-base.bin").is_err());
+        assert!(check_name("models\\ggml-base.bin").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_download_renames_and_records_checksum() {
+        let data = b"ggml model bytes".to_vec();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/ggml-tiny.bin")
+            .with_status(200)
+            .with_body(&data)
+            .create_async()
+            .await;
+
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("models").join("ggml-tiny.bin");
+        let url = model_url(&server.url(), "ggml-tiny.bin");
+        download(&url, &dest, Some(&sha256_of(&data)))
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(std::fs::read(&dest).unwrap(), data);
+        assert!(!dir.path().join("models/ggml-tiny.bin.part").exists());
+        // Pinned models are verified against the pin, not a sidecar
+        assert_eq!(recorded_checksum(&dest), None);
+        assert_eq!(
+            verify(&dest, Some(&sha256_of(&data))).unwrap(),
+            Verification::Verified {
+                sha256: sha256_of(&data)
+            }
+        );
+
+        // Custom models record the checksum of the download
+        let custom = dir.path().join("models").join("ggml-custom.bin");
+        server
+            .mock("GET", "/ggml-custom.bin")
+            .with_status(200)
+            .with_body(&data)
+            .create_async()
+            .await;
+        download(&model_url(&server.url(), "ggml-custom.bin"), &custom, None)
+            .await
+            .unwrap();
+        assert_eq!(recorded_checksum(&custom), Some(sha256_of(&data)));
+        assert_eq!(
+            verify(&custom, None).unwrap(),
+            Verification::Verified {
+                sha256: sha256_of(&data)
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_download_checksum_mismatch_leaves_nothing() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/ggml-tiny.bin")
+            .with_status(200)
+            .with_body("tampered")
+            .create_async()
+            .await;
+
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("ggml-tiny.bin");
+        let url = model_url(&server.url(), "ggml-tiny.bin");
+        let error = download(&url, &dest, Some(&sha256_of(b"original")))
+            .await
+            .unwrap_err();
+
+        assert!(error.to_string().contains("Checksum mismatch"));
+        assert!(installed(dir.path()).unwrap().is_empty());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_download_http_error() {
+        let mut server = mockito::Server::new_async().await;
+        // Client errors are not retried
+        let mock = server
+            .mock("GET", "/ggml-missing.bin")
+            .with_status(404)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("ggml-missing.bin");
+        let url = model_url(&server.url(), "ggml-missing.bin");
+        assert!(download(&url, &dest, None).await.is_err());
+        mock.assert_async().await;
+        assert!(!dest.exists());
+    }
+
+    const NO_DELAY: RetryPolicy = RetryPolicy {
+        max_retries: 2,
+        initial_delay: Duration::ZERO,
+    };
+
+    #[tokio::test]
+    async fn test_download_resumes_part_file() {
+        let data = b"ggml model bytes".to_vec();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/ggml-tiny.bin")
+            .match_header("range", "bytes=5-")
+            .match_header("if-range", "\"v1\"")
+            .with_status(206)
+            .with_header("content-range", "bytes 5-15/16")
+            .with_header("etag", "\"v1\"")
+            .with_body(&data[5..])
+            .create_async()
+            .await;
+
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("ggml-tiny.bin");
+        let part = part_path(&dest);
+        std::fs::write(&part, &data[..5]).unwrap();
+        std::fs::write(validator_path(&part), "\"v1\"").unwrap();
+        let url = model_url(&server.url(), "ggml-tiny.bin");
+        download_with_retry(&url, &dest, Some(&sha256_of(&data)), NO_DELAY)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(std::fs::read(&dest).unwrap(), data);
+        assert!(!part.exists());
+        assert!(!validator_path(&part).exists());
+    }
+
+    #[tokio::test]
+    async fn test_download_restarts_part_file_without_validator() {
+        let data = b"ggml model bytes".to_vec();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/ggml-tiny.bin")
+            .match_header("range", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body(&data)
+            .create_async()
+            .await;
+
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("ggml-tiny.bin");
+        std::fs::write(part_path(&dest), b"stale").unwrap();
+        let url = model_url(&server.url(), "ggml-tiny.bin");
+        download_with_retry(&url, &dest, Some(&sha256_of(&data)), NO_DELAY)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(std::fs::read(&dest).unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_download_restarts_when_file_changed() {
+        let data = b"ggml model bytes".to_vec();
+        let mut server = mockito::Server::new_async().await;
+        // A server that ignores If-Range and sends a range of the new file
+        let range = server
+            .mock("GET", "/ggml-tiny.bin")
+            .match_header("range", "bytes=5-")
+            .with_status(206)
+            .with_header("content-range", "bytes 5-15/16")
+            .with_header("etag", "\"v2\"")
+            .with_body(&data[5..])
+            .create_async()
+            .await;
+        let full = server
+            .mock("GET", "/ggml-tiny.bin")
+            .match_header("range", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", "\"v2\"")
+            .with_body(&data)
+            .create_async()
+            .await;
+
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("ggml-tiny.bin");
+        let part = part_path(&dest);
+        std::fs::write(&part, b"stale").unwrap();
+        std::fs::write(validator_path(&part), "\"v1\"").unwrap();
+        let url = model_url(&server.url(), "ggml-tiny.bin");
+        download_with_retry(&url, &dest, Some(&sha256_of(&data)), NO_DELAY)
+            .await
+            .unwrap();
+
+        range.assert_async().await;
+        full.assert_async().await;
+        assert_eq!(std::fs::read(&dest).unwrap(), data);
+    }
+
+    #[test]
+    fn test_validator_prefers_strong_etag() {
+        use reqwest::header::{HeaderMap, HeaderValue, ETAG, LAST_MODIFIED};
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(validator(&headers), None);
+        headers.insert(
+            LAST_MODIFIED,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        headers.insert(ETAG, HeaderValue::from_static("W/\"weak\""));
+        assert_eq!(
+            validator(&headers).as_deref(),
+            Some("Wed, 21 Oct 2015 07:28:00 GMT")
+        );
+        headers.insert(ETAG, HeaderValue::from_static("\"strong\""));
+        assert_eq!(validator(&headers).as_deref(), Some("\"strong\""));
+    }
+
+    #[tokio::test]
+    async fn test_download_restarts_when_range_is_ignored() {
+        let data = b"ggml model bytes".to_vec();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/ggml-tiny.bin")
+            .match_header("range", "bytes=5-")
+            .with_status(200)
+            .with_body(&data)
+            .create_async()
+            .await;
+
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("ggml-tiny.bin");
+        let part = part_path(&dest);
+        std::fs::write(&part, b"stale").unwrap();
+        std::fs::write(validator_path(&part), "\"v1\"").unwrap();
+        let url = model_url(&server.url(), "ggml-tiny.bin");
+        download_with_retry(&url, &dest, Some(&sha256_of(&data)), NO_DELAY)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(std::fs::read(&dest).unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_download_completes_part_file_that_is_whole() {
+        let data = b"ggml model bytes".to_vec();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/ggml-tiny.bin")
+            .match_header("range", "bytes=16-")
+            .with_status(416)
+            .with_header("content-range", "bytes */16")
+            .create_async()
+            .await;
+
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("ggml-tiny.bin");
+        let part = part_path(&dest);
+        std::fs::write(&part, &data).unwrap();
+        std::fs::write(validator_path(&part), "\"v1\"").unwrap();
+        let url = model_url(&server.url(), "ggml-tiny.bin");
+        download_with_retry(&url, &dest, None, NO_DELAY)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(std::fs::read(&dest).unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_download_retries_server_errors() {
+        let data = b"ggml model bytes".to_vec();
+        let mut server = mockito::Server::new_async().await;
+        let unavailable = server
+            .mock("GET", "/ggml-tiny.bin")
+            .with_status(503)
+            .expect(2)
+            .create_async()
+            .await;
+        let ok = server
+            .mock("GET", "/ggml-tiny.bin")
+            .with_status(200)
+            .with_body(&data)
+            .create_async()
+            .await;
+
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("ggml-tiny.bin");
+        let url = model_url(&server.url(), "ggml-tiny.bin");
+        download_with_retry(&url, &dest, None, NO_DELAY)
+            .await
+            .unwrap();
+
+        unavailable.assert_async().await;
+        ok.assert_async().await;
+        assert_eq!(std::fs::read(&dest).unwrap(), data);
+    }
+
+    #[tokio::test]
+    async fn test_download_gives_up_after_retries() {
+        let mut server = mockito::Server::new_async().await;
+        let unavailable = server
+            .mock("GET", "/ggml-tiny.bin")
+            .with_status(503)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("ggml-tiny.bin");
+        let url = model_url(&server.url(), "ggml-tiny.bin");
+        let error = download_with_retry(&url, &dest, None, NO_DELAY)
+            .await
+            .unwrap_err();
+
+        unavailable.assert_async().await;
+        assert!(error.to_string().contains("503"));
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(
+            parse_content_range("bytes 100-199/1000"),
+            (Some(100), Some(1000))
+        );
+        assert_eq!(parse_content_range("bytes */1000"), (None, Some(1000)));
+        assert_eq!(parse_content_range("bytes 0-9/*"), (Some(0), None));
+        assert_eq!(parse_content_range("items 1-2/3"), (None, None));
+    }
+
+    #[test]
+    fn test_verify_detects_corruption() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("ggml-base.bin");
+        std::fs::write(&path, b"ggml model bytes").unwrap();
+
+        assert_eq!(
+            verify(&path, None).unwrap(),
+            Verification::Unknown {
+                sha256: sha256_of(b"ggml model bytes")
+            }
+        );
+
+        let pinned = sha256_of(b"other bytes");
+        assert!(matches!(
+            verify(&path, Some(&pinned)).unwrap(),
+            Verification::Mismatch { expected, .. } if expected == pinned
+        ));
+
+        // A recorded checksum is used when nothing is pinned
+        std::fs::write(
+            checksum_path(&path),
+            format!("{}  ggml-base.bin\n", sha256_of(b"ggml model bytes")),
+        )
+        .unwrap();
+        assert!(matches!(
+            verify(&path, None).unwrap(),
+            Verification::Verified { .. }
+        ));
+        std::fs::write(&path, b"truncated").unwrap();
+        assert!(matches!(
+            verify(&path, None).unwrap(),
+            Verification::Mismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn test_installed_and_remove() {
+        let dir = TempDir::new().unwrap();
+        assert!(installed(&dir.path().join("missing")).unwrap().is_empty());
+
+        std::fs::write(dir.path().join("ggml-base.bin"), b"base").unwrap();
+        std::fs::write(dir.path().join("ggml-base.bin.sha256"), b"abc").unwrap();
+        std::fs::write(dir.path().join("ggml-tiny.bin"), b"tiny!").unwrap();
+        std::fs::write(dir.path().join("ggml-small.bin.part"), b"sm").unwrap();
+
+        let models = installed(dir.path()).unwrap();
+        assert_eq!(
+            models,
+            vec![
+                InstalledModel {
+                    file: "ggml-base.bin".to_string(),
+                    size: 4
+                },
+                InstalledModel {
+                    file: "ggml-tiny.bin".to_string(),
+                    size: 5
+                },
+            ]
+        );
+
+        remove(&dir.path().join("ggml-base.bin")).unwrap();
+        assert!(!dir.path().join("ggml-base.bin.sha256").exists());
+        assert_eq!(installed(dir.path()).unwrap().len(), 1);
+        assert!(remove(&dir.path().join("ggml-base.bin")).is_err());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(142 * 1024 * 1024), "142 MiB");
+        assert_eq!(format_size(1536 * 1024 * 1024), "1.5 GiB");
+    }
+}
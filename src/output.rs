@@ -0,0 +1,267 @@
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_sign_loss)]
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::transcription::{Segment, Transcript};
+
+/// How a transcription is written to stdout or the pipe command
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Plain transcribed text
+    #[default]
+    Text,
+    /// Transcript with segments, words and metadata as a single-line JSON object
+    Json,
+    /// SubRip subtitles
+    Srt,
+    /// WebVTT subtitles
+    Vtt,
+    /// Tab-separated start, end (milliseconds) and text per segment
+    Tsv,
+}
+
+/// Details about how a transcription was produced
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptionMetadata {
+    pub provider: String,
+    pub model: String,
+    /// Length of the transcribed audio in seconds
+    pub audio_duration: f64,
+    /// Time spent waiting for the provider in seconds
+    pub processing_latency: f64,
+    /// File the audio was read from by `waystt transcribe`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+#[derive(Serialize)]
+struct JsonOutput<'a> {
+    #[serde(flatten)]
+    transcript: &'a Transcript,
+    metadata: &'a TranscriptionMetadata,
+}
+
+/// Render a transcript in the requested format
+pub fn render(
+    format: OutputFormat,
+    transcript: &Transcript,
+    metadata: &TranscriptionMetadata,
+) -> String {
+    match format {
+        OutputFormat::Text => transcript.text.clone(),
+        OutputFormat::Json => serde_json::to_string(&JsonOutput {
+            transcript,
+            metadata,
+        })
+        .expect("transcripts always serialize"),
+        OutputFormat::Srt => cues(transcript, metadata)
+            .iter()
+            .enumerate()
+            .map(|(i, cue)| {
+                format!(
+                    "{}\n{} --> {}\n{}\n",
+                    i + 1,
+                    timestamp(cue.start, ','),
+                    timestamp(cue.end, ','),
+                    cue.text
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        OutputFormat::Vtt => {
+            let mut output = String::from("WEBVTT\n");
+            for cue in cues(transcript, metadata) {
+                output.push_str(&format!(
+                    "\n{} --> {}\n{}\n",
+                    timestamp(cue.start, '.'),
+                    timestamp(cue.end, '.'),
+                    cue.text
+                ));
+            }
+            output
+        }
+        OutputFormat::Tsv => {
+            let mut output = String::from("start\tend\ttext");
+            for cue in cues(transcript, metadata) {
+                output.push_str(&format!(
+                    "\n{}\t{}\t{}",
+                    milliseconds(cue.start),
+                    milliseconds(cue.end),
+                    cue.text.replace(['\t', '\n'], " ")
+                ));
+            }
+            output
+        }
+    }
+}
+
+/// Segments to render as timed cues
+/// Providers without timestamps get one cue spanning the whole recording.
+fn cues(transcript: &Transcript, metadata: &TranscriptionMetadata) -> Vec<Segment> {
+    if !transcript.segments.is_empty() {
+        return transcript
+            .segments
+            .iter()
+            .filter(|segment| !segment.text.trim().is_empty())
+            .cloned()
+            .collect();
+    }
+
+    if transcript.text.trim().is_empty() {
+        return Vec::new();
+    }
+
+    vec![Segment {
+        text: transcript.text.trim().to_string(),
+        start: 0.0,
+        end: metadata.audio_duration,
+        confidence: transcript.confidence,
+    }]
+}
+
+fn milliseconds(seconds: f64) -> u64 {
+    (seconds.max(0.0) * 1000.0).round() as u64
+}
+
+/// Format seconds as `HH:MM:SS<separator>mmm`
+fn timestamp(seconds: f64, separator: char) -> String {
+    let ms = milliseconds(seconds);
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::Word;
+
+    fn metadata() -> TranscriptionMetadata {
+        TranscriptionMetadata {
+            provider: "openai".to_string(),
+            model: "whisper-1".to_string(),
+            audio_duration: 4.25,
+            processing_latency: 0.5,
+            file: None,
+        }
+    }
+
+    fn transcript() -> Transcript {
+        let segments = vec![
+            Segment {
+                text: "Hello there.".to_string(),
+                start: 0.0,
+                end: 1.5,
+                confidence: Some(0.9),
+            },
+            Segment {
+                text: "General Kenobi.".to_string(),
+                start: 1.5,
+                end: 3723.004,
+                confidence: None,
+            },
+        ];
+        let words = vec![Word {
+            text: "Hello".to_string(),
+            start: 0.0,
+            end: 0.5,
+            confidence: None,
+        }];
+        Transcript::from_segments(segments, words, Some("en".to_string()))
+    }
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(0.0, ','), "00:00:00,000");
+        assert_eq!(timestamp(1.5, '.'), "00:00:01.500");
+        assert_eq!(timestamp(3723.004, ','), "01:02:03,004");
+        assert_eq!(timestamp(-1.0, ','), "00:00:00,000");
+    }
+
+    #[test]
+    fn test_render_text() {
+        assert_eq!(
+            render(OutputFormat::Text, &transcript(), &metadata()),
+            "Hello there. General Kenobi."
+        );
+    }
+
+    #[test]
+    fn test_render_srt() {
+        assert_eq!(
+            render(OutputFormat::Srt, &transcript(), &metadata()),
+            "1\n00:00:00,000 --> 00:00:01,500\nHello there.\n\n\
+             2\n00:00:01,500 --> 01:02:03,004\nGeneral Kenobi.\n"
+        );
+    }
+
+    #[test]
+    fn test_render_vtt() {
+        assert_eq!(
+            render(OutputFormat::Vtt, &transcript(), &metadata()),
+            "WEBVTT\n\n00:00:00.000 --> 00:00:01.500\nHello there.\n\n\
+             00:00:01.500 --> 01:02:03.004\nGeneral Kenobi.\n"
+        );
+    }
+
+    #[test]
+    fn test_render_tsv() {
+        assert_eq!(
+            render(OutputFormat::Tsv, &transcript(), &metadata()),
+            "start\tend\ttext\n0\t1500\tHello there.\n1500\t3723004\tGeneral Kenobi."
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let output = render(OutputFormat::Json, &transcript(), &metadata());
+        assert!(!output.contains('\n'));
+
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["text"], "Hello there. General Kenobi.");
+        assert_eq!(json["language"], "en");
+        // Left out when the provider reports no probability
+        assert!(json.get("language_probability").is_none());
+        assert_eq!(json["segments"][1]["start"], 1.5);
+        assert_eq!(json["segments"][1]["confidence"], serde_json::Value::Null);
+        assert_eq!(json["words"][0]["text"], "Hello");
+        assert_eq!(json["metadata"]["provider"], "openai");
+        assert_eq!(json["metadata"]["model"], "whisper-1");
+        assert_eq!(json["metadata"]["audio_duration"], 4.25);
+        assert_eq!(json["metadata"]["processing_latency"], 0.5);
+        assert!(json["metadata"].get("file").is_none());
+
+        let metadata = TranscriptionMetadata {
+            file: Some("talk.wav".to_string()),
+            ..metadata()
+        };
+        let output = render(OutputFormat::Json, &transcript(), &metadata);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["metadata"]["file"], "talk.wav");
+    }
+
+    #[test]
+    fn test_subtitles_without_segments_span_recording() {
+        let transcript = Transcript {
+            text: "hello".to_string(),
+            ..Transcript::default()
+        };
+        assert_eq!(
+            render(OutputFormat::Srt, &transcript, &metadata()),
+            "1\n00:00:00,000 --> 00:00:04,250\nhello\n"
+        );
+
+        // Empty transcriptions produce no cues
+        assert_eq!(
+            render(OutputFormat::Vtt, &Transcript::default(), &metadata()),
+            "WEBVTT\n"
+        );
+    }
+}
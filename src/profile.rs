@@ -0,0 +1,334 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+
+/// Contents of `config.toml`: named profiles selected with `--profile`
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    profile: BTreeMap<String, toml::Table>,
+}
+
+/// A profile loaded from `config.toml`
+#[derive(Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    settings: ProfileSettings,
+    /// Keys set in the profile table, to report where settings came from
+    keys: BTreeSet<String>,
+}
+
+impl Profile {
+    /// Override the settings of `config` that this profile sets
+    pub fn apply(&self, config: Config) -> Config {
+        self.settings.clone().apply(config)
+    }
+
+    /// Whether the profile sets the setting with this environment variable name
+    pub fn sets(&self, key: &str) -> bool {
+        self.keys.contains(&key.to_lowercase())
+    }
+}
+
+/// Settings of one `[profile.<name>]` table
+/// Keys are the environment variable names in lowercase, and every key is
+/// optional. They override the `.env` file, and variables set in the
+/// environment override them.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileSettings {
+    pub openai_api_key: Option<String>,
+    pub openai_base_url: Option<String>,
+    pub openai_prompt: Option<String>,
+    pub openai_temperature: Option<f32>,
+    pub openai_response_format: Option<String>,
+    pub transcription_provider: Option<String>,
+    pub audio_buffer_duration_seconds: Option<usize>,
+    pub audio_sample_rate: Option<u32>,
+    pub audio_channels: Option<u16>,
+    pub audio_input_device: Option<String>,
+    pub audio_pre_roll_ms: Option<u64>,
+    pub audio_standby: Option<bool>,
+    pub whisper_model: Option<String>,
+    pub whisper_language: Option<String>,
+    pub whisper_timeout_seconds: Option<u64>,
+    pub whisper_max_retries: Option<u32>,
+    pub whisper_model_mirror: Option<String>,
+    pub whisper_allowed_languages: Option<Vec<String>>,
+    pub translate: Option<bool>,
+    pub local_whisper_threads: Option<u32>,
+    pub local_whisper_beam_size: Option<u32>,
+    pub local_whisper_temperature_increment: Option<f32>,
+    pub local_whisper_prompt: Option<String>,
+    pub local_whisper_no_context: Option<bool>,
+    pub rust_log: Option<String>,
+    pub enable_audio_feedback: Option<bool>,
+    pub beep_volume: Option<f32>,
+    pub history_enabled: Option<bool>,
+    pub history_save_audio: Option<bool>,
+    pub history_max_entries: Option<usize>,
+    pub history_max_age_days: Option<u64>,
+    pub google_application_credentials: Option<String>,
+    pub google_speech_language_code: Option<String>,
+    pub google_speech_model: Option<String>,
+    pub google_speech_alternative_languages: Option<Vec<String>>,
+}
+
+impl ProfileSettings {
+    /// Override the settings of `config` that this profile sets
+    pub fn apply(self, mut config: Config) -> Config {
+        if let Some(key) = self.openai_api_key {
+            config.openai_api_key = Some(key);
+        }
+        if let Some(url) = self.openai_base_url {
+            config.openai_base_url = Some(url);
+        }
+        if let Some(prompt) = self.openai_prompt.filter(|p| !p.trim().is_empty()) {
+            config.openai_prompt = Some(prompt);
+        }
+        if let Some(temperature) = self.openai_temperature {
+            config.openai_temperature = Some(temperature);
+        }
+        if let Some(format) = self.openai_response_format {
+            config.openai_response_format = format;
+        }
+        if let Some(provider) = self.transcription_provider {
+            config.transcription_provider = provider;
+        }
+        if let Some(duration) = self.audio_buffer_duration_seconds {
+            config.audio_buffer_duration_seconds = duration;
+        }
+        if let Some(sample_rate) = self.audio_sample_rate {
+            config.audio_sample_rate = sample_rate;
+        }
+        if let Some(channels) = self.audio_channels {
+            config.audio_channels = channels;
+        }
+        if let Some(device) = self.audio_input_device.filter(|d| !d.trim().is_empty()) {
+            config.audio_input_device = Some(device);
+        }
+        if let Some(pre_roll) = self.audio_pre_roll_ms {
+            config.audio_pre_roll_ms = pre_roll;
+        }
+        if let Some(standby) = self.audio_standby {
+            config.audio_standby = standby;
+        }
+        if let Some(model) = self.whisper_model {
+            config.whisper_model = model;
+        }
+        if let Some(language) = self.whisper_language {
+            config.whisper_language = language;
+        }
+        if let Some(timeout) = self.whisper_timeout_seconds {
+            config.whisper_timeout_seconds = timeout;
+        }
+        if let Some(retries) = self.whisper_max_retries {
+            config.whisper_max_retries = retries;
+        }
+        if let Some(mirror) = self.whisper_model_mirror {
+            config.whisper_model_mirror = mirror;
+        }
+        if let Some(languages) = self.whisper_allowed_languages {
+            config.whisper_allowed_languages = languages;
+        }
+        if let Some(translate) = self.translate {
+            config.translate = translate;
+        }
+        if let Some(threads) = self.local_whisper_threads {
+            config.local_whisper_threads = Some(threads);
+        }
+        if let Some(beam_size) = self.local_whisper_beam_size {
+            config.local_whisper_beam_size = beam_size;
+        }
+        if let Some(increment) = self.local_whisper_temperature_increment {
+            config.local_whisper_temperature_increment = increment;
+        }
+        if let Some(prompt) = self.local_whisper_prompt.filter(|p| !p.trim().is_empty()) {
+            config.local_whisper_prompt = Some(prompt);
+        }
+        if let Some(no_context) = self.local_whisper_no_context {
+            config.local_whisper_no_context = no_context;
+        }
+        if let Some(log_level) = self.rust_log {
+            config.rust_log = log_level;
+        }
+        if let Some(enabled) = self.enable_audio_feedback {
+            config.enable_audio_feedback = enabled;
+        }
+        if let Some(volume) = self.beep_volume {
+            config.beep_volume = volume;
+        }
+        if let Some(enabled) = self.history_enabled {
+            config.history_enabled = enabled;
+        }
+        if let Some(save_audio) = self.history_save_audio {
+            config.history_save_audio = save_audio;
+        }
+        if let Some(max_entries) = self.history_max_entries {
+            config.history_max_entries = max_entries;
+        }
+        if let Some(max_age) = self.history_max_age_days {
+            config.history_max_age_days = max_age;
+        }
+        if let Some(credentials) = self.google_application_credentials {
+            config.google_application_credentials = Some(credentials);
+        }
+        if let Some(language) = self.google_speech_language_code {
+            config.google_speech_language_code = language;
+        }
+        if let Some(model) = self.google_speech_model {
+            config.google_speech_model = model;
+        }
+        if let Some(languages) = self.google_speech_alternative_languages {
+            config.google_speech_alternative_languages = languages;
+        }
+        config
+    }
+}
+
+/// Default location of the profiles file, next to the `.env` file
+pub fn default_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| std::env::var("HOME").map_or_else(|_| PathBuf::from("."), PathBuf::from))
+        .join("waystt")
+        .join("config.toml")
+}
+
+/// Read the profile called `name` from a `config.toml` file
+pub fn load(path: &Path, name: &str) -> Result<Profile> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let mut file: ConfigFile =
+        toml::from_str(&contents).map_err(|e| anyhow!("Invalid {}: {}", path.display(), e))?;
+
+    let table = file.profile.remove(name).ok_or_else(|| {
+        let available: Vec<&str> = file.profile.keys().map(String::as_str).collect();
+        if available.is_empty() {
+            anyhow!(
+                "Profile '{}' not found: {} has no profiles",
+                name,
+                path.display()
+            )
+        } else {
+            anyhow!(
+                "Profile '{}' not found in {}, available: {}",
+                name,
+                path.display(),
+                available.join(", ")
+            )
+        }
+    })?;
+
+    let keys = table.keys().cloned().collect();
+    let settings = toml::Value::Table(table)
+        .try_into()
+        .map_err(|e| anyhow!("Invalid profile '{}' in {}: {}", name, path.display(), e))?;
+    Ok(Profile {
+        name: name.to_string(),
+        settings,
+        keys,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn config_file(contents: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file
+    }
+
+    const PROFILES: &str = r#"
+[profile.code]
+transcription_provider = "local"
+whisper_model = "ggml-base.en.bin"
+whisper_language = "en"
+
+[profile.meeting]
+transcription_provider = "google"
+google_speech_language_code = "en-US"
+google_speech_alternative_languages = ["de-DE", "fr-FR"]
+beep_volume = 2.0
+"#;
+
+    #[test]
+    fn test_load_profile() {
+        let file = config_file(PROFILES);
+        let code = load(file.path(), "code").unwrap();
+        assert_eq!(code.name, "code");
+        assert_eq!(
+            code.settings.transcription_provider.as_deref(),
+            Some("local")
+        );
+        assert_eq!(
+            code.settings.whisper_model.as_deref(),
+            Some("ggml-base.en.bin")
+        );
+        assert_eq!(code.settings.google_speech_model, None);
+
+        assert!(code.sets("WHISPER_MODEL"));
+        assert!(!code.sets("GOOGLE_SPEECH_MODEL"));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_apply_only_overrides_set_keys() {
+        let file = config_file(PROFILES);
+        let config = load(file.path(), "meeting")
+            .unwrap()
+            .apply(Config::default());
+
+        assert_eq!(config.transcription_provider, "google");
+        assert_eq!(
+            config.google_speech_alternative_languages,
+            vec!["de-DE", "fr-FR"]
+        );
+        // Out of range values are left for validation to report
+        assert_eq!(config.beep_volume, 2.0);
+        assert!(config
+            .validate()
+            .unwrap_err()
+            .to_string()
+            .contains("BEEP_VOLUME"));
+        // Untouched settings keep their defaults
+        assert_eq!(config.whisper_model, "whisper-1");
+        assert_eq!(config.audio_sample_rate, 16000);
+    }
+
+    #[test]
+    fn test_unknown_profile_lists_available() {
+        let file = config_file(PROFILES);
+        let error = load(file.path(), "podcast").unwrap_err().to_string();
+        assert!(error.contains("podcast"));
+        assert!(error.contains("code, meeting"));
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let file = config_file("[profile.code]\nwhisper_modle = \"ggml-base.en.bin\"\n");
+        let error = load(file.path(), "code").unwrap_err().to_string();
+        assert!(error.contains("whisper_modle"));
+
+        let file = config_file("[profiles.code]\n");
+        assert!(load(file.path(), "code").is_err());
+    }
+
+    #[test]
+    fn test_wrong_type_is_rejected() {
+        let file = config_file("[profile.code]\naudio_sample_rate = \"fast\"\n");
+        assert!(load(file.path(), "code").is_err());
+    }
+
+    #[test]
+    fn test_missing_file() {
+        assert!(load(Path::new("/nonexistent/waystt/config.toml"), "code").is_err());
+    }
+}
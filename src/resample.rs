@@ -0,0 +1,204 @@
+#![allow(clippy::cast_precision_loss)]
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_sign_loss)]
+
+/// Convert interleaved audio between channel layouts
+/// Downmixing to mono averages all input channels; any other layout maps
+/// output channel `n` to input channel `n % from`, so mono is duplicated
+/// and surplus input channels are dropped.
+pub fn convert_channels(input: &[f32], from: u16, to: u16, output: &mut Vec<f32>) {
+    let from = usize::from(from.max(1));
+    let to = usize::from(to.max(1));
+
+    if from == to {
+        output.extend_from_slice(input);
+        return;
+    }
+
+    for frame in input.chunks_exact(from) {
+        if to == 1 {
+            let sum: f32 = frame.iter().sum();
+            output.push(sum / from as f32);
+        } else {
+            for channel in 0..to {
+                output.push(frame[channel % from]);
+            }
+        }
+    }
+}
+
+/// Streaming sample rate converter for interleaved f32 audio
+/// Uses linear interpolation between neighbouring frames and keeps the last
+/// frame of every chunk so consecutive callbacks join without clicks.
+pub struct Resampler {
+    from_rate: u32,
+    to_rate: u32,
+    channels: usize,
+    step: f64,
+    position: f64,
+    previous: Vec<f32>,
+}
+
+impl Resampler {
+    /// Create a resampler converting `from_rate` to `to_rate` for `channels` channels
+    pub fn new(from_rate: u32, to_rate: u32, channels: u16) -> Self {
+        let channels = usize::from(channels.max(1));
+        Self {
+            from_rate,
+            to_rate,
+            channels,
+            step: f64::from(from_rate) / f64::from(to_rate.max(1)),
+            position: 0.0,
+            previous: vec![0.0; channels],
+        }
+    }
+
+    /// Whether input and output rates are identical
+    pub fn is_passthrough(&self) -> bool {
+        self.from_rate == self.to_rate
+    }
+
+    /// Resample one chunk of interleaved input, appending the result to `output`
+    pub fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        if self.is_passthrough() {
+            output.extend_from_slice(input);
+            return;
+        }
+
+        let channels = self.channels;
+        let frames = input.len() / channels;
+        if frames == 0 {
+            return;
+        }
+
+        // `position` is relative to the first frame of this chunk; -1.0 refers
+        // to the last frame of the previous chunk, kept in `previous`
+        let last = (frames - 1) as f64;
+        while self.position < last {
+            let index = self.position.floor();
+            let fraction = (self.position - index) as f32;
+            let index = index as isize;
+
+            for channel in 0..channels {
+                let a = if index < 0 {
+                    self.previous[channel]
+                } else {
+                    input[index as usize * channels + channel]
+                };
+                let b = input[(index + 1) as usize * channels + channel];
+                output.push(a + (b - a) * fraction);
+            }
+
+            self.position += self.step;
+        }
+
+        self.position -= frames as f64;
+        self.previous
+            .copy_from_slice(&input[(frames - 1) * channels..frames * channels]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_channels_passthrough() {
+        let mut output = Vec::new();
+        convert_channels(&[0.1, 0.2, 0.3], 1, 1, &mut output);
+        assert_eq!(output, vec![0.1, 0.2, 0.3]);
+    }
+
+    #[test]
+    fn test_convert_channels_stereo_to_mono() {
+        let mut output = Vec::new();
+        convert_channels(&[0.2, 0.4, -0.5, 0.5], 2, 1, &mut output);
+        assert_eq!(output.len(), 2);
+        assert!((output[0] - 0.3).abs() < 1e-6);
+        assert!(output[1].abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_convert_channels_mono_to_stereo() {
+        let mut output = Vec::new();
+        convert_channels(&[0.1, 0.2], 1, 2, &mut output);
+        assert_eq!(output, vec![0.1, 0.1, 0.2, 0.2]);
+    }
+
+    #[test]
+    fn test_convert_channels_drops_partial_frames() {
+        let mut output = Vec::new();
+        convert_channels(&[0.1, 0.2, 0.3], 2, 1, &mut output);
+        assert_eq!(output.len(), 1);
+    }
+
+    #[test]
+    fn test_resampler_passthrough() {
+        let mut resampler = Resampler::new(16000, 16000, 1);
+        assert!(resampler.is_passthrough());
+
+        let mut output = Vec::new();
+        resampler.process(&[0.1, 0.2, 0.3], &mut output);
+        assert_eq!(output, vec![0.1, 0.2, 0.3]);
+    }
+
+    #[test]
+    fn test_resampler_downsample_length() {
+        let mut resampler = Resampler::new(48000, 16000, 1);
+        let input = vec![0.5; 48000];
+
+        let mut output = Vec::new();
+        resampler.process(&input, &mut output);
+
+        // One second in, roughly one second out
+        assert!((output.len() as i64 - 16000).abs() <= 1);
+        assert!(output.iter().all(|&s| (s - 0.5).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_resampler_upsample_interpolates() {
+        let mut resampler = Resampler::new(8000, 16000, 1);
+        let mut output = Vec::new();
+        resampler.process(&[0.0, 1.0, 0.0], &mut output);
+
+        assert_eq!(output.len(), 4);
+        assert!((output[0] - 0.0).abs() < 1e-6);
+        assert!((output[1] - 0.5).abs() < 1e-6);
+        assert!((output[2] - 1.0).abs() < 1e-6);
+        assert!((output[3] - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_resampler_streaming_matches_single_chunk() {
+        let input: Vec<f32> = (0..4410).map(|i| (i as f32 * 0.01).sin()).collect();
+
+        let mut whole = Vec::new();
+        Resampler::new(44100, 16000, 1).process(&input, &mut whole);
+
+        let mut chunked = Vec::new();
+        let mut resampler = Resampler::new(44100, 16000, 1);
+        for chunk in input.chunks(441) {
+            resampler.process(chunk, &mut chunked);
+        }
+
+        assert_eq!(whole.len(), chunked.len());
+        for (a, b) in whole.iter().zip(chunked.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_resampler_keeps_channels_interleaved() {
+        let mut resampler = Resampler::new(32000, 16000, 2);
+        let input = [0.1, -0.1, 0.1, -0.1, 0.1, -0.1, 0.1, -0.1];
+
+        let mut output = Vec::new();
+        resampler.process(&input, &mut output);
+
+        assert_eq!(output.len() % 2, 0);
+        for frame in output.chunks(2) {
+            assert!((frame[0] - 0.1).abs() < 1e-6);
+            assert!((frame[1] + 0.1).abs() < 1e-6);
+        }
+    }
+}
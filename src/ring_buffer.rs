@@ -0,0 +1,238 @@
+#![allow(clippy::cast_possible_truncation)]
+
+use std::sync::atomic::{fence, AtomicU32, AtomicU64, Ordering};
+
+/// Fixed-capacity single-producer ring buffer for captured audio
+/// The realtime capture callback is the only writer and never blocks or
+/// allocates; once full, the oldest samples are overwritten. Readers take
+/// copies (`snapshot`, `read_range`) and advance with `consume` without locking.
+/// Samples are stored as f32 bit patterns in atomics, and positions are
+/// absolute sample counts since creation so readers can detect overwrites.
+pub struct AudioRingBuffer {
+    slots: Box<[AtomicU32]>,
+    /// End of the chunk the producer is currently writing
+    reserved: AtomicU64,
+    /// End of the last fully written chunk
+    written: AtomicU64,
+    consumed: AtomicU64,
+}
+
+impl AudioRingBuffer {
+    /// Create a ring buffer holding at most `capacity` samples
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            slots: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+            reserved: AtomicU64::new(0),
+            written: AtomicU64::new(0),
+            consumed: AtomicU64::new(0),
+        }
+    }
+
+    /// Maximum number of samples retained
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Total number of samples written since creation
+    pub fn written(&self) -> u64 {
+        self.written.load(Ordering::Acquire)
+    }
+
+    /// Position of the first unconsumed sample
+    pub fn consumed(&self) -> u64 {
+        self.consumed.load(Ordering::Acquire)
+    }
+
+    /// Number of unconsumed samples currently retained
+    pub fn available(&self) -> usize {
+        let written = self.written();
+        (written - self.oldest_readable(written)) as usize
+    }
+
+    /// Append samples, overwriting the oldest ones when full
+    /// Must only be called from a single producer thread.
+    pub fn push(&self, samples: &[f32]) {
+        let capacity = self.capacity() as u64;
+        let start = self.written.load(Ordering::Relaxed);
+        let end = start + samples.len() as u64;
+
+        // Announce the slots about to be overwritten before touching them
+        self.reserved.store(end, Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        // Only the newest `capacity` samples of an oversized chunk can survive
+        let skip = samples.len().saturating_sub(self.capacity());
+        for (offset, sample) in samples[skip..].iter().enumerate() {
+            let position = start + (skip + offset) as u64;
+            self.slots[(position % capacity) as usize].store(sample.to_bits(), Ordering::Relaxed);
+        }
+
+        self.written.store(end, Ordering::Release);
+    }
+
+    /// Copy the unconsumed samples without advancing the read position
+    /// The recorder reads by position instead so it can mute ranges.
+    #[cfg(test)]
+    pub fn snapshot(&self) -> Vec<f32> {
+        let written = self.written();
+        self.read_range(self.oldest_readable(written), written)
+    }
+
+    /// Mark every sample before absolute position `until` as consumed
+    pub fn consume(&self, until: u64) {
+        self.consumed
+            .fetch_max(until.min(self.written()), Ordering::AcqRel);
+    }
+
+    /// Discard all unconsumed samples
+    pub fn clear(&self) {
+        self.consume(self.written());
+    }
+
+    /// Copy samples in the absolute range `[start, end)`
+    /// Samples that were already overwritten are dropped from the front.
+    pub fn read_range(&self, start: u64, end: u64) -> Vec<f32> {
+        let capacity = self.capacity() as u64;
+        let start = start.max(end.saturating_sub(capacity));
+        if start >= end {
+            return Vec::new();
+        }
+
+        let samples: Vec<f32> = (start..end)
+            .map(|position| {
+                f32::from_bits(self.slots[(position % capacity) as usize].load(Ordering::Relaxed))
+            })
+            .collect();
+
+        // The producer may have lapped us while copying; drop anything it overwrote
+        fence(Ordering::Acquire);
+        let overwritten_until = self
+            .reserved
+            .load(Ordering::Relaxed)
+            .saturating_sub(capacity);
+        if overwritten_until > start {
+            let stale = ((overwritten_until - start) as usize).min(samples.len());
+            return samples[stale..].to_vec();
+        }
+
+        samples
+    }
+
+    fn oldest_readable(&self, written: u64) -> u64 {
+        self.consumed()
+            .max(written.saturating_sub(self.capacity() as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_ring_buffer_push_and_snapshot() {
+        let buffer = AudioRingBuffer::new(8);
+        assert_eq!(buffer.available(), 0);
+
+        buffer.push(&[0.1, 0.2, 0.3]);
+        assert_eq!(buffer.available(), 3);
+        assert_eq!(buffer.written(), 3);
+        assert_eq!(buffer.snapshot(), vec![0.1, 0.2, 0.3]);
+
+        // Snapshots do not consume
+        assert_eq!(buffer.snapshot(), vec![0.1, 0.2, 0.3]);
+    }
+
+    #[test]
+    fn test_ring_buffer_overwrites_oldest() {
+        let buffer = AudioRingBuffer::new(4);
+        buffer.push(&[1.0, 2.0, 3.0]);
+        buffer.push(&[4.0, 5.0, 6.0]);
+
+        assert_eq!(buffer.available(), 4);
+        assert_eq!(buffer.snapshot(), vec![3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn test_ring_buffer_oversized_chunk() {
+        let buffer = AudioRingBuffer::new(3);
+        buffer.push(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        assert_eq!(buffer.written(), 5);
+        assert_eq!(buffer.snapshot(), vec![3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn test_ring_buffer_consume() {
+        let buffer = AudioRingBuffer::new(8);
+        buffer.push(&[1.0, 2.0, 3.0]);
+
+        buffer.consume(2);
+        assert_eq!(buffer.snapshot(), vec![3.0]);
+
+        // Consuming never moves backwards or past the written samples
+        buffer.consume(1);
+        buffer.consume(100);
+        assert_eq!(buffer.available(), 0);
+
+        buffer.push(&[4.0]);
+        assert_eq!(buffer.snapshot(), vec![4.0]);
+    }
+
+    #[test]
+    fn test_ring_buffer_clear() {
+        let buffer = AudioRingBuffer::new(8);
+        buffer.push(&[1.0, 2.0, 3.0]);
+        buffer.clear();
+
+        assert_eq!(buffer.available(), 0);
+        assert!(buffer.snapshot().is_empty());
+
+        buffer.push(&[4.0]);
+        assert_eq!(buffer.snapshot(), vec![4.0]);
+    }
+
+    #[test]
+    fn test_ring_buffer_read_range() {
+        let buffer = AudioRingBuffer::new(4);
+        buffer.push(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        assert_eq!(buffer.read_range(3, 5), vec![4.0, 5.0]);
+        // Positions 0 and 1 were overwritten
+        assert_eq!(buffer.read_range(0, 4), vec![3.0, 4.0]);
+        assert!(buffer.read_range(5, 5).is_empty());
+    }
+
+    #[test]
+    fn test_ring_buffer_concurrent_producer() {
+        let buffer = Arc::new(AudioRingBuffer::new(1024));
+        let producer = {
+            let buffer = Arc::clone(&buffer);
+            std::thread::spawn(move || {
+                let mut next = 0u32;
+                for _ in 0..2000 {
+                    let chunk: Vec<f32> = (0..64)
+                        .map(|_| {
+                            next += 1;
+                            next as f32
+                        })
+                        .collect();
+                    buffer.push(&chunk);
+                }
+            })
+        };
+
+        // Every snapshot must be a contiguous run of the producer's sequence
+        for _ in 0..200 {
+            let snapshot = buffer.snapshot();
+            for pair in snapshot.windows(2) {
+                assert_eq!(pair[1] - pair[0], 1.0);
+            }
+        }
+
+        producer.join().unwrap();
+        assert_eq!(buffer.available(), 1024);
+        assert_eq!(buffer.written(), 2000 * 64);
+    }
+}
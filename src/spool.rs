@@ -0,0 +1,260 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::history::{create_private_dir, private_file};
+
+/// Details saved next to a recording whose transcription failed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedRecording {
+    /// Unix time of the failure in seconds
+    pub failed_at: u64,
+    pub provider: String,
+    /// Language requested for the transcription ("auto" to detect)
+    pub language: String,
+    /// Length of the recording in seconds
+    pub duration: f64,
+    pub error: String,
+}
+
+/// A recording waiting in the spool directory
+#[derive(Debug, Clone)]
+pub struct SpooledRecording {
+    pub id: String,
+    pub wav_path: PathBuf,
+    pub details: FailedRecording,
+}
+
+/// Directory of recordings that could not be transcribed, stored as
+/// `<id>.wav` with a `<id>.json` sidecar holding `FailedRecording`
+/// Only the owner can read the files.
+pub struct Spool {
+    dir: PathBuf,
+}
+
+impl Spool {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The spool under `$XDG_STATE_HOME/waystt/failed` (usually `~/.local/state`)
+    pub fn open_default() -> Self {
+        let state_dir = dirs::state_dir().unwrap_or_else(|| {
+            dirs::home_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(".local/state")
+        });
+        Self::new(state_dir.join("waystt").join("failed"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Store a WAV recording and its details, returning the new entry
+    /// IDs are the failure time in milliseconds, so they sort chronologically.
+    pub fn save(&self, wav_data: &[u8], details: FailedRecording) -> Result<SpooledRecording> {
+        create_private_dir(&self.dir)?;
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let mut id = millis.to_string();
+        let mut suffix = 1;
+        while self.wav_path(&id).exists() {
+            suffix += 1;
+            id = format!("{}-{}", millis, suffix);
+        }
+
+        let wav_path = self.wav_path(&id);
+        private_file(&wav_path, false)?
+            .write_all(wav_data)
+            .map_err(|e| anyhow!("Failed to write {}: {}", wav_path.display(), e))?;
+        private_file(&self.sidecar_path(&id), false)?
+            .write_all(&serde_json::to_vec_pretty(&details)?)
+            .map_err(|e| anyhow!("Failed to write details for {}: {}", id, e))?;
+
+        Ok(SpooledRecording {
+            id,
+            wav_path,
+            details,
+        })
+    }
+
+    /// Every spooled recording, oldest first
+    /// Recordings whose sidecar is missing or unreadable are skipped.
+    pub fn list(&self) -> Result<Vec<SpooledRecording>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(anyhow!("Failed to read {}: {}", self.dir.display(), e)),
+        };
+
+        let mut recordings: Vec<SpooledRecording> = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "wav" {
+                    return None;
+                }
+                self.get(path.file_stem()?.to_str()?).ok()
+            })
+            .collect();
+        recordings.sort_by(|a, b| (a.details.failed_at, &a.id).cmp(&(b.details.failed_at, &b.id)));
+        Ok(recordings)
+    }
+
+    /// Look up a spooled recording by ID
+    pub fn get(&self, id: &str) -> Result<SpooledRecording> {
+        // IDs are file stems; refuse anything that would leave the spool directory
+        if id.is_empty() || id.contains(['/', '\\']) || id.starts_with('.') {
+            return Err(anyhow!("Invalid recording ID: {}", id));
+        }
+
+        let wav_path = self.wav_path(id);
+        if !wav_path.exists() {
+            return Err(anyhow!("No saved recording with ID {}", id));
+        }
+        let sidecar = std::fs::read(self.sidecar_path(id))
+            .map_err(|e| anyhow!("Failed to read details for {}: {}", id, e))?;
+
+        Ok(SpooledRecording {
+            id: id.to_string(),
+            wav_path,
+            details: serde_json::from_slice(&sidecar)?,
+        })
+    }
+
+    /// Delete a recording and its details, e.g. after a successful retry
+    pub fn remove(&self, recording: &SpooledRecording) -> Result<()> {
+        std::fs::remove_file(&recording.wav_path)?;
+        let _ = std::fs::remove_file(self.sidecar_path(&recording.id));
+        Ok(())
+    }
+
+    fn wav_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.wav", id))
+    }
+
+    fn sidecar_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+/// Seconds since the Unix epoch, for `FailedRecording::failed_at`
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Describe how long ago a Unix time was, e.g. "5m ago"
+pub fn describe_age(then: u64, now: u64) -> String {
+    let seconds = now.saturating_sub(then);
+    match seconds {
+        0..=59 => format!("{}s ago", seconds),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        3600..=86_399 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86_400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(failed_at: u64) -> FailedRecording {
+        FailedRecording {
+            failed_at,
+            provider: "openai".to_string(),
+            language: "de".to_string(),
+            duration: 2.5,
+            error: "Network error with OpenAI: Request timeout".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_save_list_and_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = Spool::new(dir.path().join("failed"));
+        assert!(spool.list().unwrap().is_empty());
+
+        let newer = spool.save(b"RIFF newer", details(200)).unwrap();
+        let older = spool.save(b"RIFF older", details(100)).unwrap();
+        assert_ne!(newer.id, older.id);
+        assert_eq!(std::fs::read(&older.wav_path).unwrap(), b"RIFF older");
+
+        let listed = spool.list().unwrap();
+        let ids: Vec<&str> = listed.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec![older.id.as_str(), newer.id.as_str()]);
+        assert_eq!(listed[0].details, details(100));
+
+        let found = spool.get(&newer.id).unwrap();
+        assert_eq!(found.wav_path, newer.wav_path);
+
+        spool.remove(&found).unwrap();
+        assert!(spool.get(&newer.id).is_err());
+        assert_eq!(spool.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_saved_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let spool = Spool::new(dir.path().join("failed"));
+        let saved = spool.save(b"RIFF", details(100)).unwrap();
+
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(spool.dir()), 0o700);
+        assert_eq!(mode(&saved.wav_path), 0o600);
+        assert_eq!(mode(&spool.sidecar_path(&saved.id)), 0o600);
+    }
+
+    #[test]
+    fn test_sidecar_is_readable_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = Spool::new(dir.path());
+        let saved = spool.save(b"RIFF", details(100)).unwrap();
+
+        let sidecar = std::fs::read_to_string(dir.path().join(format!("{}.json", saved.id)));
+        let json: serde_json::Value = serde_json::from_str(&sidecar.unwrap()).unwrap();
+        assert_eq!(json["provider"], "openai");
+        assert_eq!(json["language"], "de");
+        assert_eq!(json["duration"], 2.5);
+    }
+
+    #[test]
+    fn test_get_rejects_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = Spool::new(dir.path());
+        assert!(spool.get("../secret").is_err());
+        assert!(spool.get("").is_err());
+        assert!(spool.get("12345").is_err());
+    }
+
+    #[tokio::test]
+    #[allow(clippy::await_holding_lock)]
+    async fn test_default_spool_uses_state_dir() {
+        use crate::test_utils::{EnvGuard, ENV_MUTEX};
+        let _lock = ENV_MUTEX.lock().await;
+
+        let _state_home = EnvGuard::set("XDG_STATE_HOME", "/tmp/state");
+        assert_eq!(
+            Spool::open_default().dir(),
+            Path::new("/tmp/state/waystt/failed")
+        );
+    }
+
+    #[test]
+    fn test_describe_age() {
+        assert_eq!(describe_age(100, 130), "30s ago");
+        assert_eq!(describe_age(0, 600), "10m ago");
+        assert_eq!(describe_age(0, 7200), "2h ago");
+        assert_eq!(describe_age(0, 3 * 86_400), "3d ago");
+        assert_eq!(describe_age(200, 100), "0s ago");
+    }
+}
@@ -1,8 +1,173 @@
 //! Shared test utilities to prevent race conditions between test modules
 
+use crate::transcription::{Transcript, TranscriptionError, TranscriptionProvider};
 use tokio::sync::Mutex as AsyncMutex;
 
 // Global unified mutex for all environment variable tests to prevent race conditions
 // This single async mutex ensures that both sync and async tests using environment variables
 // cannot run simultaneously, preventing interference between tests.
 pub static ENV_MUTEX: AsyncMutex<()> = AsyncMutex::const_new(());
+
+/// Sets an environment variable until dropped, then restores its previous value
+/// Restoring on drop also covers failed assertions. Hold `ENV_MUTEX` while the
+/// guard is alive.
+pub struct EnvGuard {
+    key: &'static str,
+    original: Option<std::ffi::OsString>,
+}
+
+impl EnvGuard {
+    pub fn set(key: &'static str, value: impl AsRef<std::ffi::OsStr>) -> Self {
+        let original = std::env::var_os(key);
+        std::env::set_var(key, value);
+        Self { key, original }
+    }
+
+    pub fn remove(key: &'static str) -> Self {
+        let original = std::env::var_os(key);
+        std::env::remove_var(key);
+        Self { key, original }
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        match &self.original {
+            Some(value) => std::env::set_var(self.key, value),
+            None => std::env::remove_var(self.key),
+        }
+    }
+}
+
+/// Provider stub that records the audio and language it was given and returns fixed text
+#[derive(Default)]
+pub struct RecordingProvider {
+    pub received: std::sync::Mutex<Vec<(Vec<u8>, Option<String>)>>,
+}
+
+#[async_trait::async_trait]
+impl TranscriptionProvider for RecordingProvider {
+    async fn transcribe_with_language(
+        &self,
+        audio_data: Vec<u8>,
+        language: Option<String>,
+    ) -> Result<Transcript, TranscriptionError> {
+        self.received.lock().unwrap().push((audio_data, language));
+        Ok(Transcript {
+            text: "hello world".to_string(),
+            ..Transcript::default()
+        })
+    }
+}
+
+/// Write a whisper.cpp model with tiny dimensions and all-zero weights
+/// It loads and runs like a real ggml model, fast enough for unit tests,
+/// but its transcripts are meaningless. `multilingual` selects the
+/// vocabulary size of the multilingual models, which can detect languages.
+pub fn write_tiny_whisper_model(path: &std::path::Path, multilingual: bool) {
+    const N_STATE: i32 = 8;
+    const N_AUDIO_CTX: i32 = 1500;
+    const N_TEXT_CTX: i32 = 448;
+    const N_MELS: i32 = 80;
+    let n_vocab: i32 = if multilingual { 51865 } else { 51864 };
+
+    let mut data: Vec<u8> = Vec::new();
+    let put = |values: &[i32], data: &mut Vec<u8>| {
+        for value in values {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+    };
+
+    // Magic "ggml" and hyperparameters: one encoder and decoder layer, one head, f16
+    put(&[0x6767_6d6c], &mut data);
+    put(
+        &[
+            n_vocab,
+            N_AUDIO_CTX,
+            N_STATE,
+            1,
+            1,
+            N_TEXT_CTX,
+            N_STATE,
+            1,
+            1,
+            N_MELS,
+            1,
+        ],
+        &mut data,
+    );
+
+    // Mel filters
+    put(&[N_MELS, 201], &mut data);
+    data.resize(data.len() + (N_MELS * 201 * 4) as usize, 0);
+
+    // Only the tokens decoding looks up by text; whisper.cpp names the others
+    let vocab = [" ", " -", " '", " hello", " world"];
+    put(&[vocab.len() as i32], &mut data);
+    for token in vocab {
+        put(&[token.len() as i32], &mut data);
+        data.extend_from_slice(token.as_bytes());
+    }
+
+    let s = N_STATE;
+    let mut tensors: Vec<(String, Vec<i32>)> = vec![
+        ("encoder.positional_embedding".into(), vec![s, N_AUDIO_CTX]),
+        ("encoder.conv1.weight".into(), vec![3, N_MELS, s]),
+        ("encoder.conv1.bias".into(), vec![1, s]),
+        ("encoder.conv2.weight".into(), vec![3, s, s]),
+        ("encoder.conv2.bias".into(), vec![1, s]),
+        ("encoder.ln_post.weight".into(), vec![s]),
+        ("encoder.ln_post.bias".into(), vec![s]),
+        ("decoder.positional_embedding".into(), vec![s, N_TEXT_CTX]),
+        ("decoder.token_embedding.weight".into(), vec![s, n_vocab]),
+        ("decoder.ln.weight".into(), vec![s]),
+        ("decoder.ln.bias".into(), vec![s]),
+    ];
+    for block in ["encoder.blocks.0", "decoder.blocks.0"] {
+        for (name, shape) in [
+            ("mlp_ln.weight", vec![s]),
+            ("mlp_ln.bias", vec![s]),
+            ("mlp.0.weight", vec![s, 4 * s]),
+            ("mlp.0.bias", vec![4 * s]),
+            ("mlp.2.weight", vec![4 * s, s]),
+            ("mlp.2.bias", vec![s]),
+        ] {
+            tensors.push((format!("{}.{}", block, name), shape));
+        }
+    }
+    for attention in [
+        "encoder.blocks.0.attn",
+        "decoder.blocks.0.attn",
+        "decoder.blocks.0.cross_attn",
+    ] {
+        for (name, shape) in [
+            ("_ln.weight", vec![s]),
+            ("_ln.bias", vec![s]),
+            (".query.weight", vec![s, s]),
+            (".query.bias", vec![s]),
+            (".key.weight", vec![s, s]),
+            (".value.weight", vec![s, s]),
+            (".value.bias", vec![s]),
+            (".out.weight", vec![s, s]),
+            (".out.bias", vec![s]),
+        ] {
+            tensors.push((format!("{}{}", attention, name), shape));
+        }
+    }
+
+    // Matrix and convolution weights are f16, biases, norms and positions f32
+    for (name, shape) in tensors {
+        let f16 = name.ends_with(".weight") && shape.len() > 1;
+        put(
+            &[shape.len() as i32, name.len() as i32, i32::from(f16)],
+            &mut data,
+        );
+        put(&shape, &mut data);
+        data.extend_from_slice(name.as_bytes());
+        let elements: i32 = shape.iter().product();
+        let size = if f16 { 2 } else { 4 };
+        data.resize(data.len() + elements as usize * size, 0);
+    }
+
+    std::fs::write(path, data).unwrap();
+}
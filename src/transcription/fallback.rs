@@ -0,0 +1,226 @@
+use super::{Transcript, TranscriptionError, TranscriptionProvider};
+use async_trait::async_trait;
+
+/// Tries each provider in order until one succeeds
+/// Only failures another provider could avoid (network errors, HTTP 5xx and
+/// 429) move on to the next provider; anything else, such as rejected
+/// credentials, is returned immediately. The transcript records which
+/// provider produced it.
+pub struct FallbackProvider {
+    providers: Vec<(String, Box<dyn TranscriptionProvider>)>,
+}
+
+impl FallbackProvider {
+    /// Create a chain from named providers, tried in the given order
+    pub fn new(providers: Vec<(String, Box<dyn TranscriptionProvider>)>) -> Self {
+        assert!(!providers.is_empty(), "a fallback chain needs a provider");
+        Self { providers }
+    }
+}
+
+/// Whether a failed transcription is worth retrying with the next provider
+pub fn should_fall_back(error: &TranscriptionError) -> bool {
+    match error {
+        TranscriptionError::NetworkError(_) => true,
+        TranscriptionError::ApiError(details) => {
+            matches!(details.status_code, Some(429 | 500..=599))
+        }
+        _ => false,
+    }
+}
+
+#[async_trait]
+impl TranscriptionProvider for FallbackProvider {
+    async fn transcribe_with_language(
+        &self,
+        audio_data: Vec<u8>,
+        language: Option<String>,
+    ) -> Result<Transcript, TranscriptionError> {
+        let mut error = None;
+        for (name, provider) in &self.providers {
+            if let Some(error) = &error {
+                eprintln!("⚠️ Falling back to {} after: {}", name, error);
+            }
+
+            match provider
+                .transcribe_with_language(audio_data.clone(), language.clone())
+                .await
+            {
+                Ok(transcript) => {
+                    if error.is_some() {
+                        eprintln!("✅ Transcribed with fallback provider {}", name);
+                    }
+                    return Ok(Transcript {
+                        provider: Some(name.clone()),
+                        ..transcript
+                    });
+                }
+                Err(e) if should_fall_back(&e) => error = Some(e),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(error.expect("the last provider was tried"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcription::openai::OpenAIProvider;
+    use crate::transcription::{ApiErrorDetails, NetworkErrorDetails};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Provider that counts its calls and returns fixed text or a network error
+    struct StubProvider {
+        text: Option<&'static str>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl TranscriptionProvider for StubProvider {
+        async fn transcribe_with_language(
+            &self,
+            _audio_data: Vec<u8>,
+            _language: Option<String>,
+        ) -> Result<Transcript, TranscriptionError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match self.text {
+                Some(text) => Ok(Transcript {
+                    text: text.to_string(),
+                    ..Transcript::default()
+                }),
+                None => Err(TranscriptionError::NetworkError(NetworkErrorDetails {
+                    provider: "Stub".to_string(),
+                    error_type: "Connection failed".to_string(),
+                    error_message: "unreachable".to_string(),
+                })),
+            }
+        }
+    }
+
+    fn stub(text: Option<&'static str>) -> (Box<dyn TranscriptionProvider>, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = StubProvider {
+            text,
+            calls: Arc::clone(&calls),
+        };
+        (Box::new(provider), calls)
+    }
+
+    fn openai(server: &mockito::Server) -> Box<dyn TranscriptionProvider> {
+        Box::new(
+            OpenAIProvider::new_with_options(
+                "test-key".to_string(),
+                Some(5),
+                Some(0),
+                Some("gpt-4o-transcribe".to_string()),
+                Some(server.url()),
+            )
+            .unwrap(),
+        )
+    }
+
+    async fn openai_then_stub(status: usize) -> (Result<Transcript, TranscriptionError>, usize) {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/audio/transcriptions")
+            .with_status(status)
+            .with_body(r#"{"error":{"message":"try later","code":"busy"}}"#)
+            .create_async()
+            .await;
+
+        let (local, calls) = stub(Some("from local"));
+        let chain = FallbackProvider::new(vec![
+            ("openai".to_string(), openai(&server)),
+            ("local".to_string(), local),
+        ]);
+
+        let result = chain.transcribe_with_language(vec![1, 2, 3], None).await;
+        mock.assert_async().await;
+        (result, calls.load(Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_on_server_error() {
+        let (result, calls) = openai_then_stub(503).await;
+        let transcript = result.unwrap();
+        assert_eq!(transcript.text, "from local");
+        assert_eq!(transcript.provider.as_deref(), Some("local"));
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_on_rate_limit() {
+        let (result, calls) = openai_then_stub(429).await;
+        assert_eq!(result.unwrap().provider.as_deref(), Some("local"));
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_do_not_fall_back() {
+        let (result, calls) = openai_then_stub(400).await;
+        match result {
+            Err(TranscriptionError::ApiError(details)) => {
+                assert_eq!(details.status_code, Some(400));
+            }
+            other => panic!("expected the OpenAI error, got {:?}", other),
+        }
+        assert_eq!(calls, 0);
+    }
+
+    #[tokio::test]
+    async fn test_first_success_is_reported_and_stops_the_chain() {
+        let (first, first_calls) = stub(Some("from first"));
+        let (second, second_calls) = stub(Some("from second"));
+        let chain = FallbackProvider::new(vec![
+            ("first".to_string(), first),
+            ("second".to_string(), second),
+        ]);
+
+        let transcript = chain.transcribe_with_language(vec![], None).await.unwrap();
+        assert_eq!(transcript.text, "from first");
+        assert_eq!(transcript.provider.as_deref(), Some("first"));
+        assert_eq!(first_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(second_calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_last_error_is_returned_when_all_fail() {
+        let (first, _) = stub(None);
+        let (second, second_calls) = stub(None);
+        let chain = FallbackProvider::new(vec![
+            ("first".to_string(), first),
+            ("second".to_string(), second),
+        ]);
+
+        let result = chain.transcribe_with_language(vec![], None).await;
+        assert!(matches!(result, Err(TranscriptionError::NetworkError(_))));
+        assert_eq!(second_calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_should_fall_back() {
+        let api_error = |status_code| {
+            TranscriptionError::ApiError(ApiErrorDetails {
+                provider: "OpenAI".to_string(),
+                status_code,
+                error_code: None,
+                error_message: String::new(),
+                raw_response: None,
+            })
+        };
+
+        assert!(should_fall_back(&api_error(Some(500))));
+        assert!(should_fall_back(&api_error(Some(429))));
+        assert!(!should_fall_back(&api_error(Some(404))));
+        assert!(!should_fall_back(&api_error(None)));
+        assert!(!should_fall_back(
+            &TranscriptionError::AuthenticationFailed {
+                provider: "OpenAI".to_string(),
+                details: None,
+            }
+        ));
+    }
+}
@@ -1,8 +1,12 @@
 use async_trait::async_trait;
+use futures::stream::StreamExt;
 use google_api_proto::google::cloud::speech::v2::{
-    recognition_config::DecodingConfig, recognize_request::AudioSource,
-    speech_client::SpeechClient, AutoDetectDecodingConfig, RecognitionConfig, RecognitionFeatures,
-    RecognizeRequest,
+    explicit_decoding_config::AudioEncoding, recognition_config::DecodingConfig,
+    recognize_request::AudioSource, speech_client::SpeechClient,
+    streaming_recognize_request::StreamingRequest, AutoDetectDecodingConfig,
+    ExplicitDecodingConfig, RecognitionConfig, RecognitionFeatures, RecognizeRequest,
+    RecognizeResponse, StreamingRecognitionConfig, StreamingRecognitionFeatures,
+    StreamingRecognizeRequest, StreamingRecognizeResponse,
 };
 use tonic::{
     transport::{Channel, ClientTlsConfig},
@@ -10,7 +14,13 @@ use tonic::{
 };
 use yup_oauth2::{ServiceAccountAuthenticator, ServiceAccountKey};
 
-use crate::transcription::{TranscriptionError, TranscriptionProvider};
+use crate::transcription::{
+    Hypothesis, HypothesisStream, PcmStream, Segment, Transcript, TranscriptionError,
+    TranscriptionProvider, Word,
+};
+
+// Google rejects streaming requests carrying more than 15KB of audio
+const MAX_STREAMING_CHUNK_BYTES: usize = 15 * 1024;
 
 pub struct GoogleV2Provider {
     client: SpeechClient<Channel>,
@@ -22,7 +32,6 @@ pub struct GoogleV2Provider {
 }
 
 impl GoogleV2Provider {
-    #[allow(dead_code)]
     pub async fn new(
         credentials_path: String,
         language_code: String,
@@ -133,6 +142,154 @@ impl GoogleV2Provider {
 
         language_codes
     }
+
+    /// Wrap a request message and attach the OAuth2 authorization header
+    fn authorize<T>(&self, message: T) -> Result<Request<T>, TranscriptionError> {
+        let mut req = Request::new(message);
+        req.metadata_mut().insert(
+            "authorization",
+            self.auth_token
+                .parse()
+                .map_err(|_| TranscriptionError::AuthenticationFailed {
+                    provider: "Google Speech-to-Text gRPC".to_string(),
+                    details: Some("Invalid authorization token format".to_string()),
+                })?,
+        );
+        Ok(req)
+    }
+}
+
+/// Convert a protobuf duration into seconds
+fn seconds(duration: Option<&prost_types::Duration>) -> f64 {
+    duration.map_or(0.0, |d| d.seconds as f64 + f64::from(d.nanos) / 1e9)
+}
+
+/// Google reports unset confidence as 0.0
+fn confidence(value: f32) -> Option<f32> {
+    (value > 0.0).then_some(value)
+}
+
+/// Build a transcript from a synchronous recognition response
+/// Each result covers the audio since the previous one, so consecutive end
+/// offsets become segment boundaries.
+fn response_transcript(response: RecognizeResponse) -> Transcript {
+    let mut segments = Vec::new();
+    let mut words = Vec::new();
+    let mut language = None;
+    let mut start = 0.0;
+
+    for result in response.results {
+        let end = seconds(result.result_end_offset.as_ref());
+        if language.is_none() && !result.language_code.is_empty() {
+            language = Some(result.language_code);
+        }
+
+        if let Some(alternative) = result.alternatives.into_iter().next() {
+            words.extend(alternative.words.into_iter().map(|w| Word {
+                text: w.word,
+                start: seconds(w.start_offset.as_ref()),
+                end: seconds(w.end_offset.as_ref()),
+                confidence: confidence(w.confidence),
+            }));
+            segments.push(Segment {
+                text: alternative.transcript.trim().to_string(),
+                start,
+                end,
+                confidence: confidence(alternative.confidence),
+            });
+        }
+
+        start = end;
+    }
+
+    Transcript::from_segments(segments, words, language)
+}
+
+/// Map a gRPC status to a transcription error
+/// Timeouts and unavailability are network errors; other statuses become API
+/// errors with the equivalent HTTP status, so a fallback chain only moves on
+/// for the ones another provider could avoid.
+fn status_to_error(e: &tonic::Status) -> TranscriptionError {
+    let provider = "Google Speech-to-Text gRPC".to_string();
+    let (http_status, error_code) = match e.code() {
+        tonic::Code::Unauthenticated => {
+            return TranscriptionError::AuthenticationFailed {
+                provider,
+                details: Some(format!("Authentication failed: {}", e.message())),
+            };
+        }
+        tonic::Code::DeadlineExceeded | tonic::Code::Unavailable => {
+            let error_type = if e.code() == tonic::Code::DeadlineExceeded {
+                "Request timeout"
+            } else {
+                "Service unavailable"
+            };
+            return TranscriptionError::NetworkError(crate::transcription::NetworkErrorDetails {
+                provider,
+                error_type: error_type.to_string(),
+                error_message: format!(
+                    "status={:?}, message={}, details={:?}",
+                    e.code(),
+                    e.message(),
+                    e.metadata()
+                ),
+            });
+        }
+        tonic::Code::InvalidArgument => (400, "INVALID_ARGUMENT"),
+        tonic::Code::FailedPrecondition => (400, "FAILED_PRECONDITION"),
+        tonic::Code::OutOfRange => (400, "OUT_OF_RANGE"),
+        tonic::Code::PermissionDenied => (403, "PERMISSION_DENIED"),
+        tonic::Code::NotFound => (404, "NOT_FOUND"),
+        tonic::Code::AlreadyExists => (409, "ALREADY_EXISTS"),
+        tonic::Code::Aborted => (409, "ABORTED"),
+        tonic::Code::ResourceExhausted => (429, "RESOURCE_EXHAUSTED"),
+        tonic::Code::Cancelled => (499, "CANCELLED"),
+        tonic::Code::Unimplemented => (501, "UNIMPLEMENTED"),
+        tonic::Code::Internal => (500, "INTERNAL"),
+        tonic::Code::DataLoss => (500, "DATA_LOSS"),
+        tonic::Code::Unknown | tonic::Code::Ok => (500, "UNKNOWN"),
+    };
+
+    TranscriptionError::ApiError(crate::transcription::ApiErrorDetails {
+        provider,
+        status_code: Some(http_status),
+        error_code: Some(error_code.to_string()),
+        error_message: e.message().to_string(),
+        raw_response: None,
+    })
+}
+
+/// Split a PCM chunk into streaming audio requests within Google's size limit
+fn audio_requests(recognizer: &str, samples: &[i16]) -> Vec<StreamingRecognizeRequest> {
+    samples
+        .chunks(MAX_STREAMING_CHUNK_BYTES / 2)
+        .map(|chunk| {
+            let bytes: Vec<u8> = chunk.iter().flat_map(|s| s.to_le_bytes()).collect();
+            StreamingRecognizeRequest {
+                recognizer: recognizer.to_string(),
+                streaming_request: Some(StreamingRequest::Audio(bytes.into())),
+            }
+        })
+        .collect()
+}
+
+/// Combine the results of a streaming response into one hypothesis
+fn response_hypothesis(response: &StreamingRecognizeResponse) -> Option<Hypothesis> {
+    if response.results.is_empty() {
+        return None;
+    }
+
+    let text: String = response
+        .results
+        .iter()
+        .filter_map(|result| result.alternatives.first())
+        .map(|alternative| alternative.transcript.as_str())
+        .collect();
+
+    Some(Hypothesis {
+        text,
+        is_final: response.results.iter().all(|result| result.is_final),
+    })
 }
 
 #[async_trait]
@@ -141,7 +298,7 @@ impl TranscriptionProvider for GoogleV2Provider {
         &self,
         audio_data: Vec<u8>,
         language: Option<String>,
-    ) -> Result<String, TranscriptionError> {
+    ) -> Result<Transcript, TranscriptionError> {
         if audio_data.is_empty() {
             return Err(TranscriptionError::ApiError(
                 crate::transcription::ApiErrorDetails {
@@ -171,8 +328,8 @@ impl TranscriptionProvider for GoogleV2Provider {
             language_codes,
             features: Some(RecognitionFeatures {
                 enable_automatic_punctuation: true,
-                enable_word_time_offsets: false,
-                enable_word_confidence: false,
+                enable_word_time_offsets: true,
+                enable_word_confidence: true,
                 ..Default::default()
             }),
             adaptation: None,
@@ -190,65 +347,95 @@ impl TranscriptionProvider for GoogleV2Provider {
 
         // Make the API call with auth header
         let mut client = self.client.clone();
-        let mut req = Request::new(request);
-        req.metadata_mut().insert(
-            "authorization",
-            self.auth_token
-                .parse()
-                .map_err(|_| TranscriptionError::AuthenticationFailed {
-                    provider: "Google Speech-to-Text gRPC".to_string(),
-                    details: Some("Invalid authorization token format".to_string()),
-                })?,
-        );
+        let req = self.authorize(request)?;
 
-        let response = client.recognize(req).await.map_err(|e| {
-            let error_type = match e.code() {
-                tonic::Code::DeadlineExceeded => "Request timeout",
-                tonic::Code::Unavailable => "Service unavailable",
-                tonic::Code::Internal => "Internal server error",
-                tonic::Code::InvalidArgument => "Invalid request",
-                tonic::Code::Unauthenticated => "Authentication failed",
-                tonic::Code::PermissionDenied => "Permission denied",
-                tonic::Code::NotFound => "Service not found",
-                _ => "gRPC error",
-            };
+        let response = client
+            .recognize(req)
+            .await
+            .map_err(|e| status_to_error(&e))?;
 
-            if e.code() == tonic::Code::Unauthenticated {
-                TranscriptionError::AuthenticationFailed {
-                    provider: "Google Speech-to-Text gRPC".to_string(),
-                    details: Some(format!("{}: {}", error_type, e.message())),
-                }
-            } else {
-                TranscriptionError::NetworkError(crate::transcription::NetworkErrorDetails {
-                    provider: "Google Speech-to-Text gRPC".to_string(),
-                    error_type: error_type.to_string(),
-                    error_message: format!(
-                        "status={:?}, message={}, details={:?}",
-                        e.code(),
-                        e.message(),
-                        e.metadata()
-                    ),
-                })
-            }
-        })?;
+        Ok(response_transcript(response.into_inner()))
+    }
 
-        let recognize_response = response.into_inner();
+    async fn transcribe_stream(
+        &self,
+        audio: PcmStream,
+        sample_rate: u32,
+        language: Option<String>,
+    ) -> Result<HypothesisStream, TranscriptionError> {
+        let recognizer = format!("{}/recognizers/_", self.parent);
+
+        let config = StreamingRecognitionConfig {
+            config: Some(RecognitionConfig {
+                decoding_config: Some(DecodingConfig::ExplicitDecodingConfig(
+                    ExplicitDecodingConfig {
+                        encoding: AudioEncoding::Linear16 as i32,
+                        sample_rate_hertz: sample_rate as i32,
+                        audio_channel_count: 1,
+                    },
+                )),
+                model: self.model.clone(),
+                language_codes: self.build_language_codes(language),
+                features: Some(RecognitionFeatures {
+                    enable_automatic_punctuation: true,
+                    ..Default::default()
+                }),
+                adaptation: None,
+                transcript_normalization: None,
+                translation_config: None,
+            }),
+            config_mask: None,
+            streaming_features: Some(StreamingRecognitionFeatures {
+                interim_results: true,
+                ..Default::default()
+            }),
+        };
 
-        // Extract transcription from response
-        // Note: results is a Vec, not an Option<Vec>
-        if let Some(result) = recognize_response.results.first() {
-            // alternatives is a Vec, not an Option<Vec>
-            if let Some(alternative) = result.alternatives.first() {
-                return Ok(alternative.transcript.clone());
+        // The first request carries the configuration, every following one audio
+        let config_request = StreamingRecognizeRequest {
+            recognizer: recognizer.clone(),
+            streaming_request: Some(StreamingRequest::StreamingConfig(config)),
+        };
+        let (sender, requests) = futures::channel::mpsc::unbounded();
+        let _ = sender.unbounded_send(config_request);
+
+        // Forward audio as it arrives; the call ends once the audio stream does
+        let mut audio = audio;
+        tokio::spawn(async move {
+            while let Some(chunk) = audio.next().await {
+                for request in audio_requests(&recognizer, &chunk) {
+                    if sender.unbounded_send(request).is_err() {
+                        return;
+                    }
+                }
             }
-        }
+        });
 
-        Ok(String::new())
+        let req = self.authorize(requests)?;
+        let mut client = self.client.clone();
+        let responses = client
+            .streaming_recognize(req)
+            .await
+            .map_err(|e| status_to_error(&e))?
+            .into_inner();
+
+        Ok(Box::pin(
+            responses
+                .map(|response| match response {
+                    Ok(response) => response_hypothesis(&response).map(Ok),
+                    Err(e) => Some(Err(status_to_error(&e))),
+                })
+                .filter_map(futures::future::ready),
+        ))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use google_api_proto::google::cloud::speech::v2::{
+        SpeechRecognitionAlternative, SpeechRecognitionResult, StreamingRecognitionResult, WordInfo,
+    };
 
     // Helper to create provider for testing language code logic
     fn create_test_provider(
@@ -342,4 +529,157 @@ mod tests {
         let codes = provider.build_language_codes(Some("en-US".to_string()));
         assert_eq!(codes, vec!["en-US", "fr-FR"]); // No duplicates
     }
+
+    #[test]
+    fn test_audio_requests_respect_size_limit() {
+        let samples = vec![1i16; MAX_STREAMING_CHUNK_BYTES];
+        let requests = audio_requests("recognizer", &samples);
+
+        assert_eq!(requests.len(), 2);
+        for request in &requests {
+            assert_eq!(request.recognizer, "recognizer");
+            match &request.streaming_request {
+                Some(StreamingRequest::Audio(bytes)) => {
+                    assert_eq!(bytes.len(), MAX_STREAMING_CHUNK_BYTES);
+                    assert_eq!(&bytes[..2], &[1, 0]);
+                }
+                _ => panic!("Expected audio request"),
+            }
+        }
+
+        assert!(audio_requests("recognizer", &[]).is_empty());
+    }
+
+    fn streaming_result(transcript: &str, is_final: bool) -> StreamingRecognitionResult {
+        StreamingRecognitionResult {
+            alternatives: vec![SpeechRecognitionAlternative {
+                transcript: transcript.to_string(),
+                ..Default::default()
+            }],
+            is_final,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_response_hypothesis() {
+        let interim = StreamingRecognizeResponse {
+            results: vec![
+                streaming_result("hello", false),
+                streaming_result(" wor", false),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            response_hypothesis(&interim),
+            Some(Hypothesis {
+                text: "hello wor".to_string(),
+                is_final: false,
+            })
+        );
+
+        let final_response = StreamingRecognizeResponse {
+            results: vec![streaming_result("hello world", true)],
+            ..Default::default()
+        };
+        assert!(response_hypothesis(&final_response).unwrap().is_final);
+
+        // Speech events carry no results
+        assert_eq!(
+            response_hypothesis(&StreamingRecognizeResponse::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_response_transcript() {
+        let offset = |seconds: i64, nanos: i32| Some(prost_types::Duration { seconds, nanos });
+        let response = RecognizeResponse {
+            results: vec![
+                SpeechRecognitionResult {
+                    alternatives: vec![SpeechRecognitionAlternative {
+                        transcript: "hello world".to_string(),
+                        confidence: 0.9,
+                        words: vec![
+                            WordInfo {
+                                word: "hello".to_string(),
+                                start_offset: offset(0, 100_000_000),
+                                end_offset: offset(0, 500_000_000),
+                                confidence: 0.95,
+                                ..Default::default()
+                            },
+                            WordInfo {
+                                word: "world".to_string(),
+                                start_offset: offset(0, 600_000_000),
+                                end_offset: offset(1, 0),
+                                ..Default::default()
+                            },
+                        ],
+                    }],
+                    result_end_offset: offset(1, 200_000_000),
+                    language_code: "en-us".to_string(),
+                    ..Default::default()
+                },
+                SpeechRecognitionResult {
+                    alternatives: vec![SpeechRecognitionAlternative {
+                        transcript: " again".to_string(),
+                        ..Default::default()
+                    }],
+                    result_end_offset: offset(2, 0),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let transcript = response_transcript(response);
+        assert_eq!(transcript.text, "hello world again");
+        assert_eq!(transcript.language.as_deref(), Some("en-us"));
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.segments[1].start, 1.2);
+        assert_eq!(transcript.segments[1].end, 2.0);
+        assert_eq!(transcript.segments[1].confidence, None);
+        assert_eq!(transcript.confidence, Some(0.9));
+
+        assert_eq!(transcript.words.len(), 2);
+        assert_eq!(transcript.words[0].start, 0.1);
+        assert_eq!(transcript.words[0].confidence, Some(0.95));
+        assert_eq!(transcript.words[1].end, 1.0);
+        assert_eq!(transcript.words[1].confidence, None);
+    }
+
+    #[test]
+    fn test_status_to_error_only_falls_back_for_transient_codes() {
+        use crate::transcription::fallback::should_fall_back;
+
+        for code in [
+            tonic::Code::Unavailable,
+            tonic::Code::DeadlineExceeded,
+            tonic::Code::ResourceExhausted,
+            tonic::Code::Internal,
+        ] {
+            let error = status_to_error(&tonic::Status::new(code, "try again"));
+            assert!(should_fall_back(&error), "{:?} should fall back", code);
+        }
+
+        for (code, status) in [
+            (tonic::Code::InvalidArgument, 400),
+            (tonic::Code::PermissionDenied, 403),
+            (tonic::Code::NotFound, 404),
+        ] {
+            let error = status_to_error(&tonic::Status::new(code, "bad recognizer"));
+            assert!(!should_fall_back(&error), "{:?} should not fall back", code);
+            let TranscriptionError::ApiError(details) = error else {
+                panic!("{:?} should be an API error", code);
+            };
+            assert_eq!(details.status_code, Some(status));
+            assert_eq!(details.error_message, "bad recognizer");
+        }
+
+        let error = status_to_error(&tonic::Status::unauthenticated("expired"));
+        assert!(matches!(
+            error,
+            TranscriptionError::AuthenticationFailed { .. }
+        ));
+    }
 }
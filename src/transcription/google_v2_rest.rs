@@ -3,7 +3,7 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use yup_oauth2::{ServiceAccountAuthenticator, ServiceAccountKey};
 
-use crate::transcription::{TranscriptionError, TranscriptionProvider};
+use crate::transcription::{Segment, Transcript, TranscriptionError, TranscriptionProvider, Word};
 
 pub struct GoogleV2RestProvider {
     client: Client,
@@ -47,17 +47,82 @@ struct RecognitionFeatures {
 
 #[derive(Deserialize)]
 struct RecognizeResponse {
+    #[serde(default)]
     results: Vec<SpeechRecognitionResult>,
 }
 
 #[derive(Deserialize)]
 struct SpeechRecognitionResult {
+    #[serde(default)]
     alternatives: Vec<SpeechRecognitionAlternative>,
+    #[serde(rename = "resultEndOffset")]
+    result_end_offset: Option<String>,
+    #[serde(rename = "languageCode")]
+    language_code: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct SpeechRecognitionAlternative {
+    #[serde(default)]
     transcript: String,
+    confidence: Option<f32>,
+    #[serde(default)]
+    words: Vec<WordInfo>,
+}
+
+#[derive(Deserialize)]
+struct WordInfo {
+    word: String,
+    #[serde(rename = "startOffset")]
+    start_offset: Option<String>,
+    #[serde(rename = "endOffset")]
+    end_offset: Option<String>,
+    confidence: Option<f32>,
+}
+
+/// Parse a protobuf JSON duration such as `"1.500s"` into seconds
+fn parse_offset(offset: Option<&str>) -> f64 {
+    offset
+        .and_then(|o| o.strip_suffix('s'))
+        .and_then(|o| o.parse().ok())
+        .unwrap_or(0.0)
+}
+
+impl RecognizeResponse {
+    /// Each result covers the audio since the previous one, so consecutive
+    /// end offsets become segment boundaries
+    fn into_transcript(self) -> Transcript {
+        let mut segments = Vec::new();
+        let mut words = Vec::new();
+        let mut language = None;
+        let mut start = 0.0;
+
+        for result in self.results {
+            let end = parse_offset(result.result_end_offset.as_deref());
+            if language.is_none() {
+                language = result.language_code;
+            }
+
+            if let Some(alternative) = result.alternatives.into_iter().next() {
+                words.extend(alternative.words.into_iter().map(|w| Word {
+                    text: w.word,
+                    start: parse_offset(w.start_offset.as_deref()),
+                    end: parse_offset(w.end_offset.as_deref()),
+                    confidence: w.confidence,
+                }));
+                segments.push(Segment {
+                    text: alternative.transcript.trim().to_string(),
+                    start,
+                    end,
+                    confidence: alternative.confidence,
+                });
+            }
+
+            start = end;
+        }
+
+        Transcript::from_segments(segments, words, language)
+    }
 }
 
 impl GoogleV2RestProvider {
@@ -177,7 +242,7 @@ impl TranscriptionProvider for GoogleV2RestProvider {
         &self,
         audio_data: Vec<u8>,
         language: Option<String>,
-    ) -> Result<String, TranscriptionError> {
+    ) -> Result<Transcript, TranscriptionError> {
         if audio_data.is_empty() {
             return Err(TranscriptionError::ApiError(
                 crate::transcription::ApiErrorDetails {
@@ -214,8 +279,8 @@ impl TranscriptionProvider for GoogleV2RestProvider {
                 language_codes,
                 features: RecognitionFeatures {
                     enable_automatic_punctuation: false,
-                    enable_word_time_offsets: false,
-                    enable_word_confidence: false,
+                    enable_word_time_offsets: true,
+                    enable_word_confidence: true,
                 },
             },
             content: audio_base64,
@@ -311,19 +376,13 @@ impl TranscriptionProvider for GoogleV2RestProvider {
             })
         })?;
 
-        // Extract transcription from response
-        if let Some(result) = recognize_response.results.first() {
-            if let Some(alternative) = result.alternatives.first() {
-                return Ok(alternative.transcript.clone());
-            }
-        }
-
-        Ok(String::new())
+        Ok(recognize_response.into_transcript())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
 
     // Helper to create provider for testing language code logic
     fn create_test_provider(
@@ -417,4 +476,64 @@ mod tests {
         let codes = provider.build_language_codes(Some("en-US".to_string()));
         assert_eq!(codes, vec!["en-US", "fr-FR"]); // No duplicates
     }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset(Some("1.500s")), 1.5);
+        assert_eq!(parse_offset(Some("3s")), 3.0);
+        assert_eq!(parse_offset(Some("garbage")), 0.0);
+        assert_eq!(parse_offset(None), 0.0);
+    }
+
+    #[test]
+    fn test_response_into_transcript() {
+        let response: RecognizeResponse = serde_json::from_str(
+            r#"{
+                "results": [
+                    {
+                        "alternatives": [{
+                            "transcript": "hello world",
+                            "confidence": 0.9,
+                            "words": [
+                                {"word": "hello", "startOffset": "0.100s", "endOffset": "0.500s", "confidence": 0.95},
+                                {"word": "world", "startOffset": "0.600s", "endOffset": "1s"}
+                            ]
+                        }],
+                        "resultEndOffset": "1.200s",
+                        "languageCode": "en-us"
+                    },
+                    {
+                        "alternatives": [{"transcript": " again", "confidence": 0.6}],
+                        "resultEndOffset": "2s",
+                        "languageCode": "en-us"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let transcript = response.into_transcript();
+        assert_eq!(transcript.text, "hello world again");
+        assert_eq!(transcript.language.as_deref(), Some("en-us"));
+
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.segments[1].text, "again");
+        assert_eq!(transcript.segments[1].start, 1.2);
+        assert_eq!(transcript.segments[1].end, 2.0);
+
+        assert_eq!(transcript.words.len(), 2);
+        assert_eq!(transcript.words[0].start, 0.1);
+        assert_eq!(transcript.words[0].confidence, Some(0.95));
+        assert_eq!(transcript.words[1].end, 1.0);
+        assert_eq!(transcript.words[1].confidence, None);
+
+        let confidence = transcript.confidence.unwrap();
+        assert!((confidence - (0.9 * 1.2 + 0.6 * 0.8) / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_empty_response_into_transcript() {
+        let response: RecognizeResponse = serde_json::from_str("{}").unwrap();
+        assert_eq!(response.into_transcript(), Transcript::default());
+    }
 }
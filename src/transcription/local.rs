@@ -1,11 +1,168 @@
-use super::{ApiErrorDetails, TranscriptionError, TranscriptionProvider};
+use super::{
+    ApiErrorDetails, Segment, Transcript, TranscriptionError, TranscriptionProvider, Word,
+};
+use crate::resample;
 use async_trait::async_trait;
 use hound;
 use std::path::Path;
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use whisper_rs::{
+    FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState,
+};
 
+// Whisper models are trained on 16 kHz mono audio
+const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// Decoding settings for local whisper
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalWhisperOptions {
+    /// Threads used for inference, the whisper.cpp default (up to 4) when unset
+    pub threads: Option<u32>,
+    /// Beam width for beam search, 1 decodes greedily
+    pub beam_size: u32,
+    /// Step by which the temperature rises when a decode fails, 0.0 disables fallback
+    pub temperature_increment: f32,
+    /// Text that biases vocabulary and spelling
+    pub initial_prompt: Option<String>,
+    /// Translate speech to English
+    pub translate: bool,
+    /// Decode each 30s window without the text of the previous one
+    pub no_context: bool,
+    /// Language codes automatic detection may choose, any language when empty
+    pub allowed_languages: Vec<String>,
+}
+
+impl Default for LocalWhisperOptions {
+    fn default() -> Self {
+        Self {
+            threads: None,
+            beam_size: 1,
+            temperature_increment: 0.2,
+            initial_prompt: None,
+            translate: false,
+            no_context: true,
+            allowed_languages: Vec::new(),
+        }
+    }
+}
+
+impl LocalWhisperOptions {
+    fn sampling_strategy(&self) -> SamplingStrategy {
+        if self.beam_size > 1 {
+            SamplingStrategy::BeamSearch {
+                beam_size: self.beam_size as i32,
+                patience: -1.0,
+            }
+        } else {
+            SamplingStrategy::Greedy { best_of: 1 }
+        }
+    }
+
+    /// Threads for inference, matching the whisper.cpp default when unset
+    fn thread_count(&self) -> usize {
+        self.threads.map_or_else(
+            || std::thread::available_parallelism().map_or(1, |n| n.get().min(4)),
+            |threads| threads as usize,
+        )
+    }
+}
+
+/// Decoding parameters that `full_params` sets from the options
+/// `FullParams` cannot be read back, so tests check these plain values.
+#[derive(Debug, Clone, PartialEq)]
+struct DecodeSettings<'a> {
+    language: Option<&'a str>,
+    threads: Option<i32>,
+    /// Beam width, or `None` for greedy decoding
+    beam_size: Option<i32>,
+    initial_prompt: Option<&'a str>,
+    temperature_increment: f32,
+    translate: bool,
+    no_context: bool,
+}
+
+impl<'a> DecodeSettings<'a> {
+    fn new(options: &'a LocalWhisperOptions, language: Option<&'a str>) -> Self {
+        Self {
+            language,
+            threads: options.threads.map(|threads| threads as i32),
+            beam_size: match options.sampling_strategy() {
+                SamplingStrategy::BeamSearch { beam_size, .. } => Some(beam_size),
+                SamplingStrategy::Greedy { .. } => None,
+            },
+            initial_prompt: options.initial_prompt.as_deref(),
+            temperature_increment: options.temperature_increment,
+            translate: options.translate,
+            no_context: options.no_context,
+        }
+    }
+}
+
+/// Parameters for decoding with `options`, without abort or progress callbacks
+fn full_params<'a>(
+    options: &'a LocalWhisperOptions,
+    language: Option<&'a str>,
+) -> FullParams<'a, 'static> {
+    let settings = DecodeSettings::new(options, language);
+    let mut params = FullParams::new(options.sampling_strategy());
+    if let Some(lang) = settings.language {
+        params.set_language(Some(lang));
+    }
+    if let Some(threads) = settings.threads {
+        params.set_n_threads(threads);
+    }
+    if let Some(prompt) = settings.initial_prompt {
+        params.set_initial_prompt(prompt);
+    }
+    params.set_temperature_inc(settings.temperature_increment);
+    params.set_translate(settings.translate);
+    params.set_no_context(settings.no_context);
+    params.set_print_special(false);
+    params.set_print_progress(false);
+    params.set_suppress_blank(true);
+    params.set_token_timestamps(true);
+    params
+}
+
+/// The most probable language among `allowed`, or among all languages when
+/// `allowed` is empty, with its probability
+fn pick_language(probabilities: &[f32], allowed: &[String]) -> Option<(&'static str, f32)> {
+    probabilities
+        .iter()
+        .enumerate()
+        .filter_map(|(id, &p)| Some((whisper_rs::get_lang_str(i32::try_from(id).ok()?)?, p)))
+        .filter(|(code, _)| allowed.is_empty() || allowed.iter().any(|a| a == code))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Called with the percentage of the audio transcribed so far
+pub type ProgressCallback = Arc<dyn Fn(i32) + Send + Sync>;
+
+/// Sets the abort flag when dropped
+/// Held by the transcription future, so dropping it, e.g. on SIGTERM, stops
+/// the inference running on the blocking thread at its next check.
+struct AbortOnDrop(Arc<AtomicBool>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// whisper.cpp abort callback, `user_data` points to the abort flag
+/// whisper-rs 0.15 `set_abort_callback_safe` passes its closure with the
+/// wrong type, so the flag is read directly instead.
+unsafe extern "C" fn abort_requested(user_data: *mut std::ffi::c_void) -> bool {
+    // SAFETY: `inference_params` sets `user_data` to a flag that outlives the inference
+    unsafe { &*user_data.cast::<AtomicBool>() }.load(Ordering::Relaxed)
+}
+
+#[derive(Clone)]
 pub struct LocalWhisperProvider {
-    context: WhisperContext,
+    context: Arc<WhisperContext>,
+    options: LocalWhisperOptions,
+    progress: Option<ProgressCallback>,
 }
 
 impl LocalWhisperProvider {
@@ -26,29 +183,149 @@ impl LocalWhisperProvider {
             TranscriptionError::ConfigurationError(format!("Failed to load model: {}", e))
         })?;
 
-        Ok(Self { context: ctx })
+        Ok(Self {
+            context: Arc::new(ctx),
+            options: LocalWhisperOptions::default(),
+            progress: None,
+        })
+    }
+
+    /// Decode with the given settings instead of the defaults
+    pub fn with_options(mut self, options: LocalWhisperOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Report progress while transcribing
+    /// whisper.cpp reports once per 30 second window, so short recordings
+    /// produce no updates.
+    pub fn with_progress(mut self, progress: ProgressCallback) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Parameters for one inference, which must finish before `abort` is dropped
+    fn inference_params<'a>(
+        &'a self,
+        language: Option<&'a str>,
+        abort: &AtomicBool,
+    ) -> FullParams<'a, 'static> {
+        let mut params = full_params(&self.options, language);
+
+        // SAFETY: the callback only reads the flag, which the caller keeps alive
+        unsafe {
+            params.set_abort_callback(Some(abort_requested));
+            params.set_abort_callback_user_data(std::ptr::from_ref(abort).cast_mut().cast());
+        }
+        if let Some(progress) = self.progress.clone() {
+            // The first window reports 0%, which says nothing, and the last
+            // can overshoot 100% when it ends past the audio
+            let mut reported = 0;
+            params.set_progress_callback_safe(move |percent: i32| {
+                let percent = percent.min(100);
+                if percent > reported {
+                    reported = percent;
+                    progress(percent);
+                }
+            });
+        }
+        params
+    }
+
+    /// Detect the spoken language from the first 30 seconds
+    /// Detection runs here rather than inside `full` so its probability can
+    /// be reported and the choice restricted to the allowed languages.
+    fn detect_language(
+        &self,
+        state: &mut WhisperState,
+        samples: &[f32],
+    ) -> Result<(&'static str, f32), TranscriptionError> {
+        let threads = self.options.thread_count();
+        let detection_error = |message: String| {
+            TranscriptionError::ApiError(ApiErrorDetails {
+                provider: "Local".to_string(),
+                status_code: None,
+                error_code: None,
+                error_message: format!("Language detection failed: {}", message),
+                raw_response: None,
+            })
+        };
+
+        state
+            .pcm_to_mel(samples, threads)
+            .map_err(|e| detection_error(e.to_string()))?;
+        let (_, probabilities) = state
+            .lang_detect(0, threads)
+            .map_err(|e| detection_error(e.to_string()))?;
+        pick_language(&probabilities, &self.options.allowed_languages)
+            .ok_or_else(|| detection_error("no allowed language".to_string()))
     }
 }
 
-#[async_trait]
-impl TranscriptionProvider for LocalWhisperProvider {
-    async fn transcribe_with_language(
+/// Whisper timestamps are in centiseconds
+fn centiseconds_to_seconds(t: i64) -> f64 {
+    t as f64 / 100.0
+}
+
+/// A decoded text token with its timing in centiseconds
+struct TokenTiming {
+    text: String,
+    t0: i64,
+    t1: i64,
+    probability: f32,
+}
+
+/// Merge sub-word tokens into words; a token with a leading space starts a new word
+fn merge_tokens(tokens: &[TokenTiming]) -> Vec<Word> {
+    let mut words: Vec<Word> = Vec::new();
+    let mut probabilities: Vec<f32> = Vec::new();
+
+    let finish = |word: &mut Word, probabilities: &mut Vec<f32>| {
+        if !probabilities.is_empty() {
+            word.confidence = Some(probabilities.iter().sum::<f32>() / probabilities.len() as f32);
+            probabilities.clear();
+        }
+    };
+
+    for token in tokens {
+        let starts_word = token.text.starts_with(' ') || words.is_empty();
+        let text = token.text.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        if starts_word {
+            if let Some(word) = words.last_mut() {
+                finish(word, &mut probabilities);
+            }
+            words.push(Word {
+                text: text.to_string(),
+                start: centiseconds_to_seconds(token.t0),
+                end: centiseconds_to_seconds(token.t1),
+                confidence: None,
+            });
+        } else if let Some(word) = words.last_mut() {
+            word.text.push_str(text);
+            word.end = centiseconds_to_seconds(token.t1);
+        }
+        probabilities.push(token.probability);
+    }
+
+    if let Some(word) = words.last_mut() {
+        finish(word, &mut probabilities);
+    }
+
+    words
+}
+
+impl LocalWhisperProvider {
+    /// Transcribe mono 16 kHz samples, stopping early once `abort` is set
+    fn transcribe_samples(
         &self,
-        audio_data: Vec<u8>,
+        samples: &[f32],
         language: Option<String>,
-    ) -> Result<String, TranscriptionError> {
-        // Decode WAV to PCM samples
-        let reader = hound::WavReader::new(std::io::Cursor::new(audio_data)).map_err(|e| {
-            TranscriptionError::ConfigurationError(format!("Failed to read WAV data: {}", e))
-        })?;
-        let samples: Result<Vec<f32>, _> = reader
-            .into_samples::<i16>()
-            .map(|s| s.map(|v| f32::from(v) / f32::from(i16::MAX)))
-            .collect();
-        let samples = samples.map_err(|e| {
-            TranscriptionError::ConfigurationError(format!("Failed to parse WAV samples: {}", e))
-        })?;
-
+        abort: &AtomicBool,
+    ) -> Result<Transcript, TranscriptionError> {
         let mut state = self.context.create_state().map_err(|e| {
             TranscriptionError::ApiError(ApiErrorDetails {
                 provider: "Local".to_string(),
@@ -59,34 +336,394 @@ impl TranscriptionProvider for LocalWhisperProvider {
             })
         })?;
 
-        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
-        if let Some(ref lang) = language {
-            params.set_language(Some(lang));
-        }
-        params.set_translate(false);
-        params.set_print_special(false);
-        params.set_print_progress(false);
-        params.set_suppress_blank(true);
+        // English-only models cannot detect languages and always transcribe English
+        let (language, language_probability) = match language {
+            Some(language) => (language, None),
+            None if self.context.is_multilingual() => {
+                let (code, probability) = self.detect_language(&mut state, samples)?;
+                (code.to_string(), Some(probability))
+            }
+            None => ("en".to_string(), None),
+        };
 
-        state.full(params, &samples).map_err(|e| {
+        let params = self.inference_params(Some(&language), abort);
+        state.full(params, samples).map_err(|e| {
+            let error_message = if abort.load(Ordering::Relaxed) {
+                "Transcription cancelled".to_string()
+            } else {
+                e.to_string()
+            };
             TranscriptionError::ApiError(ApiErrorDetails {
                 provider: "Local".to_string(),
                 status_code: None,
                 error_code: None,
-                error_message: e.to_string(),
+                error_message,
                 raw_response: None,
             })
         })?;
 
-        let mut result = String::new();
+        // Timestamp, language and other special tokens all sort after end-of-text
+        let token_eot = self.context.token_eot();
+
+        let mut segments = Vec::new();
+        let mut words = Vec::new();
         let num_segments = state.full_n_segments();
         for i in 0..num_segments {
-            if let Some(segment) = state.get_segment(i) {
-                if let Ok(text) = segment.to_str() {
-                    result.push_str(text);
+            let Some(segment) = state.get_segment(i) else {
+                continue;
+            };
+            let Ok(text) = segment.to_str() else {
+                continue;
+            };
+
+            let mut tokens = Vec::new();
+            for t in 0..segment.n_tokens() {
+                let Some(token) = segment.get_token(t) else {
+                    continue;
+                };
+                if token.token_id() >= token_eot {
+                    continue;
                 }
+                let data = token.token_data();
+                tokens.push(TokenTiming {
+                    text: token
+                        .to_str_lossy()
+                        .map(|t| t.into_owned())
+                        .unwrap_or_default(),
+                    t0: data.t0,
+                    t1: data.t1,
+                    probability: data.p,
+                });
             }
+
+            let confidence = (!tokens.is_empty())
+                .then(|| tokens.iter().map(|t| t.probability).sum::<f32>() / tokens.len() as f32);
+
+            words.extend(merge_tokens(&tokens));
+            segments.push(Segment {
+                text: text.trim().to_string(),
+                start: centiseconds_to_seconds(segment.start_timestamp()),
+                end: centiseconds_to_seconds(segment.end_timestamp()),
+                confidence,
+            });
+        }
+
+        Ok(Transcript {
+            language_probability,
+            ..Transcript::from_segments(segments, words, Some(language))
+        })
+    }
+}
+
+/// Decode WAV data to the mono 16 kHz samples whisper expects
+/// Recordings and converted files follow `AUDIO_SAMPLE_RATE` and
+/// `AUDIO_CHANNELS`, so other layouts are converted here.
+fn decode_wav(audio_data: Vec<u8>) -> Result<Vec<f32>, TranscriptionError> {
+    let reader = hound::WavReader::new(std::io::Cursor::new(audio_data)).map_err(|e| {
+        TranscriptionError::ConfigurationError(format!("Failed to read WAV data: {}", e))
+    })?;
+    let spec = reader.spec();
+    let samples: Result<Vec<f32>, _> = reader
+        .into_samples::<i16>()
+        .map(|s| s.map(|v| f32::from(v) / f32::from(i16::MAX)))
+        .collect();
+    let samples = samples.map_err(|e| {
+        TranscriptionError::ConfigurationError(format!("Failed to parse WAV samples: {}", e))
+    })?;
+
+    let mut mono = Vec::with_capacity(samples.len() / usize::from(spec.channels.max(1)));
+    resample::convert_channels(&samples, spec.channels, 1, &mut mono);
+    let mut resampler = resample::Resampler::new(spec.sample_rate, WHISPER_SAMPLE_RATE, 1);
+    if resampler.is_passthrough() {
+        return Ok(mono);
+    }
+    let mut resampled = Vec::new();
+    resampler.process(&mono, &mut resampled);
+    Ok(resampled)
+}
+
+#[async_trait]
+impl TranscriptionProvider for LocalWhisperProvider {
+    async fn transcribe_with_language(
+        &self,
+        audio_data: Vec<u8>,
+        language: Option<String>,
+    ) -> Result<Transcript, TranscriptionError> {
+        let samples = decode_wav(audio_data)?;
+
+        // Inference takes seconds to minutes, so it runs on a blocking thread
+        let abort = Arc::new(AtomicBool::new(false));
+        let _abort_on_drop = AbortOnDrop(Arc::clone(&abort));
+        let provider = self.clone();
+        tokio::task::spawn_blocking(move || provider.transcribe_samples(&samples, language, &abort))
+            .await
+            .map_err(|e| {
+                TranscriptionError::ApiError(ApiErrorDetails {
+                    provider: "Local".to_string(),
+                    status_code: None,
+                    error_code: None,
+                    error_message: format!("Inference task failed: {}", e),
+                    raw_response: None,
+                })
+            })?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(text: &str, t0: i64, t1: i64, probability: f32) -> TokenTiming {
+        TokenTiming {
+            text: text.to_string(),
+            t0,
+            t1,
+            probability,
         }
-        Ok(result)
+    }
+
+    #[test]
+    fn test_merge_tokens_into_words() {
+        let words = merge_tokens(&[
+            token(" Hel", 0, 20, 0.8),
+            token("lo", 20, 40, 0.6),
+            token(" world", 50, 90, 0.9),
+            token(".", 90, 95, 1.0),
+        ]);
+
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].text, "Hello");
+        assert_eq!(words[0].start, 0.0);
+        assert_eq!(words[0].end, 0.4);
+        assert!((words[0].confidence.unwrap() - 0.7).abs() < 1e-6);
+
+        assert_eq!(words[1].text, "world.");
+        assert_eq!(words[1].start, 0.5);
+        assert_eq!(words[1].end, 0.95);
+        assert!((words[1].confidence.unwrap() - 0.95).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_merge_tokens_skips_blank_tokens() {
+        let words = merge_tokens(&[token("Hi", 0, 10, 0.5), token(" ", 10, 12, 0.1)]);
+
+        assert_eq!(words.len(), 1);
+        assert_eq!(words[0].text, "Hi");
+        assert_eq!(words[0].confidence, Some(0.5));
+        assert!(merge_tokens(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_sampling_strategy_follows_beam_size() {
+        let options = LocalWhisperOptions::default();
+        assert!(matches!(
+            options.sampling_strategy(),
+            SamplingStrategy::Greedy { best_of: 1 }
+        ));
+
+        let options = LocalWhisperOptions {
+            beam_size: 5,
+            ..Default::default()
+        };
+        assert!(matches!(
+            options.sampling_strategy(),
+            SamplingStrategy::BeamSearch { beam_size: 5, .. }
+        ));
+    }
+
+    fn silence_wav(seconds: usize) -> Vec<u8> {
+        crate::wav::WavEncoder::new(16000, 1)
+            .encode_to_wav(&vec![0.0; 16000 * seconds])
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tiny_model_transcribes_with_defaults() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let model = dir.path().join("ggml-tiny-test.bin");
+        crate::test_utils::write_tiny_whisper_model(&model, false);
+
+        let provider = LocalWhisperProvider::new(&model).unwrap();
+        let transcript = provider
+            .transcribe_with_language(silence_wav(1), Some("en".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn test_decode_wav_converts_to_16khz_mono() {
+        let stereo = crate::wav::WavEncoder::new(48000, 2)
+            .encode_to_wav(&vec![0.5; 48000 * 2])
+            .unwrap();
+        let samples = decode_wav(stereo).unwrap();
+        assert!((15990..=16000).contains(&samples.len()));
+        assert!(samples.iter().all(|sample| (sample - 0.5).abs() < 1e-3));
+
+        let samples = decode_wav(silence_wav(1)).unwrap();
+        assert_eq!(samples.len(), 16000);
+    }
+
+    #[tokio::test]
+    async fn test_tiny_model_transcribes_48khz_audio() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let model = dir.path().join("ggml-tiny-test.bin");
+        crate::test_utils::write_tiny_whisper_model(&model, false);
+
+        let provider = LocalWhisperProvider::new(&model).unwrap();
+        let wav = crate::wav::WavEncoder::new(48000, 1)
+            .encode_to_wav(&vec![0.0; 48000 * 3])
+            .unwrap();
+        let transcript = provider
+            .transcribe_with_language(wav, Some("en".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn test_full_params_follow_options() {
+        let options = LocalWhisperOptions::default();
+        assert_eq!(
+            DecodeSettings::new(&options, None),
+            DecodeSettings {
+                language: None,
+                threads: None,
+                beam_size: None,
+                initial_prompt: None,
+                temperature_increment: 0.2,
+                translate: false,
+                no_context: true,
+            }
+        );
+
+        let options = LocalWhisperOptions {
+            threads: Some(2),
+            beam_size: 3,
+            temperature_increment: 0.0,
+            initial_prompt: Some("hello world".to_string()),
+            translate: true,
+            no_context: false,
+            allowed_languages: Vec::new(),
+        };
+        assert_eq!(
+            DecodeSettings::new(&options, Some("de")),
+            DecodeSettings {
+                language: Some("de"),
+                threads: Some(2),
+                beam_size: Some(3),
+                initial_prompt: Some("hello world"),
+                temperature_increment: 0.0,
+                translate: true,
+                no_context: false,
+            }
+        );
+        // Building the parameters copies the prompt into whisper.cpp's form
+        let _ = full_params(&options, Some("de"));
+    }
+
+    #[test]
+    fn test_pick_language_respects_allowed_languages() {
+        // Ids follow whisper's language table: en, zh, de, es, ...
+        let probabilities = [0.1, 0.6, 0.25, 0.05];
+        assert_eq!(pick_language(&probabilities, &[]), Some(("zh", 0.6)));
+
+        let allowed = vec!["en".to_string(), "de".to_string()];
+        assert_eq!(pick_language(&probabilities, &allowed), Some(("de", 0.25)));
+
+        let allowed = vec!["pl".to_string()];
+        assert_eq!(pick_language(&probabilities, &allowed), None);
+    }
+
+    #[tokio::test]
+    async fn test_tiny_model_detects_language() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let model = dir.path().join("ggml-tiny-test.bin");
+        crate::test_utils::write_tiny_whisper_model(&model, true);
+
+        let provider = LocalWhisperProvider::new(&model).unwrap();
+        let transcript = provider
+            .transcribe_with_language(silence_wav(1), None)
+            .await
+            .unwrap();
+        let language = transcript.language.unwrap();
+        assert!(whisper_rs::get_lang_id(&language).is_some());
+        let probability = transcript.language_probability.unwrap();
+        assert!((0.0..=1.0).contains(&probability));
+
+        let provider = provider.with_options(LocalWhisperOptions {
+            allowed_languages: vec!["pl".to_string()],
+            ..Default::default()
+        });
+        let transcript = provider
+            .transcribe_with_language(silence_wav(1), None)
+            .await
+            .unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("pl"));
+        assert!(transcript.language_probability.is_some());
+
+        // A requested language is used as is
+        let transcript = provider
+            .transcribe_with_language(silence_wav(1), Some("en".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert_eq!(transcript.language_probability, None);
+    }
+
+    #[tokio::test]
+    async fn test_english_only_model_reports_english() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let model = dir.path().join("ggml-tiny-test.bin");
+        crate::test_utils::write_tiny_whisper_model(&model, false);
+
+        let provider = LocalWhisperProvider::new(&model).unwrap();
+        let transcript = provider
+            .transcribe_with_language(silence_wav(1), None)
+            .await
+            .unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert_eq!(transcript.language_probability, None);
+    }
+
+    #[test]
+    fn test_abort_stops_inference() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let model = dir.path().join("ggml-tiny-test.bin");
+        crate::test_utils::write_tiny_whisper_model(&model, false);
+
+        let provider = LocalWhisperProvider::new(&model).unwrap();
+        let error = provider
+            .transcribe_samples(
+                &vec![0.0; 16000],
+                Some("en".to_string()),
+                &AtomicBool::new(true),
+            )
+            .unwrap_err();
+        assert!(error.to_string().contains("Transcription cancelled"));
+    }
+
+    #[tokio::test]
+    async fn test_progress_is_reported_per_window() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let model = dir.path().join("ggml-tiny-test.bin");
+        crate::test_utils::write_tiny_whisper_model(&model, false);
+
+        let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let progress = Arc::clone(&reported);
+        let provider = LocalWhisperProvider::new(&model)
+            .unwrap()
+            .with_progress(Arc::new(move |percent| {
+                progress.lock().unwrap().push(percent);
+            }));
+        provider
+            .transcribe_with_language(silence_wav(65), Some("en".to_string()))
+            .await
+            .unwrap();
+
+        let reported = reported.lock().unwrap();
+        assert!(!reported.is_empty());
+        assert!(reported.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(reported.iter().all(|percent| (1..=100).contains(percent)));
     }
 }
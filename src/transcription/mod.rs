@@ -1,6 +1,12 @@
 use async_trait::async_trait;
+use futures::stream::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::fmt::Write;
+use std::pin::Pin;
+
+use crate::config::Config;
+use crate::wav::WavEncoder;
 
 pub mod openai;
 // Secure Google provider using google-api-proto
@@ -9,6 +15,8 @@ pub mod google_v2;
 pub mod google_v2_rest;
 // Local whisper provider using whisper-rs
 pub mod local;
+// Chain of providers tried in order
+pub mod fallback;
 
 #[derive(Debug)]
 pub struct ApiErrorDetails {
@@ -88,46 +96,236 @@ impl fmt::Display for TranscriptionError {
 
 impl std::error::Error for TranscriptionError {}
 
+/// A recognized word with its position in the audio, in seconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Word {
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+    pub confidence: Option<f32>,
+}
+
+/// A recognized phrase or sentence with its position in the audio, in seconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+    pub confidence: Option<f32>,
+}
+
+/// Transcription result with optional timing and confidence details
+/// Providers fill in as much as their API reports; `segments` and `words`
+/// are empty and `language`/`confidence` are `None` when unavailable.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Transcript {
+    pub text: String,
+    pub segments: Vec<Segment>,
+    pub words: Vec<Word>,
+    pub language: Option<String>,
+    /// Probability of the detected language, when the provider detected it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language_probability: Option<f32>,
+    pub confidence: Option<f32>,
+    /// Provider in a fallback chain that produced the transcript
+    #[serde(skip)]
+    pub provider: Option<String>,
+}
+
+impl Transcript {
+    /// Create a transcript whose text joins the segment texts
+    pub fn from_segments(
+        segments: Vec<Segment>,
+        words: Vec<Word>,
+        language: Option<String>,
+    ) -> Self {
+        let text = segments
+            .iter()
+            .map(|segment| segment.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Self {
+            text,
+            confidence: Self::segment_confidence(&segments),
+            segments,
+            words,
+            language,
+            language_probability: None,
+            provider: None,
+        }
+    }
+
+    /// Duration-weighted mean of the segment confidences
+    pub fn segment_confidence(segments: &[Segment]) -> Option<f32> {
+        let (weighted, total) = segments
+            .iter()
+            .filter_map(|segment| {
+                let duration = (segment.end - segment.start).max(0.0);
+                segment
+                    .confidence
+                    .map(|c| (f64::from(c) * duration, duration))
+            })
+            .fold((0.0, 0.0), |(w, t), (cw, d)| (w + cw, t + d));
+
+        if total > 0.0 {
+            Some((weighted / total) as f32)
+        } else {
+            None
+        }
+    }
+}
+
+/// Mono 16-bit PCM chunks fed to a streaming transcription as they are recorded
+pub type PcmStream = Pin<Box<dyn Stream<Item = Vec<i16>> + Send>>;
+
+/// Hypotheses produced by a streaming transcription
+pub type HypothesisStream =
+    Pin<Box<dyn Stream<Item = Result<Hypothesis, TranscriptionError>> + Send>>;
+
+/// Text recognized from a streaming transcription
+/// Partial hypotheses may still change; final ones cover a finished part of
+/// the audio and are not repeated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hypothesis {
+    pub text: String,
+    pub is_final: bool,
+}
+
 #[async_trait]
 pub trait TranscriptionProvider: Send + Sync {
     async fn transcribe_with_language(
         &self,
         audio_data: Vec<u8>,
         language: Option<String>,
-    ) -> Result<String, TranscriptionError>;
+    ) -> Result<Transcript, TranscriptionError>;
+
+    /// Transcribe audio while it is still being recorded
+    /// Providers without native streaming buffer the whole stream and fall
+    /// back to `transcribe_with_language`, yielding a single final hypothesis.
+    async fn transcribe_stream(
+        &self,
+        mut audio: PcmStream,
+        sample_rate: u32,
+        language: Option<String>,
+    ) -> Result<HypothesisStream, TranscriptionError> {
+        let mut samples = Vec::new();
+        while let Some(chunk) = audio.next().await {
+            samples.extend_from_slice(&chunk);
+        }
+
+        let wav_data = WavEncoder::new(sample_rate, 1).encode_pcm_to_wav(&samples);
+        let transcript = self.transcribe_with_language(wav_data, language).await?;
+
+        Ok(Box::pin(futures::stream::once(async move {
+            Ok(Hypothesis {
+                text: transcript.text,
+                is_final: true,
+            })
+        })))
+    }
+}
+
+/// Whisper language code for a code or an English language name
+/// e.g. `german` and `de` both give `de`.
+pub fn whisper_language_code(language: &str) -> Option<&'static str> {
+    let language = language.trim().to_lowercase();
+    (0..=whisper_rs::get_lang_max_id())
+        .filter_map(|id| {
+            Some((
+                whisper_rs::get_lang_str(id)?,
+                whisper_rs::get_lang_str_full(id)?,
+            ))
+        })
+        .find(|(code, name)| *code == language || *name == language)
+        .map(|(code, _)| code)
 }
 
 pub struct TranscriptionFactory;
 
 impl TranscriptionFactory {
+    /// Create the configured provider
+    /// A comma-separated list such as `openai,local` creates a `FallbackProvider`
+    /// that tries each in order.
     pub async fn create_provider(
         provider_type: &str,
+        config: &Config,
     ) -> Result<Box<dyn TranscriptionProvider>, TranscriptionError> {
+        let names: Vec<&str> = provider_type
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect();
+        if names.len() < 2 {
+            return Self::create_single_provider(
+                names.first().copied().unwrap_or(provider_type),
+                config,
+            )
+            .await;
+        }
+
+        let mut providers = Vec::with_capacity(names.len());
+        for name in names {
+            providers.push((
+                name.to_lowercase(),
+                Self::create_single_provider(name, config).await?,
+            ));
+        }
+        Ok(Box::new(fallback::FallbackProvider::new(providers)))
+    }
+
+    async fn create_single_provider(
+        provider_type: &str,
+        config: &Config,
+    ) -> Result<Box<dyn TranscriptionProvider>, TranscriptionError> {
+        let config = config.clone();
         match provider_type.to_lowercase().as_str() {
             "openai" => {
-                let config = crate::config::load_config();
                 let api_key = config.openai_api_key.ok_or_else(|| {
                     TranscriptionError::ConfigurationError("OpenAI API key not found".to_string())
                 })?;
 
+                let response_format = config
+                    .openai_response_format
+                    .parse()
+                    .map_err(TranscriptionError::ConfigurationError)?;
+
                 let client = openai::OpenAIProvider::new_with_options(
                     api_key,
                     Some(config.whisper_timeout_seconds),
                     Some(config.whisper_max_retries),
                     Some(config.whisper_model),
                     config.openai_base_url,
-                )?;
+                )?
+                .with_request_options(openai::RequestOptions {
+                    prompt: config.openai_prompt,
+                    temperature: config.openai_temperature,
+                    response_format,
+                    translate: config.translate,
+                    allowed_languages: config.whisper_allowed_languages,
+                });
 
                 Ok(Box::new(client))
             }
             "local" => {
-                let config = crate::config::load_config();
-                let model_path = crate::config::Config::model_path(&config.whisper_model);
-                let provider = local::LocalWhisperProvider::new(&model_path)?;
+                let model_path = Config::model_path(&config.whisper_model);
+                let provider = local::LocalWhisperProvider::new(&model_path)?
+                    .with_options(local::LocalWhisperOptions {
+                        threads: config.local_whisper_threads,
+                        beam_size: config.local_whisper_beam_size,
+                        temperature_increment: config.local_whisper_temperature_increment,
+                        initial_prompt: config.local_whisper_prompt,
+                        translate: config.translate,
+                        no_context: config.local_whisper_no_context,
+                        allowed_languages: config.whisper_allowed_languages,
+                    })
+                    .with_progress(std::sync::Arc::new(|percent| {
+                        eprintln!("Local transcription {}% done", percent);
+                    }));
                 Ok(Box::new(provider))
             }
             "google" => {
-                let config = crate::config::load_config();
                 let credentials_path = config.google_application_credentials.ok_or_else(|| {
                     TranscriptionError::ConfigurationError(
                         "Google application credentials not found".to_string(),
@@ -144,6 +342,23 @@ impl TranscriptionFactory {
 
                 Ok(Box::new(client))
             }
+            "google-grpc" => {
+                let credentials_path = config.google_application_credentials.ok_or_else(|| {
+                    TranscriptionError::ConfigurationError(
+                        "Google application credentials not found".to_string(),
+                    )
+                })?;
+
+                let client = google_v2::GoogleV2Provider::new(
+                    credentials_path,
+                    config.google_speech_language_code,
+                    config.google_speech_model,
+                    config.google_speech_alternative_languages,
+                )
+                .await?;
+
+                Ok(Box::new(client))
+            }
             _ => Err(TranscriptionError::UnsupportedProvider(
                 provider_type.to_string(),
             )),
@@ -154,7 +369,92 @@ impl TranscriptionFactory {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::ENV_MUTEX;
+    use crate::test_utils::{RecordingProvider, ENV_MUTEX};
+
+    #[test]
+    fn test_transcript_from_segments() {
+        let segments = vec![
+            Segment {
+                text: " Hello there.".to_string(),
+                start: 0.0,
+                end: 1.0,
+                confidence: Some(0.6),
+            },
+            Segment {
+                text: " General Kenobi.".to_string(),
+                start: 1.0,
+                end: 2.0,
+                confidence: Some(1.0),
+            },
+        ];
+
+        let transcript = Transcript::from_segments(segments, Vec::new(), Some("en".to_string()));
+        assert_eq!(transcript.text, "Hello there. General Kenobi.");
+        assert_eq!(transcript.segments.len(), 2);
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert!((transcript.confidence.unwrap() - 0.8).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_segment_confidence_is_duration_weighted() {
+        let segment = |start: f64, end: f64, confidence: Option<f32>| Segment {
+            text: String::new(),
+            start,
+            end,
+            confidence,
+        };
+
+        let segments = [
+            segment(0.0, 3.0, Some(0.9)),
+            segment(3.0, 4.0, Some(0.5)),
+            segment(4.0, 9.0, None),
+        ];
+        let confidence = Transcript::segment_confidence(&segments).unwrap();
+        assert!((confidence - 0.8).abs() < 1e-6);
+
+        assert_eq!(Transcript::segment_confidence(&[]), None);
+        assert_eq!(
+            Transcript::segment_confidence(&[segment(0.0, 1.0, None)]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_whisper_language_code() {
+        assert_eq!(whisper_language_code("de"), Some("de"));
+        assert_eq!(whisper_language_code("German"), Some("de"));
+        assert_eq!(whisper_language_code("english"), Some("en"));
+        assert_eq!(whisper_language_code("klingon"), None);
+        assert_eq!(whisper_language_code("de-DE"), None);
+    }
+
+    #[tokio::test]
+    async fn test_default_streaming_falls_back_to_batch() {
+        let provider = RecordingProvider::default();
+        let chunks = futures::stream::iter(vec![vec![1i16, 2], vec![3], vec![]]);
+
+        let hypotheses: Vec<_> = provider
+            .transcribe_stream(Box::pin(chunks), 16000, Some("en".to_string()))
+            .await
+            .unwrap()
+            .collect()
+            .await;
+
+        assert_eq!(hypotheses.len(), 1);
+        assert_eq!(
+            hypotheses[0].as_ref().unwrap(),
+            &Hypothesis {
+                text: "hello world".to_string(),
+                is_final: true,
+            }
+        );
+
+        // All chunks arrive as a single WAV file
+        let (wav_data, language) = provider.received.lock().unwrap().pop().unwrap();
+        assert_eq!(&wav_data[0..4], b"RIFF");
+        assert_eq!(&wav_data[44..], &[1, 0, 2, 0, 3, 0]);
+        assert_eq!(language.as_deref(), Some("en"));
+    }
 
     #[test]
     fn test_transcription_error_display() {
@@ -207,7 +507,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_factory_unsupported_provider() {
-        let result = TranscriptionFactory::create_provider("unsupported").await;
+        let result = TranscriptionFactory::create_provider("unsupported", &Config::default()).await;
         assert!(result.is_err());
 
         if let Err(TranscriptionError::UnsupportedProvider(provider)) = result {
@@ -219,84 +519,44 @@ mod tests {
 
     #[tokio::test]
     async fn test_factory_openai_provider_missing_key() {
-        #[allow(clippy::await_holding_lock)]
-        {
-            let _lock = ENV_MUTEX.lock().await;
-
-            // Save current state and set up test environment
-            let original_key = std::env::var("OPENAI_API_KEY").ok();
-            std::env::remove_var("OPENAI_API_KEY");
-
-            let result = TranscriptionFactory::create_provider("openai").await;
-
-            // Restore original state
-            if let Some(key) = original_key {
-                std::env::set_var("OPENAI_API_KEY", key);
-            } else {
-                std::env::remove_var("OPENAI_API_KEY");
-            }
-
-            assert!(result.is_err());
+        let result = TranscriptionFactory::create_provider("openai", &Config::default()).await;
+        assert!(result.is_err());
 
-            if let Err(TranscriptionError::ConfigurationError(msg)) = result {
-                assert!(msg.contains("OpenAI API key not found"));
-            } else {
-                panic!("Expected ConfigurationError for missing API key");
-            }
+        if let Err(TranscriptionError::ConfigurationError(msg)) = result {
+            assert!(msg.contains("OpenAI API key not found"));
+        } else {
+            panic!("Expected ConfigurationError for missing API key");
         }
     }
 
     #[tokio::test]
     async fn test_factory_openai_provider_creation() {
-        #[allow(clippy::await_holding_lock)]
-        {
-            let _lock = ENV_MUTEX.lock().await;
-
-            // Save current state and set up test environment
-            let original_key = std::env::var("OPENAI_API_KEY").ok();
-            std::env::set_var("OPENAI_API_KEY", "test-key");
-
-            let result = TranscriptionFactory::create_provider("openai").await;
-            assert!(result.is_ok());
-
-            let provider = result.unwrap();
+        let config = Config {
+            openai_api_key: Some("test-key".to_string()),
+            // Nothing listens here, so the request fails quickly
+            openai_base_url: Some("http://127.0.0.1:9".to_string()),
+            whisper_max_retries: 0,
+            ..Config::default()
+        };
+        let result = TranscriptionFactory::create_provider("openai", &config).await;
+        assert!(result.is_ok());
 
-            // Test that the provider implements the trait
-            let empty_audio = vec![];
-            let result = provider.transcribe_with_language(empty_audio, None).await;
-            // We expect this to fail with network/auth error, but it should compile and run
-            assert!(result.is_err());
+        let provider = result.unwrap();
 
-            // Restore original state
-            if let Some(key) = original_key {
-                std::env::set_var("OPENAI_API_KEY", key);
-            } else {
-                std::env::remove_var("OPENAI_API_KEY");
-            }
-        }
+        // Test that the provider implements the trait
+        let empty_audio = vec![];
+        let result = provider.transcribe_with_language(empty_audio, None).await;
+        // We expect this to fail with network/auth error, but it should compile and run
+        assert!(result.is_err());
     }
 
     #[tokio::test]
     async fn test_factory_google_provider_missing_credentials() {
-        #[allow(clippy::await_holding_lock)]
-        {
-            let _lock = ENV_MUTEX.lock().await;
-
-            // Save current state and set up test environment
-            let original_credentials = std::env::var("GOOGLE_APPLICATION_CREDENTIALS").ok();
-            std::env::remove_var("GOOGLE_APPLICATION_CREDENTIALS");
-
-            let result = TranscriptionFactory::create_provider("google").await;
-
-            // Restore original state
-            if let Some(credentials) = original_credentials {
-                std::env::set_var("GOOGLE_APPLICATION_CREDENTIALS", credentials);
-            } else {
-                std::env::remove_var("GOOGLE_APPLICATION_CREDENTIALS");
-            }
-
-            assert!(result.is_err());
+        let config = Config::default();
+        let result = TranscriptionFactory::create_provider("google", &config).await;
+        let grpc_result = TranscriptionFactory::create_provider("google-grpc", &config).await;
 
+        for result in [result, grpc_result] {
             if let Err(TranscriptionError::ConfigurationError(msg)) = result {
                 assert!(msg.contains("Google application credentials not found"));
             } else {
@@ -307,37 +567,26 @@ mod tests {
 
     #[tokio::test]
     async fn test_provider_switching_integration() {
-        #[allow(clippy::await_holding_lock)]
-        {
-            let _lock = ENV_MUTEX.lock().await;
-
-            // Save current state and set up test environment
-            let original_key = std::env::var("OPENAI_API_KEY").ok();
-            std::env::set_var("OPENAI_API_KEY", "test-key");
+        let config = Config {
+            openai_api_key: Some("test-key".to_string()),
+            ..Config::default()
+        };
 
-            // Test case sensitivity
-            let result = TranscriptionFactory::create_provider("OpenAI").await;
-            assert!(result.is_ok());
+        // Test case sensitivity
+        let result = TranscriptionFactory::create_provider("OpenAI", &config).await;
+        assert!(result.is_ok());
 
-            let result = TranscriptionFactory::create_provider("OPENAI").await;
-            assert!(result.is_ok());
+        let result = TranscriptionFactory::create_provider("OPENAI", &config).await;
+        assert!(result.is_ok());
 
-            // Test that unsupported providers are handled correctly
-            let result = TranscriptionFactory::create_provider("unsupported_provider").await;
-            assert!(result.is_err());
-
-            if let Err(TranscriptionError::UnsupportedProvider(provider)) = result {
-                assert_eq!(provider, "unsupported_provider");
-            } else {
-                panic!("Expected UnsupportedProvider error");
-            }
+        // Test that unsupported providers are handled correctly
+        let result = TranscriptionFactory::create_provider("unsupported_provider", &config).await;
+        assert!(result.is_err());
 
-            // Restore original state
-            if let Some(key) = original_key {
-                std::env::set_var("OPENAI_API_KEY", key);
-            } else {
-                std::env::remove_var("OPENAI_API_KEY");
-            }
+        if let Err(TranscriptionError::UnsupportedProvider(provider)) = result {
+            assert_eq!(provider, "unsupported_provider");
+        } else {
+            panic!("Expected UnsupportedProvider error");
         }
     }
 
@@ -348,9 +597,12 @@ mod tests {
             let _lock = ENV_MUTEX.lock().await;
             let tmp_home = tempfile::tempdir().unwrap();
             std::env::set_var("HOME", tmp_home.path());
-            std::env::set_var("WHISPER_MODEL", "missing.bin");
 
-            let result = TranscriptionFactory::create_provider("local").await;
+            let config = Config {
+                whisper_model: "missing.bin".to_string(),
+                ..Config::default()
+            };
+            let result = TranscriptionFactory::create_provider("local", &config).await;
             assert!(result.is_err());
         }
     }
@@ -369,11 +621,12 @@ mod tests {
             std::env::set_var("OPENAI_API_KEY", "test-key");
             std::env::remove_var("TRANSCRIPTION_PROVIDER"); // Default should be openai
 
-            let config = crate::config::load_config();
+            let config = Config::from_env().unwrap();
             assert_eq!(config.transcription_provider, "openai");
 
             let provider =
-                TranscriptionFactory::create_provider(&config.transcription_provider).await;
+                TranscriptionFactory::create_provider(&config.transcription_provider, &config)
+                    .await;
             assert!(provider.is_ok());
 
             // Restore original state
@@ -389,4 +642,29 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_factory_uses_given_config_not_environment() {
+        #[allow(clippy::await_holding_lock)]
+        {
+            let _lock = ENV_MUTEX.lock().await;
+
+            // e.g. a profile providing the key while the environment has none
+            let original_key = std::env::var("OPENAI_API_KEY").ok();
+            std::env::remove_var("OPENAI_API_KEY");
+
+            let config = Config {
+                openai_api_key: Some("profile-key".to_string()),
+                ..Config::default()
+            };
+            let result =
+                TranscriptionFactory::create_provider(&config.transcription_provider, &config)
+                    .await;
+
+            if let Some(key) = original_key {
+                std::env::set_var("OPENAI_API_KEY", key);
+            }
+            assert!(result.is_ok());
+        }
+    }
 }
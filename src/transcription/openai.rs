@@ -1,14 +1,59 @@
-use super::{TranscriptionError, TranscriptionProvider};
+use super::{
+    whisper_language_code, Segment, Transcript, TranscriptionError, TranscriptionProvider, Word,
+};
 use async_trait::async_trait;
 use serde_json::Value;
+use std::str::FromStr;
 use std::time::Duration;
 
+/// Response format requested from the transcription endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ResponseFormat {
+    /// `verbose_json` for `whisper-*` models, the endpoint default otherwise
+    #[default]
+    Auto,
+    Json,
+    /// Segments, words and detected language
+    VerboseJson,
+}
+
+impl FromStr for ResponseFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "json" => Ok(Self::Json),
+            "verbose_json" => Ok(Self::VerboseJson),
+            other => Err(format!(
+                "Unsupported response format '{}', expected auto, json or verbose_json",
+                other
+            )),
+        }
+    }
+}
+
+/// Optional request fields of Whisper-compatible endpoints
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestOptions {
+    /// Text that biases vocabulary and style, e.g. names and jargon
+    pub prompt: Option<String>,
+    /// Sampling temperature from 0.0 to 1.0, the endpoint default when unset
+    pub temperature: Option<f32>,
+    pub response_format: ResponseFormat,
+    /// Translate speech to English with `/audio/translations`
+    pub translate: bool,
+    /// Language codes automatic detection may choose, any language when empty
+    pub allowed_languages: Vec<String>,
+}
+
 pub struct OpenAIProvider {
     api_key: String,
     client: reqwest::Client,
     max_retries: u32,
     model: String,
     base_url: String,
+    options: RequestOptions,
 }
 
 impl OpenAIProvider {
@@ -37,15 +82,43 @@ impl OpenAIProvider {
             max_retries: max_retries.unwrap_or(3),
             model: model.unwrap_or_else(|| "whisper-1".to_string()),
             base_url: base_url.unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
+            options: RequestOptions::default(),
         })
     }
 
+    /// Send the given optional fields with every request
+    pub fn with_request_options(mut self, options: RequestOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Whether to request `verbose_json` with segment and word timestamps
+    /// In auto mode only `whisper-*` models ask for it, as the GPT-4o
+    /// transcription models only support `json` and `text`.
+    fn requests_verbose_json(&self) -> bool {
+        match self.options.response_format {
+            ResponseFormat::Auto => self.model.starts_with("whisper"),
+            ResponseFormat::Json => false,
+            ResponseFormat::VerboseJson => true,
+        }
+    }
+
+    /// Endpoint for the request, translations produce English text
+    fn endpoint(&self) -> String {
+        let path = if self.options.translate {
+            "translations"
+        } else {
+            "transcriptions"
+        };
+        format!("{}/audio/{}", self.base_url, path)
+    }
+
     async fn transcribe_attempt(
         &self,
         audio_data: &[u8],
         language: Option<&str>,
-    ) -> Result<String, TranscriptionError> {
-        let url = format!("{}/audio/transcriptions", self.base_url);
+    ) -> Result<Transcript, TranscriptionError> {
+        let url = self.endpoint();
 
         // Create multipart form
         let audio_part = reqwest::multipart::Part::bytes(audio_data.to_vec())
@@ -63,10 +136,31 @@ impl OpenAIProvider {
             .part("file", audio_part)
             .text("model", self.model.clone());
 
-        if let Some(lang) = language {
+        // Translations always produce English, the endpoint takes no language
+        if let Some(lang) = language.filter(|_| !self.options.translate) {
             form = form.text("language", lang.to_string());
         }
 
+        if let Some(prompt) = &self.options.prompt {
+            form = form.text("prompt", prompt.clone());
+        }
+
+        if let Some(temperature) = self.options.temperature {
+            form = form.text("temperature", temperature.to_string());
+        }
+
+        if self.requests_verbose_json() {
+            form = form.text("response_format", "verbose_json");
+            // Timestamp granularities are only accepted for transcriptions
+            if !self.options.translate {
+                form = form
+                    .text("timestamp_granularities[]", "segment")
+                    .text("timestamp_granularities[]", "word");
+            }
+        } else if self.options.response_format == ResponseFormat::Json {
+            form = form.text("response_format", "json");
+        }
+
         let response = self
             .client
             .post(&url)
@@ -103,7 +197,7 @@ impl OpenAIProvider {
             reqwest::StatusCode::OK => {
                 let json: Value = serde_json::from_str(&response_text)
                     .map_err(|e| TranscriptionError::JsonError(e.to_string()))?;
-                let text = json.get("text").and_then(|t| t.as_str()).ok_or_else(|| {
+                parse_transcript(&json).ok_or_else(|| {
                     TranscriptionError::ApiError(crate::transcription::ApiErrorDetails {
                         provider: "OpenAI".to_string(),
                         status_code: Some(200),
@@ -111,8 +205,7 @@ impl OpenAIProvider {
                         error_message: "No text field in response".to_string(),
                         raw_response: Some(response_text.clone()),
                     })
-                })?;
-                Ok(text.to_string())
+                })
             }
             reqwest::StatusCode::UNAUTHORIZED => Err(TranscriptionError::AuthenticationFailed {
                 provider: "OpenAI".to_string(),
@@ -152,25 +245,78 @@ impl OpenAIProvider {
     }
 }
 
-#[async_trait]
-impl TranscriptionProvider for OpenAIProvider {
-    async fn transcribe_with_language(
-        &self,
-        audio_data: Vec<u8>,
-        language: Option<String>,
-    ) -> Result<String, TranscriptionError> {
-        // Check file size (25MB limit for OpenAI Whisper API)
-        const MAX_FILE_SIZE: usize = 25 * 1024 * 1024;
-        if audio_data.len() > MAX_FILE_SIZE {
-            return Err(TranscriptionError::FileTooLarge(audio_data.len()));
-        }
+/// Build a transcript from a `json` or `verbose_json` response
+/// Segment confidence is derived from the average token log probability.
+fn parse_transcript(json: &Value) -> Option<Transcript> {
+    let text = json.get("text")?.as_str()?.to_string();
+
+    let segments: Vec<Segment> = json
+        .get("segments")
+        .and_then(Value::as_array)
+        .map(|segments| {
+            segments
+                .iter()
+                .filter_map(|segment| {
+                    Some(Segment {
+                        text: segment.get("text")?.as_str()?.trim().to_string(),
+                        start: segment.get("start")?.as_f64()?,
+                        end: segment.get("end")?.as_f64()?,
+                        confidence: segment
+                            .get("avg_logprob")
+                            .and_then(Value::as_f64)
+                            .map(|logprob| logprob.exp() as f32),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let words = json
+        .get("words")
+        .and_then(Value::as_array)
+        .map(|words| {
+            words
+                .iter()
+                .filter_map(|word| {
+                    Some(Word {
+                        text: word.get("word")?.as_str()?.trim().to_string(),
+                        start: word.get("start")?.as_f64()?,
+                        end: word.get("end")?.as_f64()?,
+                        confidence: None,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // verbose_json names the language, e.g. `english`, instead of giving its code
+    let language = json
+        .get("language")
+        .and_then(Value::as_str)
+        .map(|language| {
+            whisper_language_code(language).map_or_else(|| language.to_string(), str::to_string)
+        });
+
+    Some(Transcript {
+        text,
+        confidence: Transcript::segment_confidence(&segments),
+        segments,
+        words,
+        language,
+        language_probability: None,
+        provider: None,
+    })
+}
 
+impl OpenAIProvider {
+    async fn transcribe_with_retries(
+        &self,
+        audio_data: &[u8],
+        language: Option<&str>,
+    ) -> Result<Transcript, TranscriptionError> {
         let mut retries = 0;
         loop {
-            match self
-                .transcribe_attempt(&audio_data, language.as_deref())
-                .await
-            {
+            match self.transcribe_attempt(audio_data, language).await {
                 Ok(result) => return Ok(result),
                 Err(e) => {
                     retries += 1;
@@ -190,6 +336,62 @@ impl TranscriptionProvider for OpenAIProvider {
             }
         }
     }
+
+    /// Let the endpoint detect the language, keeping to the allowed languages
+    /// The API reports no probabilities to choose among the allowed languages
+    /// with, so a language outside the allow-list is replaced by transcribing
+    /// once more in the first allowed language. Only `verbose_json` responses
+    /// name the language; without one the allow-list cannot be applied.
+    async fn transcribe_detected(
+        &self,
+        audio_data: &[u8],
+    ) -> Result<Transcript, TranscriptionError> {
+        let allowed = &self.options.allowed_languages;
+        if let [only] = allowed.as_slice() {
+            return self.transcribe_with_retries(audio_data, Some(only)).await;
+        }
+
+        let transcript = self.transcribe_with_retries(audio_data, None).await?;
+        let Some(first) = allowed.first() else {
+            return Ok(transcript);
+        };
+        match &transcript.language {
+            Some(language) if allowed.contains(language) => Ok(transcript),
+            Some(_) => self.transcribe_with_retries(audio_data, Some(first)).await,
+            None => {
+                eprintln!(
+                    "⚠️ {} did not report the detected language, WHISPER_ALLOWED_LANGUAGES is not applied; set OPENAI_RESPONSE_FORMAT=verbose_json if the endpoint supports it",
+                    self.model
+                );
+                Ok(transcript)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl TranscriptionProvider for OpenAIProvider {
+    async fn transcribe_with_language(
+        &self,
+        audio_data: Vec<u8>,
+        language: Option<String>,
+    ) -> Result<Transcript, TranscriptionError> {
+        // Check file size (25MB limit for OpenAI Whisper API)
+        const MAX_FILE_SIZE: usize = 25 * 1024 * 1024;
+        if audio_data.len() > MAX_FILE_SIZE {
+            return Err(TranscriptionError::FileTooLarge(audio_data.len()));
+        }
+
+        match language {
+            Some(language) => {
+                self.transcribe_with_retries(&audio_data, Some(&language))
+                    .await
+            }
+            // Translations take no language, so there is nothing to restrict
+            None if self.options.translate => self.transcribe_with_retries(&audio_data, None).await,
+            None => self.transcribe_detected(&audio_data).await,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -272,4 +474,476 @@ mod tests {
         let result = rt.block_on(provider.transcribe_with_language(over_size_data, None));
         assert!(matches!(result, Err(TranscriptionError::FileTooLarge(_))));
     }
+
+    #[test]
+    fn test_parse_transcript_plain_json() {
+        let json = serde_json::json!({ "text": "Hello world" });
+        let transcript = parse_transcript(&json).unwrap();
+
+        assert_eq!(transcript.text, "Hello world");
+        assert!(transcript.segments.is_empty());
+        assert!(transcript.words.is_empty());
+        assert_eq!(transcript.language, None);
+        assert_eq!(transcript.confidence, None);
+        assert!(parse_transcript(&serde_json::json!({})).is_none());
+    }
+
+    #[test]
+    fn test_parse_transcript_verbose_json() {
+        let json = serde_json::json!({
+            "task": "transcribe",
+            "language": "english",
+            "duration": 2.0,
+            "text": "Hello world",
+            "segments": [
+                { "id": 0, "start": 0.0, "end": 2.0, "text": " Hello world", "avg_logprob": -0.1 }
+            ],
+            "words": [
+                { "word": "Hello", "start": 0.1, "end": 0.6 },
+                { "word": "world", "start": 0.7, "end": 1.2 }
+            ]
+        });
+        let transcript = parse_transcript(&json).unwrap();
+
+        assert_eq!(transcript.text, "Hello world");
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert_eq!(transcript.segments.len(), 1);
+        assert_eq!(transcript.segments[0].text, "Hello world");
+        assert_eq!(transcript.segments[0].end, 2.0);
+
+        let confidence = transcript.segments[0].confidence.unwrap();
+        assert!((confidence - (-0.1f32).exp()).abs() < 1e-6);
+        assert_eq!(transcript.confidence, Some(confidence));
+
+        assert_eq!(transcript.words.len(), 2);
+        assert_eq!(transcript.words[1].text, "world");
+        assert_eq!(transcript.words[1].start, 0.7);
+    }
+
+    #[tokio::test]
+    async fn test_whisper_model_requests_verbose_json() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/audio/transcriptions")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex("name=\"response_format\"\r\n\r\nverbose_json".to_string()),
+                mockito::Matcher::Regex(
+                    "name=\"timestamp_granularities\\[\\]\"\r\n\r\nword".to_string(),
+                ),
+            ]))
+            .with_status(200)
+            .with_body(r#"{"text": "hi", "language": "english", "segments": [], "words": []}"#)
+            .create_async()
+            .await;
+
+        let provider = OpenAIProvider::new_with_options(
+            "test-key".to_string(),
+            None,
+            Some(0),
+            None,
+            Some(server.url()),
+        )
+        .unwrap();
+        let transcript = provider
+            .transcribe_with_language(vec![0u8; 64], None)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(transcript.text, "hi");
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+    }
+
+    #[tokio::test]
+    async fn test_gpt4o_model_keeps_default_response_format() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/audio/transcriptions")
+            .match_body(mockito::Matcher::Regex("response_format".to_string()))
+            .expect(0)
+            .create_async()
+            .await;
+        let fallback = server
+            .mock("POST", "/audio/transcriptions")
+            .with_status(200)
+            .with_body(r#"{"text": "hi"}"#)
+            .create_async()
+            .await;
+
+        let provider = OpenAIProvider::new_with_options(
+            "test-key".to_string(),
+            None,
+            Some(0),
+            Some("gpt-4o-transcribe".to_string()),
+            Some(server.url()),
+        )
+        .unwrap();
+        let transcript = provider
+            .transcribe_with_language(vec![0u8; 64], None)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        fallback.assert_async().await;
+        assert_eq!(transcript.text, "hi");
+        assert!(transcript.segments.is_empty());
+    }
+
+    #[test]
+    fn test_response_format_from_str() {
+        assert_eq!("auto".parse(), Ok(ResponseFormat::Auto));
+        assert_eq!("JSON".parse(), Ok(ResponseFormat::Json));
+        assert_eq!(" verbose_json ".parse(), Ok(ResponseFormat::VerboseJson));
+        assert!("srt".parse::<ResponseFormat>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_prompt_and_temperature_are_sent() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/audio/transcriptions")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex("name=\"prompt\"\r\n\r\nwaystt, PipeWire".to_string()),
+                mockito::Matcher::Regex("name=\"temperature\"\r\n\r\n0.2".to_string()),
+                mockito::Matcher::Regex("name=\"language\"\r\n\r\nen".to_string()),
+            ]))
+            .with_status(200)
+            .with_body(r#"{"text": "waystt uses PipeWire"}"#)
+            .create_async()
+            .await;
+
+        let provider = OpenAIProvider::new_with_options(
+            "test-key".to_string(),
+            None,
+            Some(0),
+            None,
+            Some(server.url()),
+        )
+        .unwrap()
+        .with_request_options(RequestOptions {
+            prompt: Some("waystt, PipeWire".to_string()),
+            temperature: Some(0.2),
+            ..Default::default()
+        });
+        let transcript = provider
+            .transcribe_with_language(vec![0u8; 64], Some("en".to_string()))
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(transcript.text, "waystt uses PipeWire");
+    }
+
+    #[tokio::test]
+    async fn test_unset_options_are_not_sent() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/audio/transcriptions")
+            .match_body(mockito::Matcher::Regex(
+                "name=\"(prompt|temperature)\"".to_string(),
+            ))
+            .expect(0)
+            .create_async()
+            .await;
+        let fallback = server
+            .mock("POST", "/audio/transcriptions")
+            .with_status(200)
+            .with_body(r#"{"text": "hi"}"#)
+            .create_async()
+            .await;
+
+        let provider = OpenAIProvider::new_with_options(
+            "test-key".to_string(),
+            None,
+            Some(0),
+            None,
+            Some(server.url()),
+        )
+        .unwrap();
+        provider
+            .transcribe_with_language(vec![0u8; 64], None)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        fallback.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_json_response_format_skips_timestamps() {
+        let mut server = mockito::Server::new_async().await;
+        let with_timestamps = server
+            .mock("POST", "/audio/transcriptions")
+            .match_body(mockito::Matcher::Regex(
+                "timestamp_granularities".to_string(),
+            ))
+            .expect(0)
+            .create_async()
+            .await;
+        let mock = server
+            .mock("POST", "/audio/transcriptions")
+            .match_body(mockito::Matcher::Regex(
+                "name=\"response_format\"\r\n\r\njson\r\n".to_string(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"text": "hi"}"#)
+            .create_async()
+            .await;
+
+        let provider = OpenAIProvider::new_with_options(
+            "test-key".to_string(),
+            None,
+            Some(0),
+            Some("whisper-1".to_string()),
+            Some(server.url()),
+        )
+        .unwrap()
+        .with_request_options(RequestOptions {
+            response_format: ResponseFormat::Json,
+            ..Default::default()
+        });
+        provider
+            .transcribe_with_language(vec![0u8; 64], None)
+            .await
+            .unwrap();
+
+        with_timestamps.assert_async().await;
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_verbose_json_can_be_requested_for_any_model() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/audio/transcriptions")
+            .match_body(mockito::Matcher::Regex(
+                "name=\"response_format\"\r\n\r\nverbose_json".to_string(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"text": "hi", "segments": [{"start": 0.0, "end": 1.0, "text": "hi"}]}"#)
+            .create_async()
+            .await;
+
+        let provider = OpenAIProvider::new_with_options(
+            "test-key".to_string(),
+            None,
+            Some(0),
+            Some("large-v3".to_string()),
+            Some(server.url()),
+        )
+        .unwrap()
+        .with_request_options(RequestOptions {
+            response_format: ResponseFormat::VerboseJson,
+            ..Default::default()
+        });
+        let transcript = provider
+            .transcribe_with_language(vec![0u8; 64], None)
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(transcript.segments.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_translate_uses_translations_endpoint() {
+        let mut server = mockito::Server::new_async().await;
+        let transcriptions = server
+            .mock("POST", "/audio/transcriptions")
+            .expect(0)
+            .create_async()
+            .await;
+        let with_language = server
+            .mock("POST", "/audio/translations")
+            .match_body(mockito::Matcher::Regex(
+                "name=\"(language|timestamp_granularities\\[\\])\"".to_string(),
+            ))
+            .expect(0)
+            .create_async()
+            .await;
+        let translations = server
+            .mock("POST", "/audio/translations")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex("name=\"model\"\r\n\r\nwhisper-1".to_string()),
+                mockito::Matcher::Regex("name=\"response_format\"\r\n\r\nverbose_json".to_string()),
+            ]))
+            .with_status(200)
+            .with_body(r#"{"text": "Good morning", "language": "german"}"#)
+            .create_async()
+            .await;
+
+        let provider = OpenAIProvider::new_with_options(
+            "test-key".to_string(),
+            None,
+            Some(0),
+            None,
+            Some(server.url()),
+        )
+        .unwrap()
+        .with_request_options(RequestOptions {
+            translate: true,
+            ..Default::default()
+        });
+        let transcript = provider
+            .transcribe_with_language(vec![0u8; 64], Some("de".to_string()))
+            .await
+            .unwrap();
+
+        transcriptions.assert_async().await;
+        with_language.assert_async().await;
+        translations.assert_async().await;
+        assert_eq!(transcript.text, "Good morning");
+    }
+
+    fn language_mock_body(language: &str, text: &str, avg_logprob: f64) -> String {
+        serde_json::json!({
+            "text": text,
+            "language": language,
+            "segments": [{ "start": 0.0, "end": 1.0, "text": text, "avg_logprob": avg_logprob }]
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_language_retries_with_first_allowed_one() {
+        let mut server = mockito::Server::new_async().await;
+        let english = server
+            .mock("POST", "/audio/transcriptions")
+            .match_body(mockito::Matcher::Regex(
+                "name=\"language\"\r\n\r\nen\r".to_string(),
+            ))
+            .with_status(200)
+            .with_body(language_mock_body("english", "Hoi", -1.5))
+            .expect(1)
+            .create_async()
+            .await;
+        let german = server
+            .mock("POST", "/audio/transcriptions")
+            .match_body(mockito::Matcher::Regex(
+                "name=\"language\"\r\n\r\nde\r".to_string(),
+            ))
+            .with_status(200)
+            .with_body(language_mock_body("german", "Moin", -0.1))
+            .expect(0)
+            .create_async()
+            .await;
+        let detected = server
+            .mock("POST", "/audio/transcriptions")
+            .with_status(200)
+            .with_body(language_mock_body("dutch", "Moi", -0.3))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let provider = OpenAIProvider::new_with_options(
+            "test-key".to_string(),
+            None,
+            Some(0),
+            None,
+            Some(server.url()),
+        )
+        .unwrap()
+        .with_request_options(RequestOptions {
+            allowed_languages: vec!["en".to_string(), "de".to_string()],
+            ..Default::default()
+        });
+        let transcript = provider
+            .transcribe_with_language(vec![0u8; 64], None)
+            .await
+            .unwrap();
+
+        detected.assert_async().await;
+        english.assert_async().await;
+        german.assert_async().await;
+        assert_eq!(transcript.text, "Hoi");
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+    }
+
+    #[tokio::test]
+    async fn test_allowed_languages_need_a_detected_language() {
+        let mut server = mockito::Server::new_async().await;
+        // `json` responses carry no language, so there is nothing to check
+        let detected = server
+            .mock("POST", "/audio/transcriptions")
+            .with_status(200)
+            .with_body(r#"{"text": "Moi"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let provider = OpenAIProvider::new_with_options(
+            "test-key".to_string(),
+            None,
+            Some(0),
+            Some("gpt-4o-transcribe".to_string()),
+            Some(server.url()),
+        )
+        .unwrap()
+        .with_request_options(RequestOptions {
+            allowed_languages: vec!["en".to_string(), "de".to_string()],
+            ..Default::default()
+        });
+        let transcript = provider
+            .transcribe_with_language(vec![0u8; 64], None)
+            .await
+            .unwrap();
+
+        detected.assert_async().await;
+        assert_eq!(transcript.text, "Moi");
+        assert_eq!(transcript.language, None);
+    }
+
+    #[tokio::test]
+    async fn test_allowed_languages_restrict_detection() {
+        let mut server = mockito::Server::new_async().await;
+        let polish = server
+            .mock("POST", "/audio/transcriptions")
+            .match_body(mockito::Matcher::Regex(
+                "name=\"language\"\r\n\r\npl\r".to_string(),
+            ))
+            .with_status(200)
+            .with_body(language_mock_body("polish", "Dzień dobry", -0.2))
+            .expect(1)
+            .create_async()
+            .await;
+        let detected = server
+            .mock("POST", "/audio/transcriptions")
+            .with_status(200)
+            .with_body(language_mock_body("german", "Guten Tag", -0.2))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let provider = |allowed: &[&str]| {
+            OpenAIProvider::new_with_options(
+                "test-key".to_string(),
+                None,
+                Some(0),
+                None,
+                Some(server.url()),
+            )
+            .unwrap()
+            .with_request_options(RequestOptions {
+                allowed_languages: allowed.iter().map(|l| (*l).to_string()).collect(),
+                ..Default::default()
+            })
+        };
+
+        // A single allowed language is sent instead of detecting
+        let transcript = provider(&["pl"])
+            .transcribe_with_language(vec![0u8; 64], None)
+            .await
+            .unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("pl"));
+
+        // An allowed detection is kept without further requests
+        let transcript = provider(&["en", "de"])
+            .transcribe_with_language(vec![0u8; 64], None)
+            .await
+            .unwrap();
+        assert_eq!(transcript.language.as_deref(), Some("de"));
+
+        polish.assert_async().await;
+        detected.assert_async().await;
+    }
 }
@@ -0,0 +1,295 @@
+#![allow(clippy::cast_precision_loss)]
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_sign_loss)]
+
+use std::time::Duration;
+
+use crate::audio_processing::AudioProcessor;
+
+// Noise floor smoothing per 10ms frame: fall quickly when the room gets quieter,
+// rise slowly through background noise and barely at all while speech is present
+const FLOOR_FALL_RATE: f32 = 0.2;
+const FLOOR_RISE_RATE: f32 = 0.02;
+const FLOOR_SPEECH_RISE_RATE: f32 = 0.0005;
+
+const FRAME_MS: u32 = 10;
+
+/// Voice activity detection settings
+#[derive(Debug, Clone)]
+pub struct VadConfig {
+    /// Trailing silence required after speech before it is considered finished
+    pub hangover: Duration,
+    /// Continuous speech required before an utterance is considered started
+    pub min_speech: Duration,
+    /// Frames louder than the noise floor times this ratio count as speech
+    pub threshold_ratio: f32,
+    /// Absolute RMS a frame must exceed to count as speech
+    pub min_threshold: f32,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            hangover: Duration::from_millis(1500),
+            min_speech: Duration::from_millis(100),
+            threshold_ratio: 3.0,
+            min_threshold: 0.01,
+        }
+    }
+}
+
+/// Speech boundaries, as sample positions counted from the first processed sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VadEvent {
+    /// Speech started at this position
+    SpeechStart(u64),
+    /// Speech ended at this position, reported once the hangover has elapsed
+    SpeechEnd(u64),
+}
+
+/// Streaming voice activity detector for mono audio
+/// Classifies 10ms frames by RMS against an adaptive noise floor and only
+/// reports the end of speech after `hangover` of continuous silence.
+pub struct VoiceActivityDetector {
+    processor: AudioProcessor,
+    config: VadConfig,
+    frame_size: usize,
+    min_speech_frames: usize,
+    hangover_frames: usize,
+    noise_floor: Option<f32>,
+    speaking: bool,
+    speech_run: usize,
+    silence_run: usize,
+    position: u64,
+    pending: Vec<f32>,
+}
+
+impl VoiceActivityDetector {
+    /// Create a detector for mono audio at `sample_rate`
+    pub fn new(sample_rate: u32, config: VadConfig) -> Self {
+        let frame_size = (sample_rate as usize * FRAME_MS as usize / 1000).max(1);
+        let frames =
+            |duration: Duration| (duration.as_millis() as usize).div_ceil(FRAME_MS as usize);
+
+        Self {
+            processor: AudioProcessor::new(sample_rate),
+            min_speech_frames: frames(config.min_speech).max(1),
+            hangover_frames: frames(config.hangover).max(1),
+            config,
+            frame_size,
+            noise_floor: None,
+            speaking: false,
+            speech_run: 0,
+            silence_run: 0,
+            position: 0,
+            pending: Vec::with_capacity(frame_size),
+        }
+    }
+
+    /// Current noise floor estimate as RMS
+    pub fn noise_floor(&self) -> f32 {
+        self.noise_floor.unwrap_or(0.0)
+    }
+
+    /// Feed the next chunk of samples and return any speech boundaries it completes
+    pub fn process(&mut self, samples: &[f32]) -> Vec<VadEvent> {
+        let mut events = Vec::new();
+        let mut samples = samples;
+
+        // Complete a frame left over from the previous chunk first
+        if !self.pending.is_empty() {
+            let needed = (self.frame_size - self.pending.len()).min(samples.len());
+            self.pending.extend_from_slice(&samples[..needed]);
+            samples = &samples[needed..];
+            if self.pending.len() < self.frame_size {
+                return events;
+            }
+            let frame = std::mem::take(&mut self.pending);
+            self.process_frame(&frame, &mut events);
+            self.pending = frame;
+            self.pending.clear();
+        }
+
+        let mut frames = samples.chunks_exact(self.frame_size);
+        for frame in &mut frames {
+            self.process_frame(frame, &mut events);
+        }
+        self.pending.extend_from_slice(frames.remainder());
+
+        events
+    }
+
+    fn process_frame(&mut self, frame: &[f32], events: &mut Vec<VadEvent>) {
+        let rms = self.processor.calculate_rms(frame);
+        let floor = *self.noise_floor.get_or_insert(rms);
+        let threshold = (floor * self.config.threshold_ratio).max(self.config.min_threshold);
+        let is_speech = rms > threshold;
+
+        let rate = if rms < floor {
+            FLOOR_FALL_RATE
+        } else if is_speech {
+            FLOOR_SPEECH_RISE_RATE
+        } else {
+            FLOOR_RISE_RATE
+        };
+        self.noise_floor = Some(floor + (rms - floor) * rate);
+
+        let frame_len = self.frame_size as u64;
+        self.position += frame_len;
+
+        if is_speech {
+            self.speech_run += 1;
+            self.silence_run = 0;
+            if !self.speaking && self.speech_run >= self.min_speech_frames {
+                self.speaking = true;
+                let start = self.position - self.speech_run as u64 * frame_len;
+                events.push(VadEvent::SpeechStart(start));
+            }
+        } else {
+            self.speech_run = 0;
+            if self.speaking {
+                self.silence_run += 1;
+                if self.silence_run >= self.hangover_frames {
+                    self.speaking = false;
+                    let end = self.position - self.silence_run as u64 * frame_len;
+                    events.push(VadEvent::SpeechEnd(end));
+                    self.silence_run = 0;
+                }
+            }
+        }
+    }
+}
+
+/// Parse a duration such as `1500ms`, `1.5s` or `1500` (milliseconds)
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (number, millis_per_unit) = if let Some(ms) = value.strip_suffix("ms") {
+        (ms, 1.0)
+    } else if let Some(s) = value.strip_suffix('s') {
+        (s, 1000.0)
+    } else {
+        (value, 1.0)
+    };
+
+    match number.trim().parse::<f64>() {
+        Ok(n) if n.is_finite() && n > 0.0 => {
+            Ok(Duration::from_millis((n * millis_per_unit).round() as u64))
+        }
+        _ => Err(format!(
+            "invalid duration '{}', expected e.g. 1500ms or 1.5s",
+            value
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    /// Generate `ms` milliseconds of a 440Hz tone with the given peak amplitude
+    fn tone(ms: usize, amplitude: f32) -> Vec<f32> {
+        (0..RATE as usize * ms / 1000)
+            .map(|i| amplitude * (i as f32 * 440.0 * std::f32::consts::TAU / RATE as f32).sin())
+            .collect()
+    }
+
+    fn detector(hangover_ms: u64) -> VoiceActivityDetector {
+        VoiceActivityDetector::new(
+            RATE,
+            VadConfig {
+                hangover: Duration::from_millis(hangover_ms),
+                ..VadConfig::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_vad_silence_has_no_events() {
+        let mut vad = detector(300);
+        assert!(vad.process(&tone(2000, 0.001)).is_empty());
+        assert!(!vad.speaking);
+    }
+
+    #[test]
+    fn test_vad_detects_speech_and_hangover() {
+        let mut vad = detector(300);
+        assert!(vad.process(&tone(500, 0.001)).is_empty());
+
+        let events = vad.process(&tone(400, 0.3));
+        assert_eq!(events, vec![VadEvent::SpeechStart(8000)]);
+        assert!(vad.speaking);
+
+        // Silence shorter than the hangover keeps the utterance open
+        assert!(vad.process(&tone(200, 0.001)).is_empty());
+        assert!(vad.speaking);
+
+        let events = vad.process(&tone(200, 0.001));
+        assert_eq!(events, vec![VadEvent::SpeechEnd(14400)]);
+        assert!(!vad.speaking);
+    }
+
+    #[test]
+    fn test_vad_ignores_short_clicks() {
+        let mut vad = detector(300);
+        let mut samples = tone(500, 0.001);
+        samples.extend(tone(50, 0.5));
+        samples.extend(tone(500, 0.001));
+
+        assert!(vad.process(&samples).is_empty());
+    }
+
+    #[test]
+    fn test_vad_adapts_to_background_noise() {
+        let mut vad = detector(300);
+
+        // Steady fan noise well above the absolute threshold is not speech
+        assert!(vad.process(&tone(1000, 0.05)).is_empty());
+        assert!((vad.noise_floor() - 0.05 / 2f32.sqrt()).abs() < 0.005);
+
+        // Speech on top of it still is
+        let events = vad.process(&tone(300, 0.5));
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], VadEvent::SpeechStart(_)));
+    }
+
+    #[test]
+    fn test_vad_noise_floor_follows_quieter_room() {
+        let mut vad = detector(300);
+        vad.process(&tone(500, 0.05));
+        vad.process(&tone(500, 0.002));
+        assert!(vad.noise_floor() < 0.005);
+
+        // Speech quieter than the old noise level is now detected
+        let events = vad.process(&tone(300, 0.04));
+        assert!(matches!(events[..], [VadEvent::SpeechStart(_)]));
+    }
+
+    #[test]
+    fn test_vad_chunking_does_not_change_events() {
+        let mut samples = tone(300, 0.001);
+        samples.extend(tone(500, 0.3));
+        samples.extend(tone(600, 0.001));
+
+        let whole = detector(300).process(&samples);
+
+        let mut vad = detector(300);
+        let chunked: Vec<VadEvent> = samples.chunks(333).flat_map(|c| vad.process(c)).collect();
+
+        assert_eq!(whole.len(), 2);
+        assert_eq!(whole, chunked);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("1500ms"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("2s"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("800"), Ok(Duration::from_millis(800)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("0ms").is_err());
+        assert!(parse_duration("-1s").is_err());
+        assert!(parse_duration("soon").is_err());
+    }
+}
@@ -3,7 +3,8 @@
 #![allow(clippy::doc_markdown)]
 #![allow(clippy::cast_lossless)]
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use std::path::Path;
 
 /// WAV file encoder for converting f32 audio samples to 16-bit PCM WAV format
 /// Optimized for OpenAI Whisper API requirements: 16kHz mono, 16-bit PCM
@@ -75,6 +76,11 @@ impl WavEncoder {
         // Convert samples to i16 PCM
         let pcm_samples = self.convert_samples(samples);
 
+        Ok(self.encode_pcm_to_wav(&pcm_samples))
+    }
+
+    /// Wrap 16-bit PCM samples in a WAV header
+    pub fn encode_pcm_to_wav(&self, pcm_samples: &[i16]) -> Vec<u8> {
         // Generate WAV header
         let header = self.generate_header(pcm_samples.len());
 
@@ -87,8 +93,40 @@ impl WavEncoder {
             wav_data.extend_from_slice(&sample.to_le_bytes());
         }
 
-        Ok(wav_data)
+        wav_data
+    }
+}
+
+/// Interleaved audio decoded from a WAV file, scaled to [-1.0, 1.0]
+pub struct DecodedAudio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+/// Decode a WAV file with integer PCM of any bit depth or 32-bit float samples
+pub fn decode_wav_file(path: &Path) -> Result<DecodedAudio> {
+    let reader = hound::WavReader::open(path)
+        .map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+    let spec = reader.spec();
+
+    let samples = match spec.sample_format {
+        hound::SampleFormat::Float => reader.into_samples::<f32>().collect::<Result<Vec<_>, _>>(),
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1u64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .into_samples::<i32>()
+                .map(|s| s.map(|v| v as f32 * scale))
+                .collect::<Result<Vec<_>, _>>()
+        }
     }
+    .map_err(|e| anyhow!("Failed to decode {}: {}", path.display(), e))?;
+
+    Ok(DecodedAudio {
+        samples,
+        sample_rate: spec.sample_rate,
+        channels: spec.channels,
+    })
 }
 
 impl Default for WavEncoder {
@@ -232,6 +270,15 @@ mod tests {
         assert_eq!(wav_data.len(), 44 + 6);
     }
 
+    #[test]
+    fn test_encode_pcm_to_wav() {
+        let encoder = WavEncoder::default();
+        let wav_data = encoder.encode_pcm_to_wav(&[0, 1, -1]);
+
+        assert_eq!(wav_data.len(), 44 + 6);
+        assert_eq!(&wav_data[44..], &[0, 0, 1, 0, 0xff, 0xff]);
+    }
+
     #[test]
     fn test_wav_size_calculation() {
         // Test WAV size calculation manually since calculate_wav_size was removed
@@ -280,4 +327,67 @@ mod tests {
             u32::from_le_bytes([wav_data[40], wav_data[41], wav_data[42], wav_data[43]]);
         assert_eq!(data_size, 32000);
     }
+
+    fn write_wav<S: hound::Sample + Copy>(
+        spec: hound::WavSpec,
+        samples: &[S],
+    ) -> tempfile::NamedTempFile {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mut writer = hound::WavWriter::create(file.path(), spec).unwrap();
+        for &sample in samples {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+        file
+    }
+
+    #[test]
+    fn test_decode_wav_file_int16_stereo() {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let file = write_wav(spec, &[0i16, 16384, -32768, 8192]);
+
+        let decoded = decode_wav_file(file.path()).unwrap();
+        assert_eq!(decoded.sample_rate, 44100);
+        assert_eq!(decoded.channels, 2);
+        assert_eq!(decoded.samples, vec![0.0, 0.5, -1.0, 0.25]);
+    }
+
+    #[test]
+    fn test_decode_wav_file_int24_and_float() {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 24,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let file = write_wav(spec, &[4_194_304i32, -8_388_608]);
+        assert_eq!(
+            decode_wav_file(file.path()).unwrap().samples,
+            vec![0.5, -1.0]
+        );
+
+        let spec = hound::WavSpec {
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+            ..spec
+        };
+        let file = write_wav(spec, &[0.25f32, -0.75]);
+        assert_eq!(
+            decode_wav_file(file.path()).unwrap().samples,
+            vec![0.25, -0.75]
+        );
+    }
+
+    #[test]
+    fn test_decode_wav_file_rejects_invalid_files() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), b"not a wav file").unwrap();
+        assert!(decode_wav_file(file.path()).is_err());
+        assert!(decode_wav_file(Path::new("/nonexistent/audio.wav")).is_err());
+    }
 }